@@ -0,0 +1,103 @@
+//! UI tree from egui's AccessKit output
+//!
+//! Enabled with the `accesskit` cargo feature. AT-SPI only sees apps that
+//! run on a desktop session with an accessibility bus. [`McpPlugin`](crate::McpPlugin)
+//! converts the AccessKit update of each root frame into a [`UiTree`] so
+//! the server can get it over IPC (`GetUiTree`) instead. Node IDs are the
+//! AccessKit IDs, which are also the IDs AT-SPI reports for egui widgets.
+
+use egui::accesskit::{Node, NodeId, Role, Toggled, TreeUpdate};
+use egui_mcp_protocol::{NodeInfo, Rect, UiTree};
+
+/// Convert a full AccessKit tree update (as egui sends every frame) into a [`UiTree`]
+pub(crate) fn ui_tree(update: &TreeUpdate) -> UiTree {
+    let roots = match &update.tree {
+        Some(tree) => vec![tree.root.0],
+        None => update
+            .nodes
+            .first()
+            .map(|(id, _)| id.0)
+            .into_iter()
+            .collect(),
+    };
+    let nodes = update
+        .nodes
+        .iter()
+        .map(|(id, node)| node_info(*id, node, *id == update.focus))
+        .collect();
+    UiTree { roots, nodes }
+}
+
+fn node_info(id: NodeId, node: &Node, focused: bool) -> NodeInfo {
+    let role = node.role();
+    // egui puts the text of `Label` nodes into the value
+    let label = node
+        .label()
+        .or_else(|| (role == Role::Label).then(|| node.value()).flatten())
+        .or_else(|| node.description())
+        .map(str::to_string);
+    let value = node
+        .value()
+        .map(str::to_string)
+        .or_else(|| node.numeric_value().map(|v| v.to_string()));
+
+    NodeInfo {
+        id: id.0,
+        role: format!("{:?}", role),
+        label,
+        value,
+        bounds: node.bounds().map(|b| Rect {
+            x: b.x0 as f32,
+            y: b.y0 as f32,
+            width: b.width() as f32,
+            height: b.height() as f32,
+        }),
+        children: node.children().iter().map(|child| child.0).collect(),
+        toggled: node.toggled().map(|t| t != Toggled::False),
+        disabled: node.is_disabled(),
+        focused,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ui_tree_from_egui_frame() {
+        let ctx = egui::Context::default();
+        ctx.enable_accesskit();
+        let output = ctx.run(egui::RawInput::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.label("Status: ready");
+                let _ = ui.button("Save");
+                ui.checkbox(&mut true, "Autosave");
+                ui.add(egui::Slider::new(&mut 0.5, 0.0..=1.0));
+            });
+        });
+        let tree = ui_tree(output.platform_output.accesskit_update.as_ref().unwrap());
+
+        assert_eq!(tree.roots.len(), 1);
+        assert!(tree.get(tree.roots[0]).is_some());
+
+        let label = &tree.find_by_label("Status: ready", true)[0];
+        assert_eq!(label.role, "Label");
+
+        let button = &tree.find_by_label("Save", true)[0];
+        assert_eq!(button.role, "Button");
+        let bounds = button.bounds.unwrap();
+        assert!(bounds.width > 0.0 && bounds.height > 0.0);
+
+        let checkbox = &tree.find_by_role("CheckBox")[0];
+        assert_eq!(checkbox.label.as_deref(), Some("Autosave"));
+        assert_eq!(checkbox.toggled, Some(true));
+
+        let slider = &tree.find_by_role("Slider")[0];
+        assert_eq!(slider.value.as_deref(), Some("0.5"));
+
+        // Every child is a node of the tree
+        for node in &tree.nodes {
+            assert!(node.children.iter().all(|child| tree.get(*child).is_some()));
+        }
+    }
+}
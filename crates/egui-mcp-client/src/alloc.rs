@@ -0,0 +1,166 @@
+//! Counting global allocator for memory tracking
+//!
+//! Enabled with the `alloc-tracking` cargo feature. Install [`McpAllocator`]
+//! as the global allocator to report live heap bytes and per-frame
+//! allocation churn through `GetMemoryStats` and `PerfReport`:
+//!
+//! ```rust,ignore
+//! #[global_allocator]
+//! static ALLOC: egui_mcp_client::McpAllocator = egui_mcp_client::McpAllocator::system();
+//! ```
+//!
+//! Without the feature (or without installing the allocator) the heap
+//! statistics are simply not reported.
+
+#[cfg(feature = "alloc-tracking")]
+use std::alloc::{GlobalAlloc, Layout, System};
+#[cfg(feature = "alloc-tracking")]
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[cfg(feature = "alloc-tracking")]
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+#[cfg(feature = "alloc-tracking")]
+static DEALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+#[cfg(feature = "alloc-tracking")]
+static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+#[cfg(feature = "alloc-tracking")]
+static FREED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// Global allocator wrapper that counts allocations and bytes
+///
+/// Wraps [`System`] by default; use [`McpAllocator::new`] to wrap another
+/// allocator.
+#[cfg(feature = "alloc-tracking")]
+pub struct McpAllocator<A = System> {
+    inner: A,
+}
+
+#[cfg(feature = "alloc-tracking")]
+impl McpAllocator<System> {
+    /// Count allocations of the system allocator
+    pub const fn system() -> Self {
+        Self { inner: System }
+    }
+}
+
+#[cfg(feature = "alloc-tracking")]
+impl<A> McpAllocator<A> {
+    /// Count allocations of the given allocator
+    pub const fn new(inner: A) -> Self {
+        Self { inner }
+    }
+}
+
+#[cfg(feature = "alloc-tracking")]
+fn count_alloc(size: usize) {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    ALLOCATED_BYTES.fetch_add(size as u64, Ordering::Relaxed);
+}
+
+#[cfg(feature = "alloc-tracking")]
+fn count_dealloc(size: usize) {
+    DEALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    FREED_BYTES.fetch_add(size as u64, Ordering::Relaxed);
+}
+
+// SAFETY: all calls are forwarded to the wrapped allocator unchanged; the
+// wrapper only updates atomic counters, which never allocates.
+#[cfg(feature = "alloc-tracking")]
+unsafe impl<A: GlobalAlloc> GlobalAlloc for McpAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { self.inner.alloc(layout) };
+        if !ptr.is_null() {
+            count_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { self.inner.alloc_zeroed(layout) };
+        if !ptr.is_null() {
+            count_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { self.inner.dealloc(ptr, layout) };
+        count_dealloc(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = unsafe { self.inner.realloc(ptr, layout, new_size) };
+        if !new_ptr.is_null() {
+            // Counted as freeing the old block and allocating a new one
+            count_dealloc(layout.size());
+            count_alloc(new_size);
+        }
+        new_ptr
+    }
+}
+
+/// Snapshot of the allocation counters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct AllocCounters {
+    /// Total number of allocations
+    pub allocations: u64,
+    /// Total number of deallocations
+    pub deallocations: u64,
+    /// Total bytes allocated
+    pub allocated_bytes: u64,
+    /// Total bytes freed
+    pub freed_bytes: u64,
+}
+
+impl AllocCounters {
+    /// Bytes currently allocated
+    pub fn live_bytes(&self) -> u64 {
+        self.allocated_bytes.saturating_sub(self.freed_bytes)
+    }
+
+    /// Allocations not freed yet
+    pub fn live_allocations(&self) -> u64 {
+        self.allocations.saturating_sub(self.deallocations)
+    }
+}
+
+/// Read the allocation counters
+///
+/// Returns `None` unless the `alloc-tracking` feature is enabled and
+/// [`McpAllocator`] is the global allocator (i.e. something was counted).
+#[cfg(feature = "alloc-tracking")]
+pub(crate) fn counters() -> Option<AllocCounters> {
+    let counters = AllocCounters {
+        allocations: ALLOCATIONS.load(Ordering::Relaxed),
+        deallocations: DEALLOCATIONS.load(Ordering::Relaxed),
+        allocated_bytes: ALLOCATED_BYTES.load(Ordering::Relaxed),
+        freed_bytes: FREED_BYTES.load(Ordering::Relaxed),
+    };
+    (counters.allocations > 0).then_some(counters)
+}
+
+/// Read the allocation counters (always `None` without `alloc-tracking`)
+#[cfg(not(feature = "alloc-tracking"))]
+pub(crate) fn counters() -> Option<AllocCounters> {
+    None
+}
+
+#[cfg(all(test, feature = "alloc-tracking"))]
+mod tests {
+    use super::*;
+
+    #[global_allocator]
+    static ALLOC: McpAllocator = McpAllocator::system();
+
+    #[test]
+    fn test_counts_allocations() {
+        let before = counters().unwrap();
+        let data = vec![0u8; 1 << 20];
+        let during = counters().unwrap();
+        drop(data);
+
+        assert!(during.allocations > before.allocations);
+        assert!(during.allocated_bytes - before.allocated_bytes >= 1 << 20);
+        assert!(counters().unwrap().freed_bytes - before.freed_bytes >= 1 << 20);
+    }
+}
@@ -0,0 +1,118 @@
+//! Panic hook that records crashes for the MCP server
+//!
+//! When the application panics, the socket closes and the server can no
+//! longer ask the app what happened. The hook installed by
+//! [`McpClient::install_panic_hook`](crate::McpClient::install_panic_hook)
+//! writes a [`CrashReport`] to the crash file next to the socket, which the
+//! server reads from `check_connection` and `get_last_crash`.
+
+use egui_mcp_protocol::CrashReport;
+use std::any::Any;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+
+/// Install a panic hook that writes a crash report to `crash_path`
+///
+/// The previously installed hook still runs afterwards, so the usual panic
+/// message is printed as before.
+pub(crate) fn install_panic_hook(crash_path: PathBuf) {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        record_crash(&crash_path, info);
+        previous(info);
+    }));
+}
+
+/// Write the crash report for a panic to `crash_path`
+fn record_crash(crash_path: &Path, info: &PanicHookInfo<'_>) {
+    let report = crash_report(info);
+    if let Err(e) = write_crash_report(crash_path, &report) {
+        eprintln!("Failed to write crash report to {:?}: {}", crash_path, e);
+    }
+}
+
+/// Build the crash report for a panic
+fn crash_report(info: &PanicHookInfo<'_>) -> CrashReport {
+    CrashReport {
+        message: payload_message(info.payload()),
+        location: info
+            .location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column())),
+        thread: std::thread::current().name().map(str::to_string),
+        backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+        timestamp_ms: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0),
+        pid: std::process::id(),
+    }
+}
+
+/// Get the message of a panic payload (`panic!` produces `&str` or `String`)
+fn payload_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "Box<dyn Any>".to_string()
+    }
+}
+
+fn write_crash_report(path: &Path, report: &CrashReport) -> std::io::Result<()> {
+    let json = serde_json::to_vec_pretty(report).map_err(std::io::Error::other)?;
+    std::fs::write(path, json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_message() {
+        let payload: Box<dyn Any + Send> = Box::new("static message");
+        assert_eq!(payload_message(payload.as_ref()), "static message");
+        let payload: Box<dyn Any + Send> = Box::new(format!("formatted {}", 42));
+        assert_eq!(payload_message(payload.as_ref()), "formatted 42");
+        let payload: Box<dyn Any + Send> = Box::new(42);
+        assert_eq!(payload_message(payload.as_ref()), "Box<dyn Any>");
+    }
+
+    #[test]
+    fn test_panic_writes_crash_file() {
+        let crash_path =
+            std::env::temp_dir().join(format!("egui-mcp-test-{}.crash.json", std::process::id()));
+        let _ = std::fs::remove_file(&crash_path);
+
+        // The panic hook is process-wide: only record this test's panic,
+        // pass the others on, and restore the previous hook afterwards
+        let previous: std::sync::Arc<dyn Fn(&PanicHookInfo<'_>) + Send + Sync> =
+            std::panic::take_hook().into();
+        let hook_previous = previous.clone();
+        let hook_path = crash_path.clone();
+        std::panic::set_hook(Box::new(move |info| {
+            if std::thread::current().name() == Some("crashing") {
+                record_crash(&hook_path, info);
+            } else {
+                hook_previous(info);
+            }
+        }));
+
+        let result = std::thread::Builder::new()
+            .name("crashing".to_string())
+            .spawn(|| panic!("boom {}", 1))
+            .unwrap()
+            .join();
+        let _ = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| previous(info)));
+        assert!(result.is_err());
+
+        let report: CrashReport =
+            serde_json::from_slice(&std::fs::read(&crash_path).unwrap()).unwrap();
+        std::fs::remove_file(&crash_path).unwrap();
+        assert_eq!(report.message, "boom 1");
+        assert_eq!(report.thread.as_deref(), Some("crashing"));
+        assert!(report.location.unwrap().contains("crash.rs"));
+        assert_eq!(report.pid, std::process::id());
+    }
+}
@@ -0,0 +1,208 @@
+//! In-process app harness serving the IPC protocol
+//!
+//! Enabled with the `headless` cargo feature. [`HeadlessHarness`] drives an
+//! `egui::Context` with a UI closure on a timer, without a window, GPU or
+//! D-Bus session. It wires up everything a windowed app does by hand:
+//!
+//! - Injected input and input recording (`take_viewport_inputs_sync`)
+//! - Screenshots rendered by [`SoftwareRenderer`](crate::SoftwareRenderer)
+//! - The UI tree from AccessKit (`GetUiTree`, used by the server's tree tools)
+//! - Frame timings, paint stats, idle state, time control, highlights and
+//!   context inspection
+//!
+//! Logs work as usual: install [`McpLogLayer`](crate::McpLogLayer) and pass
+//! its buffer to the client.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use egui_mcp_client::{HeadlessHarness, McpClient};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut counter = 0;
+//!     HeadlessHarness::new(McpClient::new())
+//!         .with_size(800.0, 600.0)
+//!         .run(move |ctx| {
+//!             egui::CentralPanel::default().show(ctx, |ui| {
+//!                 if ui.button("Increment").clicked() {
+//!                     counter += 1;
+//!                 }
+//!                 ui.label(format!("Counter: {}", counter));
+//!             });
+//!         })
+//!         .await;
+//! }
+//! ```
+
+use crate::{McpClient, McpPlugin, draw_highlights, inject_inputs};
+use std::time::{Duration, Instant};
+
+/// Runs a UI closure headlessly and serves it to the MCP server
+pub struct HeadlessHarness {
+    client: McpClient,
+    ctx: egui::Context,
+    /// Screen size in points
+    size: egui::Vec2,
+    pixels_per_point: f32,
+    frame_interval: Duration,
+    start: Instant,
+}
+
+impl HeadlessHarness {
+    /// Create a harness for the client (800x600 points at 60 frames per second)
+    ///
+    /// Installs [`McpPlugin`] with software screenshots and enables AccessKit
+    /// on a new context.
+    pub fn new(client: McpClient) -> Self {
+        let ctx = egui::Context::default();
+        ctx.enable_accesskit();
+        ctx.add_plugin(McpPlugin::new(client.clone()).with_software_screenshots(true));
+        Self {
+            client,
+            ctx,
+            size: egui::vec2(800.0, 600.0),
+            pixels_per_point: 1.0,
+            frame_interval: Duration::from_micros(16_667),
+            start: Instant::now(),
+        }
+    }
+
+    /// Set the screen size in points
+    pub fn with_size(mut self, width: f32, height: f32) -> Self {
+        self.size = egui::vec2(width, height);
+        self
+    }
+
+    /// Set the native pixels per point (screenshots are scaled accordingly)
+    pub fn with_pixels_per_point(mut self, pixels_per_point: f32) -> Self {
+        self.pixels_per_point = pixels_per_point;
+        self
+    }
+
+    /// Set the time between frames in [`run`](Self::run)
+    pub fn with_frame_interval(mut self, interval: Duration) -> Self {
+        self.frame_interval = interval;
+        self
+    }
+
+    /// The context the UI runs in
+    pub fn context(&self) -> &egui::Context {
+        &self.ctx
+    }
+
+    /// The client serving this harness
+    pub fn client(&self) -> &McpClient {
+        &self.client
+    }
+
+    /// Run a single frame of the UI
+    ///
+    /// Useful to drive the UI step by step in tests; [`run`](Self::run)
+    /// calls it on a timer.
+    pub fn run_frame(&mut self, mut ui: impl FnMut(&egui::Context)) -> egui::FullOutput {
+        let mut raw_input = egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(egui::Pos2::ZERO, self.size)),
+            time: Some(self.start.elapsed().as_secs_f64()),
+            predicted_dt: self.frame_interval.as_secs_f32(),
+            focused: true,
+            ..Default::default()
+        };
+        raw_input
+            .viewports
+            .entry(egui::ViewportId::ROOT)
+            .or_default()
+            .native_pixels_per_point = Some(self.pixels_per_point);
+
+        self.client
+            .apply_time_control_sync(&self.ctx, &mut raw_input);
+        let inputs = self.client.take_viewport_inputs_sync(&raw_input);
+        inject_inputs(&self.ctx, &mut raw_input, inputs);
+
+        let client = &self.client;
+        self.ctx.run(raw_input, |ctx| {
+            ui(ctx);
+
+            let highlights = client.get_highlights_sync();
+            draw_highlights(ctx, &highlights);
+            client.handle_inspect_sync(ctx);
+            client.record_idle_state_sync(ctx);
+            client.record_frame_auto_sync();
+        })
+    }
+
+    /// Start the IPC server and run frames on the timer forever
+    ///
+    /// Must be called within a tokio runtime. Drop (or abort) the future to stop.
+    pub async fn run(mut self, mut ui: impl FnMut(&egui::Context)) {
+        let server = self.client.start_server();
+        let mut interval = tokio::time::interval(self.frame_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        // Stops the server when the harness future is dropped
+        struct AbortOnDrop(tokio::task::JoinHandle<()>);
+        impl Drop for AbortOnDrop {
+            fn drop(&mut self) {
+                self.0.abort();
+            }
+        }
+        let _server = AbortOnDrop(server);
+
+        loop {
+            interval.tick().await;
+            self.run_frame(&mut ui);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PendingInput;
+    use egui_mcp_protocol::MouseButton;
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn test_harness_serves_tree_input_and_screenshots() {
+        let mut harness = HeadlessHarness::new(McpClient::new()).with_size(200.0, 100.0);
+        let client = harness.client().clone();
+        let clicks = std::cell::Cell::new(0);
+        let mut ui = |ctx: &egui::Context| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                if ui.button("Increment").clicked() {
+                    clicks.set(clicks.get() + 1);
+                }
+            });
+        };
+
+        harness.run_frame(&mut ui);
+        let tree = block_on(client.get_ui_tree()).unwrap();
+        let button = tree.find_by_label("Increment", true)[0].bounds.unwrap();
+
+        // Clicks injected like the IPC server does
+        block_on(client.queue_input(PendingInput::Click {
+            x: button.x + button.width / 2.0,
+            y: button.y + button.height / 2.0,
+            button: MouseButton::Left,
+        }));
+        for _ in 0..3 {
+            harness.run_frame(&mut ui);
+        }
+        assert_eq!(clicks.get(), 1);
+
+        let mut rx = block_on(client.request_screenshot());
+        harness.run_frame(&mut ui);
+        let png = rx.try_recv().unwrap();
+        let image = image::load_from_memory(&png).unwrap();
+        assert_eq!((image.width(), image.height()), (200, 100));
+
+        let stats = block_on(client.get_frame_stats());
+        assert!(stats.sample_count >= 4);
+    }
+}
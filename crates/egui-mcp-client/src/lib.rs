@@ -8,30 +8,86 @@
 //! - Scroll events
 //!
 //! Note: UI tree access and element-based interactions are handled via AT-SPI
-//! on the server side and don't require this client library.
+//! on the server side and don't require this client library. Apps without
+//! AT-SPI can serve their AccessKit tree with the `accesskit` feature, and
+//! the `headless` feature adds [`HeadlessHarness`] to run a UI without a window.
 //!
 //! ## Usage in raw_input_hook
 //!
 //! ```rust,ignore
 //! impl eframe::App for MyApp {
 //!     fn raw_input_hook(&mut self, ctx: &egui::Context, raw_input: &mut egui::RawInput) {
-//!         let inputs = self.runtime.block_on(self.mcp_client.take_pending_inputs());
+//!         let inputs = self.mcp_client.take_pending_inputs_sync();
 //!         egui_mcp_client::inject_inputs(ctx, raw_input, inputs);
 //!     }
 //! }
 //! ```
+//!
+//! ## Synchronous frame-side API
+//!
+//! The `*_sync` methods never await and do not need a tokio runtime, so they
+//! can be called directly from `update` and `raw_input_hook`. Only the IPC
+//! server task needs tokio.
+//!
+//! ```rust,ignore
+//! fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+//!     if self.mcp_client.take_screenshot_request_sync() {
+//!         ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot(egui::UserData::default()));
+//!     }
+//!     // ... on egui::Event::Screenshot: self.mcp_client.set_screenshot_sync(png_data);
+//!
+//!     let highlights = self.mcp_client.get_highlights_sync();
+//!     egui_mcp_client::draw_highlights(ctx, &highlights);
+//!     self.mcp_client.record_frame_auto_sync();
+//! }
+//! ```
 
+use parking_lot::RwLock;
 use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::{RwLock, oneshot};
-
-pub use egui_mcp_protocol::{FrameStats, LogEntry, MouseButton, PerfReport, Request, Response};
-
+use tokio::sync::oneshot;
+
+pub use egui_mcp_protocol::{
+    BudgetCount, ContextInfo, CrashReport, DEFAULT_FRAME_BUDGETS_MS, FrameShapes, FrameStats,
+    HeapStats, HistogramBucket, IdleState, InputRecording, JankStreak, LatencyReport, LogEntry,
+    LogSpan, MemoryStats, MemorySummary, MouseButton, NodeInfo, PaintStats, PaintSummary,
+    PaintedShape, PerfReport, RecordedFrame, RecordedStep, RepaintCauseCount, Request, Response,
+    TextRun, TextureMemory, TraceEvent, UiTree, VectorShape, VectorStroke, WidgetRef,
+};
+
+#[cfg(feature = "accesskit")]
+mod accesskit_tree;
+mod alloc;
+mod crash;
+#[cfg(feature = "headless")]
+mod harness;
+mod log_file;
 mod log_layer;
-mod server;
+mod plugin;
+mod profile_layer;
+mod recorder;
+mod screen_recorder;
 
-pub use log_layer::{DEFAULT_MAX_MESSAGE_LENGTH, LogBuffer, McpLogLayer, level_to_priority};
+use recorder::InputRecorder;
+use screen_recorder::ScreenRecorder;
+mod server;
+#[cfg(feature = "software-renderer")]
+mod software_renderer;
+mod vector;
+
+#[cfg(feature = "alloc-tracking")]
+pub use alloc::McpAllocator;
+#[cfg(feature = "headless")]
+pub use harness::HeadlessHarness;
+pub use log_file::LogFileSink;
+pub use log_layer::{
+    DEFAULT_MAX_MESSAGE_LENGTH, LogBuffer, LogFilter, LogQuery, McpLogLayer, level_to_priority,
+};
+pub use plugin::McpPlugin;
+pub use profile_layer::{McpProfileLayer, ProfileBuffer};
 pub use server::IpcServer;
+#[cfg(feature = "software-renderer")]
+pub use software_renderer::SoftwareRenderer;
 
 // Re-export egui types for convenience
 pub use egui;
@@ -76,6 +132,10 @@ pub struct Highlight {
 }
 
 /// Shared state for the MCP client
+///
+/// The state is guarded by a `parking_lot` lock that is never held across an
+/// `.await`, so the UI thread can use the `*_sync` methods without a tokio
+/// runtime. The `async` methods are kept for the IPC server task.
 #[derive(Clone)]
 pub struct McpClient {
     state: Arc<RwLock<ClientState>>,
@@ -85,12 +145,24 @@ struct ClientState {
     socket_path: PathBuf,
     /// Pending screenshot request sender (event-driven)
     screenshot_sender: Option<oneshot::Sender<Vec<u8>>>,
-    /// Pending input events to be processed by the egui app
-    pending_inputs: Vec<PendingInput>,
+    /// Target viewport of the pending screenshot request (None = root)
+    screenshot_viewport: Option<u64>,
+    /// Whether `ViewportCommand::Screenshot` was already sent for the pending request
+    screenshot_command_sent: bool,
+    /// Pending input events with their target viewport (None = root)
+    pending_inputs: Vec<(Option<u64>, PendingInput)>,
+    /// Last known viewports (from `RawInput::viewports`)
+    viewports: egui::ViewportIdMap<egui::ViewportInfo>,
     /// Active highlights to be drawn
     highlights: Vec<Highlight>,
     /// Optional log buffer (shared with McpLogLayer)
     log_buffer: Option<LogBuffer>,
+    /// Optional reloadable log filter (shared with McpLogLayer)
+    log_filter: Option<LogFilter>,
+    /// Optional persisted log history (shared with McpLogLayer)
+    log_file: Option<LogFileSink>,
+    /// Optional profile buffer (shared with McpProfileLayer)
+    profile_buffer: Option<ProfileBuffer>,
     /// Frame times for performance monitoring (rolling window)
     frame_times: std::collections::VecDeque<std::time::Duration>,
     /// Maximum number of frame times to keep
@@ -99,6 +171,98 @@ struct ClientState {
     perf_recording: Option<PerfRecording>,
     /// Last frame instant for automatic timing
     last_frame_instant: Option<std::time::Instant>,
+    /// Paint statistics of the last frame (from `McpPlugin`)
+    last_paint_stats: Option<PaintStats>,
+    /// Allocation counters at the end of the last frame
+    last_frame_allocs: Option<alloc::AllocCounters>,
+    /// Allocation churn of the last frame as (allocations, bytes)
+    frame_alloc_churn: Option<(u64, u64)>,
+    /// Texture memory of the root viewport (from `McpPlugin`)
+    texture_memory: Option<TextureMemory>,
+    /// Deterministic time control (None = real time)
+    time_control: Option<TimeControl>,
+    /// Repaint activity for idle detection
+    idle: IdleTracker,
+    /// Pending context inspection requests with their target viewport (None = root)
+    inspect_requests: Vec<(Option<u64>, oneshot::Sender<ContextInfo>)>,
+    /// Active input latency measurement
+    latency_probe: Option<LatencyProbe>,
+    /// Active recording of real user input
+    input_recorder: Option<InputRecorder>,
+    /// UI tree of the last root frame (from AccessKit, see [`McpPlugin`])
+    ui_tree: Option<UiTree>,
+    /// Pending frame shape requests with their target viewport (None = root)
+    shape_requests: Vec<(Option<u64>, oneshot::Sender<FrameShapes>)>,
+    /// Last captured frame shapes, paged out by `GetFrameShapes`
+    frame_shapes: Option<FrameShapes>,
+    /// Active screen recording
+    screen_recorder: Option<ScreenRecorder>,
+    /// Frames of the stopped screen recording, oldest first
+    recorded_frames: std::collections::VecDeque<screen_recorder::EncodedFrame>,
+}
+
+/// State of an input latency measurement
+struct LatencyProbe {
+    /// Target viewport of the input (None = root)
+    viewport_id: Option<u64>,
+    /// Input to inject, held back until the baseline frame was captured
+    input: Option<PendingInput>,
+    /// Output shapes of the frame before the input was injected
+    baseline: Option<Vec<egui::epaint::ClippedShape>>,
+    /// Index of the last output frame of the target viewport (baseline = 0)
+    frame: u64,
+    /// When the input was made available to the app
+    injected_at: Option<std::time::Instant>,
+    /// Frame index and time at which the app took the input
+    consumed: Option<(u64, std::time::Instant)>,
+    /// Notified once the output changed
+    sender: Option<oneshot::Sender<LatencyReport>>,
+}
+
+impl LatencyProbe {
+    /// Build the report, with the time of the changed frame if there was one
+    fn report(&self, changed_at: Option<std::time::Instant>) -> LatencyReport {
+        let since_injection = |t: std::time::Instant| {
+            self.injected_at
+                .map(|injected| t.saturating_duration_since(injected).as_secs_f32() * 1000.0)
+        };
+        LatencyReport {
+            changed: changed_at.is_some(),
+            consumed_frame: self.consumed.map(|(frame, _)| frame),
+            consume_ms: self.consumed.and_then(|(_, t)| since_injection(t)),
+            changed_frame: changed_at.map(|_| self.frame),
+            frames: changed_at
+                .and(self.consumed)
+                .map(|(frame, _)| self.frame - frame + 1),
+            latency_ms: changed_at.and_then(since_injection),
+        }
+    }
+}
+
+/// State for frozen or stepped time
+#[derive(Default)]
+struct TimeControl {
+    /// Virtual time reported to egui in seconds (None until the first frozen frame)
+    time: Option<f64>,
+    /// Remaining frames to step
+    remaining_steps: u32,
+    /// Time advanced per stepped frame in seconds
+    step_dt: f32,
+    /// Notified with the virtual time once the last step was applied
+    step_sender: Option<oneshot::Sender<f64>>,
+}
+
+/// Repaint activity of the root viewport
+#[derive(Default)]
+struct IdleTracker {
+    /// Number of frames recorded
+    frame_count: u64,
+    /// Consecutive frames without a requested repaint
+    idle_frames: u32,
+    /// Whether the last frame requested an immediate repaint
+    repaint_requested: bool,
+    /// When the last frame was recorded
+    last_frame: Option<std::time::Instant>,
 }
 
 /// State for an active performance recording session
@@ -107,8 +271,26 @@ struct PerfRecording {
     start_time: std::time::Instant,
     /// Recorded frame times
     frame_times: Vec<std::time::Duration>,
+    /// When each recorded frame ended (for jank streak timestamps)
+    frame_ends: Vec<std::time::Instant>,
+    /// Frame time budgets in milliseconds, smallest first
+    budgets_ms: Vec<f32>,
     /// Optional auto-stop after duration
     duration_ms: u64,
+    /// Recorded paint statistics (from `McpPlugin`)
+    paint_stats: Vec<PaintStats>,
+    /// Recorded heap samples (with the counting allocator)
+    memory_samples: Vec<MemorySample>,
+}
+
+/// Heap usage at the end of a recorded frame
+struct MemorySample {
+    /// Bytes currently allocated
+    live_bytes: u64,
+    /// Allocations during the frame
+    allocations: u64,
+    /// Bytes allocated during the frame
+    allocated_bytes: u64,
 }
 
 impl McpClient {
@@ -123,35 +305,99 @@ impl McpClient {
             state: Arc::new(RwLock::new(ClientState {
                 socket_path,
                 screenshot_sender: None,
+                screenshot_viewport: None,
+                screenshot_command_sent: false,
                 pending_inputs: Vec::new(),
+                viewports: Default::default(),
                 highlights: Vec::new(),
                 log_buffer: None,
+                log_filter: None,
+                log_file: None,
+                profile_buffer: None,
                 frame_times: std::collections::VecDeque::with_capacity(120),
                 max_frame_samples: 120, // ~2 seconds at 60fps
                 perf_recording: None,
                 last_frame_instant: None,
+                last_paint_stats: None,
+                last_frame_allocs: None,
+                frame_alloc_churn: None,
+                texture_memory: None,
+                time_control: None,
+                idle: IdleTracker::default(),
+                inspect_requests: Vec::new(),
+                latency_probe: None,
+                input_recorder: None,
+                ui_tree: None,
+                shape_requests: Vec::new(),
+                frame_shapes: None,
+                screen_recorder: None,
+                recorded_frames: std::collections::VecDeque::new(),
             })),
         }
     }
 
     /// Set the log buffer (from McpLogLayer::new())
     pub async fn with_log_buffer(self, buffer: LogBuffer) -> Self {
-        self.state.write().await.log_buffer = Some(buffer);
-        self
+        self.with_log_buffer_sync(buffer)
     }
 
     /// Set the log buffer synchronously (for initialization)
     pub fn with_log_buffer_sync(self, buffer: LogBuffer) -> Self {
-        // Use try_write to avoid blocking
-        if let Ok(mut state) = self.state.try_write() {
-            state.log_buffer = Some(buffer);
-        }
+        self.state.write().log_buffer = Some(buffer);
+        self
+    }
+
+    /// Set the log file sink (also passed to McpLogLayer::with_file_sink())
+    pub async fn with_log_file(self, sink: LogFileSink) -> Self {
+        self.with_log_file_sync(sink)
+    }
+
+    /// Set the log file sink synchronously (for initialization)
+    pub fn with_log_file_sync(self, sink: LogFileSink) -> Self {
+        self.state.write().log_file = Some(sink);
+        self
+    }
+
+    /// Set the log filter (from McpLogLayer::filter())
+    pub async fn with_log_filter(self, filter: LogFilter) -> Self {
+        self.with_log_filter_sync(filter)
+    }
+
+    /// Set the log filter synchronously (for initialization)
+    pub fn with_log_filter_sync(self, filter: LogFilter) -> Self {
+        self.state.write().log_filter = Some(filter);
+        self
+    }
+
+    /// Set the profile buffer (from McpProfileLayer::new())
+    pub async fn with_profile_buffer(self, buffer: ProfileBuffer) -> Self {
+        self.with_profile_buffer_sync(buffer)
+    }
+
+    /// Set the profile buffer synchronously (for initialization)
+    pub fn with_profile_buffer_sync(self, buffer: ProfileBuffer) -> Self {
+        self.state.write().profile_buffer = Some(buffer);
         self
     }
 
     /// Get the socket path
     pub async fn socket_path(&self) -> PathBuf {
-        self.state.read().await.socket_path.clone()
+        self.socket_path_sync()
+    }
+
+    /// Get the socket path synchronously
+    pub fn socket_path_sync(&self) -> PathBuf {
+        self.state.read().socket_path.clone()
+    }
+
+    /// Install a panic hook that records crashes for the MCP server
+    ///
+    /// On panic, the message, location and backtrace are written to the crash
+    /// file next to the socket (see [`egui_mcp_protocol::crash_file_path`]),
+    /// so the server can report them after the connection closes. The
+    /// previous panic hook still runs. Call this once at startup.
+    pub fn install_panic_hook(&self) {
+        crash::install_panic_hook(egui_mcp_protocol::crash_file_path(&self.socket_path_sync()));
     }
 
     // Screenshot methods (event-driven)
@@ -159,54 +405,530 @@ impl McpClient {
     /// Request a screenshot and return a receiver to await the result.
     /// This is more efficient than polling as it uses a oneshot channel.
     pub async fn request_screenshot(&self) -> oneshot::Receiver<Vec<u8>> {
+        self.request_viewport_screenshot(None).await
+    }
+
+    /// Request a screenshot of a specific viewport (None = root viewport)
+    pub async fn request_viewport_screenshot(
+        &self,
+        viewport_id: Option<u64>,
+    ) -> oneshot::Receiver<Vec<u8>> {
         let (tx, rx) = oneshot::channel();
-        self.state.write().await.screenshot_sender = Some(tx);
+        let mut state = self.state.write();
+        state.screenshot_sender = Some(tx);
+        state.screenshot_viewport = viewport_id;
+        state.screenshot_command_sent = false;
         rx
     }
 
     /// Check if screenshot is requested and return the sender if available.
     /// Called by the UI to check if it should capture a screenshot.
     pub async fn take_screenshot_request(&self) -> bool {
-        self.state.read().await.screenshot_sender.is_some()
+        self.take_screenshot_request_sync()
+    }
+
+    /// Check if screenshot is requested (synchronous, for the UI thread)
+    pub fn take_screenshot_request_sync(&self) -> bool {
+        self.state.read().screenshot_sender.is_some()
     }
 
     /// Set screenshot data (PNG encoded) - sends through the oneshot channel.
     /// Called by the UI after capturing a screenshot.
     pub async fn set_screenshot(&self, data: Vec<u8>) {
-        let sender = self.state.write().await.screenshot_sender.take();
+        self.set_screenshot_sync(data);
+    }
+
+    /// Set screenshot data (synchronous, for the UI thread)
+    ///
+    /// Sending on the oneshot channel does not need a tokio runtime, so this
+    /// can be called directly from `eframe::App::update`.
+    pub fn set_screenshot_sync(&self, data: Vec<u8>) {
+        let sender = {
+            let mut state = self.state.write();
+            state.screenshot_viewport = None;
+            state.screenshot_command_sent = false;
+            state.screenshot_sender.take()
+        };
         if let Some(tx) = sender {
             // Ignore error if receiver was dropped (e.g., timeout)
             let _ = tx.send(data);
         }
     }
 
+    /// Route a pending screenshot request to the viewport `ctx` is running.
+    ///
+    /// Call this in the UI of every viewport (root `update` and deferred
+    /// viewport callbacks). If the pending request targets this viewport, it
+    /// sends `ViewportCommand::Screenshot`, then picks up the resulting
+    /// `egui::Event::Screenshot`, encodes it as PNG and completes the request.
+    /// It also captures the frames of a screen recording.
+    pub fn handle_screenshot_sync(&self, ctx: &egui::Context) {
+        let this_viewport = ctx.viewport_id();
+        let now = std::time::Instant::now();
+        {
+            let mut state = self.state.write();
+            let pending = state.screenshot_pending(this_viewport);
+            let recording = state.recording_viewport(this_viewport);
+            if !pending && !recording {
+                return;
+            }
+            let mut send_command = pending && !state.screenshot_command_sent;
+            if pending {
+                state.screenshot_command_sent = true;
+            }
+            if let Some(recorder) = state.screen_recorder.as_mut().filter(|_| recording) {
+                if recorder.capture_due(now) && !recorder.command_sent {
+                    recorder.command_sent = true;
+                    send_command = true;
+                }
+                if let Some(delay) = recorder.next_capture_in(now) {
+                    ctx.request_repaint_after(delay);
+                }
+            }
+            if send_command {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot(egui::UserData::default()));
+            }
+        }
+
+        let image = ctx.input(|i| {
+            i.events.iter().find_map(|event| match event {
+                egui::Event::Screenshot {
+                    viewport_id, image, ..
+                } if *viewport_id == this_viewport => Some(image.clone()),
+                _ => None,
+            })
+        });
+        if let Some(image) = image {
+            self.complete_screenshot_sync(this_viewport, image);
+        }
+    }
+
+    /// Hand a screenshot of `viewport` to the pending request and the screen recording
+    pub(crate) fn complete_screenshot_sync(
+        &self,
+        viewport: egui::ViewportId,
+        image: Arc<egui::ColorImage>,
+    ) {
+        let pending = {
+            let mut state = self.state.write();
+            if state.recording_viewport(viewport)
+                && let Some(recorder) = state.screen_recorder.as_mut()
+                && (recorder.command_sent || recorder.capture_due(std::time::Instant::now()))
+            {
+                recorder.add_frame(image.clone());
+            }
+            state.screenshot_pending(viewport)
+        };
+        if pending {
+            match encode_png(&image) {
+                Some(png_data) => self.set_screenshot_sync(png_data),
+                None => tracing::error!("Failed to encode screenshot as PNG"),
+            }
+        }
+    }
+
+    /// Whether a screenshot of `viewport` is pending or due for the screen
+    /// recording (used by [`McpPlugin`]'s software renderer)
+    #[cfg(feature = "software-renderer")]
+    pub(crate) fn screenshot_pending_for_sync(&self, viewport: egui::ViewportId) -> bool {
+        let state = self.state.read();
+        state.screenshot_pending(viewport)
+            || (state.recording_viewport(viewport)
+                && state
+                    .screen_recorder
+                    .as_ref()
+                    .is_some_and(|r| r.capture_due(std::time::Instant::now())))
+    }
+
+    // Screen recording methods
+
+    /// Start capturing screenshots of a viewport (None = root) at `fps`
+    /// (replaces a running recording and discards untaken frames)
+    ///
+    /// Frames are captured in [`handle_screenshot_sync`](Self::handle_screenshot_sync),
+    /// or by [`McpPlugin`]'s software renderer. Fails if `fps` is not a
+    /// positive finite number or the encoder thread cannot be spawned.
+    pub async fn start_screen_recording(
+        &self,
+        viewport_id: Option<u64>,
+        fps: f32,
+        max_duration_ms: u64,
+    ) -> std::io::Result<()> {
+        let recorder = ScreenRecorder::new(viewport_id, fps, max_duration_ms)?;
+        let previous = {
+            let mut state = self.state.write();
+            state.recorded_frames.clear();
+            state.screen_recorder.replace(recorder)
+        };
+        if let Some(previous) = previous {
+            let _ = tokio::task::spawn_blocking(move || previous.finish()).await;
+        }
+        Ok(())
+    }
+
+    /// Stop the screen recording
+    ///
+    /// Returns the number of frames and the length in milliseconds (None if
+    /// not recording). The frames are then taken with
+    /// [`take_recorded_frame`](Self::take_recorded_frame).
+    pub async fn stop_screen_recording(&self) -> Option<(usize, u64)> {
+        let recorder = self.state.write().screen_recorder.take()?;
+        // Waits for the encoder thread to finish the queued frames
+        let (frames, duration_ms) = tokio::task::spawn_blocking(move || recorder.finish())
+            .await
+            .unwrap_or_default();
+        let count = frames.len();
+        self.state.write().recorded_frames = frames.into();
+        Some((count, duration_ms))
+    }
+
+    /// Take the oldest frame of the stopped screen recording as (timestamp in ms, PNG data)
+    pub async fn take_recorded_frame(&self) -> Option<(u64, Vec<u8>)> {
+        self.state.write().recorded_frames.pop_front()
+    }
+
+    // Context inspection methods (event-driven)
+
+    /// Request a snapshot of egui `Context` internals for a viewport (None = root)
+    pub async fn request_context_info(
+        &self,
+        viewport_id: Option<u64>,
+    ) -> oneshot::Receiver<ContextInfo> {
+        let (tx, rx) = oneshot::channel();
+        self.state.write().inspect_requests.push((viewport_id, tx));
+        rx
+    }
+
+    /// Answer pending context inspection requests for the viewport `ctx` is running.
+    ///
+    /// Call this at the end of `update` (and of deferred viewport callbacks),
+    /// after all widgets have been added, so the snapshot reflects this pass.
+    pub fn handle_inspect_sync(&self, ctx: &egui::Context) {
+        let this_viewport = ctx.viewport_id();
+        let senders: Vec<_> = {
+            let mut state = self.state.write();
+            if state.inspect_requests.is_empty() {
+                return;
+            }
+            let requests = std::mem::take(&mut state.inspect_requests);
+            let (matching, rest): (Vec<_>, Vec<_>) = requests
+                .into_iter()
+                .partition(|(target, _)| state.resolve_viewport(*target) == Some(this_viewport));
+            state.inspect_requests = rest;
+            matching.into_iter().map(|(_, tx)| tx).collect()
+        };
+        if senders.is_empty() {
+            return;
+        }
+
+        let info = inspect_context(ctx);
+        for tx in senders {
+            // Ignore error if receiver was dropped (e.g., timeout)
+            let _ = tx.send(info.clone());
+        }
+    }
+
     // Input methods
 
     /// Queue an input event to be processed by the egui app
     pub async fn queue_input(&self, input: PendingInput) {
-        self.state.write().await.pending_inputs.push(input);
+        self.queue_viewport_input(None, input).await;
+    }
+
+    /// Queue an input event for a specific viewport (None = root viewport)
+    pub async fn queue_viewport_input(&self, viewport_id: Option<u64>, input: PendingInput) {
+        self.state.write().pending_inputs.push((viewport_id, input));
     }
 
     /// Take all pending input events (clears the queue)
     pub async fn take_pending_inputs(&self) -> Vec<PendingInput> {
-        std::mem::take(&mut self.state.write().await.pending_inputs)
+        self.take_pending_inputs_sync()
+    }
+
+    /// Take all pending input events regardless of their target viewport
+    /// (synchronous, for `raw_input_hook` in single-viewport apps)
+    pub fn take_pending_inputs_sync(&self) -> Vec<PendingInput> {
+        let mut state = self.state.write();
+        let mut taken: Vec<PendingInput> = std::mem::take(&mut state.pending_inputs)
+            .into_iter()
+            .map(|(_, input)| input)
+            .collect();
+        taken.extend(state.take_latency_input(None));
+        taken
+    }
+
+    /// Take the pending input events targeted at the viewport of `raw_input`
+    /// (synchronous, for `raw_input_hook` in multi-viewport apps)
+    ///
+    /// eframe calls `raw_input_hook` once per root or deferred viewport, so
+    /// each call only drains the inputs routed to `raw_input.viewport_id`.
+    /// This also records `raw_input.viewports` for `ListViewports`.
+    pub fn take_viewport_inputs_sync(&self, raw_input: &egui::RawInput) -> Vec<PendingInput> {
+        let mut state = self.state.write();
+        if !raw_input.viewports.is_empty() {
+            state.viewports = raw_input.viewports.clone();
+        }
+        // Only the real input is recorded; MCP inputs are injected after this
+        if let Some(recorder) = state.input_recorder.as_mut() {
+            recorder.record(raw_input);
+        }
+
+        let pending = std::mem::take(&mut state.pending_inputs);
+        let mut taken = Vec::new();
+        for (target, input) in pending {
+            if state.resolve_viewport(target) == Some(raw_input.viewport_id) {
+                taken.push(input);
+            } else {
+                state.pending_inputs.push((target, input));
+            }
+        }
+        taken.extend(state.take_latency_input(Some(raw_input.viewport_id)));
+        taken
+    }
+
+    // Input recording methods
+
+    /// Start recording real user input (replaces a running recording)
+    ///
+    /// The events are taken from the `RawInput` passed to
+    /// [`take_viewport_inputs_sync`](Self::take_viewport_inputs_sync), so the
+    /// app must call it in `raw_input_hook`. With [`install_plugin`](Self::install_plugin)
+    /// each step also records the widget under the pointer.
+    pub async fn start_input_recording(&self) {
+        self.state.write().input_recorder = Some(InputRecorder::new());
+    }
+
+    /// Stop recording user input and return the recording (None if not recording)
+    pub async fn stop_input_recording(&self) -> Option<InputRecording> {
+        self.state
+            .write()
+            .input_recorder
+            .take()
+            .map(InputRecorder::finish)
+    }
+
+    /// Whether an input recording is active
+    pub fn is_recording_input_sync(&self) -> bool {
+        self.state.read().input_recorder.is_some()
+    }
+
+    /// Set the widget under the pointer for the input recording (called by [`McpPlugin`])
+    pub fn record_hovered_widget_sync(
+        &self,
+        viewport: egui::ViewportId,
+        widget: Option<WidgetRef>,
+    ) {
+        if let Some(recorder) = self.state.write().input_recorder.as_mut() {
+            recorder.set_hovered(viewport, widget);
+        }
+    }
+
+    // Frame shape methods (event-driven)
+
+    /// Request the paint output of the next frame of a viewport as vector shapes (None = root)
+    pub async fn request_frame_shapes(
+        &self,
+        viewport_id: Option<u64>,
+    ) -> oneshot::Receiver<FrameShapes> {
+        let (tx, rx) = oneshot::channel();
+        self.state.write().shape_requests.push((viewport_id, tx));
+        rx
+    }
+
+    /// Keep captured frame shapes for [`frame_shapes_page`](Self::frame_shapes_page),
+    /// optionally only the text shapes
+    pub async fn set_frame_shapes(&self, mut shapes: FrameShapes, text_only: bool) {
+        if text_only {
+            shapes
+                .shapes
+                .retain(|painted| matches!(painted.shape, VectorShape::Text { .. }));
+        }
+        self.state.write().frame_shapes = Some(shapes);
+    }
+
+    /// Get a page of the last captured frame shapes
+    ///
+    /// Returns the frame with at most `limit` shapes starting at `offset`,
+    /// fewer if they would not fit in one IPC message, and the number of
+    /// shapes of the whole frame, or `None` if no frame was captured.
+    pub async fn frame_shapes_page(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> Option<(FrameShapes, usize)> {
+        let state = self.state.read();
+        let frame = state.frame_shapes.as_ref()?;
+        let page = FrameShapes {
+            viewport_id: frame.viewport_id,
+            screen_rect: frame.screen_rect,
+            pixels_per_point: frame.pixels_per_point,
+            shapes: take_page(frame.shapes.iter().skip(offset), limit),
+        };
+        Some((page, frame.shapes.len()))
+    }
+
+    /// Answer pending frame shape requests for the viewport `ctx` is running
+    /// (called by [`McpPlugin`] at the end of each pass)
+    pub fn handle_frame_shapes_sync(&self, ctx: &egui::Context) {
+        let this_viewport = ctx.viewport_id();
+        let senders: Vec<_> = {
+            let mut state = self.state.write();
+            if state.shape_requests.is_empty() {
+                return;
+            }
+            let requests = std::mem::take(&mut state.shape_requests);
+            let (matching, rest): (Vec<_>, Vec<_>) = requests
+                .into_iter()
+                .partition(|(target, _)| state.resolve_viewport(*target) == Some(this_viewport));
+            state.shape_requests = rest;
+            matching.into_iter().map(|(_, tx)| tx).collect()
+        };
+        if senders.is_empty() {
+            return;
+        }
+
+        let shapes = vector::frame_shapes(ctx);
+        for tx in senders {
+            let _ = tx.send(shapes.clone());
+        }
+    }
+
+    // UI tree methods
+
+    /// Get the UI tree of the last root frame (None without AccessKit tree capture)
+    pub async fn get_ui_tree(&self) -> Option<UiTree> {
+        self.state.read().ui_tree.clone()
+    }
+
+    /// Set the UI tree of the current root frame (called by [`McpPlugin`])
+    pub fn record_ui_tree_sync(&self, tree: UiTree) {
+        self.state.write().ui_tree = Some(tree);
+    }
+
+    // Latency methods
+
+    /// Inject an input and measure how long it takes to change the output.
+    ///
+    /// The input is held back until [`McpPlugin`] captured the output of one
+    /// frame of the target viewport as baseline. The returned receiver yields
+    /// the report once a later frame's shapes differ from it. Replaces any
+    /// running measurement.
+    ///
+    /// Requires `install_plugin()` and an app that keeps repainting (e.g.
+    /// with `request_repaint_after`). Anything else that changes the output,
+    /// such as a running animation, is also counted as a change.
+    pub async fn measure_latency(
+        &self,
+        viewport_id: Option<u64>,
+        input: PendingInput,
+    ) -> oneshot::Receiver<LatencyReport> {
+        let (tx, rx) = oneshot::channel();
+        self.state.write().latency_probe = Some(LatencyProbe {
+            viewport_id,
+            input: Some(input),
+            baseline: None,
+            frame: 0,
+            injected_at: None,
+            consumed: None,
+            sender: Some(tx),
+        });
+        rx
+    }
+
+    /// Stop the running latency measurement and report how far it got.
+    ///
+    /// Returns `None` if no baseline frame was captured (the plugin is not
+    /// installed or the app did not repaint).
+    pub async fn cancel_latency_measurement(&self) -> Option<LatencyReport> {
+        let probe = self.state.write().latency_probe.take()?;
+        probe.baseline.as_ref()?;
+        Some(probe.report(None))
+    }
+
+    /// Check the output shapes of a frame for a running latency measurement
+    /// (called by [`McpPlugin`])
+    pub fn record_output_shapes_sync(
+        &self,
+        viewport: egui::ViewportId,
+        shapes: &[egui::epaint::ClippedShape],
+    ) {
+        let mut state = self.state.write();
+        let Some(target) = state.latency_probe.as_ref().map(|p| p.viewport_id) else {
+            return;
+        };
+        if state.resolve_viewport(target) != Some(viewport) {
+            return;
+        }
+        let Some(probe) = state.latency_probe.as_mut() else {
+            return;
+        };
+
+        let now = std::time::Instant::now();
+        let Some(baseline) = &probe.baseline else {
+            probe.baseline = Some(shapes.to_vec());
+            probe.injected_at = Some(now);
+            return;
+        };
+        probe.frame += 1;
+        if probe.consumed.is_some() && baseline.as_slice() != shapes {
+            if let Some(sender) = probe.sender.take() {
+                let _ = sender.send(probe.report(Some(now)));
+            }
+            state.latency_probe = None;
+        }
+    }
+
+    // Viewport methods
+
+    /// Check whether a viewport ID refers to the root or a known viewport
+    pub async fn has_viewport(&self, viewport_id: u64) -> bool {
+        self.state
+            .read()
+            .resolve_viewport(Some(viewport_id))
+            .is_some()
+    }
+
+    /// List the known viewports (root first)
+    ///
+    /// Requires the app to call `take_viewport_inputs_sync` in `raw_input_hook`;
+    /// otherwise only the root viewport is reported.
+    pub async fn list_viewports(&self) -> Vec<egui_mcp_protocol::ViewportInfo> {
+        let state = self.state.read();
+        let mut viewports: Vec<egui_mcp_protocol::ViewportInfo> = state
+            .viewports
+            .iter()
+            .map(|(id, info)| convert_viewport_info(*id, info))
+            .collect();
+        if !state.viewports.contains_key(&egui::ViewportId::ROOT) {
+            viewports.push(convert_viewport_info(
+                egui::ViewportId::ROOT,
+                &egui::ViewportInfo::default(),
+            ));
+        }
+        viewports.sort_by_key(|v| (!v.is_root, v.id));
+        viewports
     }
 
     // Highlight methods
 
     /// Add a highlight to be drawn
     pub async fn add_highlight(&self, highlight: Highlight) {
-        self.state.write().await.highlights.push(highlight);
+        self.state.write().highlights.push(highlight);
     }
 
     /// Clear all highlights
     pub async fn clear_highlights(&self) {
-        self.state.write().await.highlights.clear();
+        self.state.write().highlights.clear();
     }
 
     /// Get active highlights (removes expired ones)
     pub async fn get_highlights(&self) -> Vec<Highlight> {
-        let mut state = self.state.write().await;
+        self.get_highlights_sync()
+    }
+
+    /// Get active highlights (synchronous, for the UI thread)
+    pub fn get_highlights_sync(&self) -> Vec<Highlight> {
+        let mut state = self.state.write();
         let now = std::time::Instant::now();
         // Remove expired highlights
         state
@@ -219,47 +941,130 @@ impl McpClient {
 
     /// Get log entries, optionally filtered by level and limited in count
     pub async fn get_logs(&self, min_level: Option<&str>, limit: Option<usize>) -> Vec<LogEntry> {
-        let state = self.state.read().await;
-        if let Some(ref buffer) = state.log_buffer {
-            let buf = buffer.lock();
-            let min_priority = min_level.map(level_to_priority).unwrap_or(0);
-
-            let filtered: Vec<LogEntry> = buf
-                .iter()
-                .filter(|entry| level_to_priority(&entry.level) >= min_priority)
-                .cloned()
-                .collect();
-
-            match limit {
-                Some(n) => filtered.into_iter().rev().take(n).rev().collect(),
-                None => filtered,
-            }
-        } else {
-            Vec::new()
+        let query = LogQuery {
+            level: min_level.map(str::to_string),
+            limit,
+            ..Default::default()
+        };
+        self.query_logs(&query)
+            .await
+            .map(|(entries, _)| entries)
+            .unwrap_or_default()
+    }
+
+    /// Get log entries matching the query
+    ///
+    /// Returns the entries (oldest first) and the cursor for the next query's
+    /// `since`, or an error message if a regex is invalid.
+    pub async fn query_logs(&self, query: &LogQuery) -> Result<(Vec<LogEntry>, u64), String> {
+        let state = self.state.read();
+        match state.log_buffer {
+            Some(ref buffer) => query.apply(&buffer.lock()),
+            None => Ok((Vec::new(), query.since.unwrap_or(0))),
         }
     }
 
+    /// Get persisted log entries matching the query
+    ///
+    /// Reads the rotated log files instead of the in-memory buffer, so entries
+    /// dropped from the buffer are included. Returns the newest `limit`
+    /// entries (default 1000), cut further to fit in one IPC response, and the
+    /// cursor of the in-memory buffer to follow new entries from there.
+    ///
+    /// `since` is rejected: the files span several runs of the app and
+    /// sequence numbers start over with each run. Returns an error message
+    /// if no log file is configured or it cannot be read.
+    pub async fn query_log_history(
+        &self,
+        query: &LogQuery,
+    ) -> Result<(Vec<LogEntry>, u64), String> {
+        if query.since.is_some() {
+            return Err(
+                "since is not supported with history: the log files span several runs of the app and sequence numbers start over with each run"
+                    .to_string(),
+            );
+        }
+        let (sink, cursor) = {
+            let state = self.state.read();
+            let cursor = state
+                .log_buffer
+                .as_ref()
+                .and_then(|buffer| buffer.lock().back().map(|entry| entry.seq))
+                .unwrap_or(0);
+            (state.log_file.clone(), cursor)
+        };
+        let sink = sink.ok_or_else(|| {
+            "Log file not configured. Pass a LogFileSink to McpLogLayer::with_file_sink() and McpClient::with_log_file_sync()."
+                .to_string()
+        })?;
+        let matches = query.matcher()?;
+        let limit = query.limit.unwrap_or(log_file::DEFAULT_HISTORY_LIMIT);
+        let path = sink.path();
+        let entries = tokio::task::spawn_blocking(move || sink.read_history(matches, limit))
+            .await
+            .map_err(|e| format!("Failed to read log file {:?}: {}", path, e))?
+            .map_err(|e| format!("Failed to read log file {:?}: {}", path, e))?;
+        Ok((entries, cursor))
+    }
+
     /// Clear all log entries
     pub async fn clear_logs(&self) {
-        let state = self.state.read().await;
+        let state = self.state.read();
         if let Some(ref buffer) = state.log_buffer {
             buffer.lock().clear();
         }
     }
 
+    /// Change which events the log layer captures (`EnvFilter` directives)
+    ///
+    /// Empty directives capture everything again. Returns an error message if
+    /// no filter is configured or the directives are invalid.
+    pub async fn set_log_filter(&self, directives: &str) -> Result<(), String> {
+        let filter = self.state.read().log_filter.clone().ok_or_else(|| {
+            "Log filter not configured. Pass McpLogLayer::filter() to McpClient::with_log_filter_sync()."
+                .to_string()
+        })?;
+        filter.set(directives)
+    }
+
     // Performance monitoring methods
 
+    /// Install [`McpPlugin`] to collect paint statistics for the perf tools.
+    ///
+    /// Call this once, e.g. with `cc.egui_ctx` in your app's constructor.
+    pub fn install_plugin(&self, ctx: &egui::Context) {
+        ctx.add_plugin(McpPlugin::new(self.clone()));
+    }
+
+    /// Record the paint statistics of a frame (called by [`McpPlugin`])
+    pub fn record_paint_stats_sync(&self, stats: PaintStats) {
+        let mut state = self.state.write();
+        if let Some(ref mut recording) = state.perf_recording {
+            recording.paint_stats.push(stats.clone());
+        }
+        state.last_paint_stats = Some(stats);
+    }
+
     /// Record a frame for performance monitoring (auto-timing version)
     /// Call this once at the end of each frame (in eframe::App::update).
     /// The frame time is automatically calculated from the previous call.
     pub async fn record_frame_auto(&self) {
-        let mut state = self.state.write().await;
+        self.record_frame_auto_sync();
+    }
+
+    /// Record a frame for performance monitoring (synchronous auto-timing version)
+    pub fn record_frame_auto_sync(&self) {
+        let mut state = self.state.write();
         let now = std::time::Instant::now();
 
         if let Some(last) = state.last_frame_instant {
             let frame_time = now.duration_since(last);
             let max_samples = state.max_frame_samples;
 
+            if let Some(ref profile) = state.profile_buffer {
+                profile.record_frame(last, frame_time);
+            }
+
             // Add to rolling window
             state.frame_times.push_back(frame_time);
             while state.frame_times.len() > max_samples {
@@ -269,18 +1074,32 @@ impl McpClient {
             // Add to recording if active
             if let Some(ref mut recording) = state.perf_recording {
                 recording.frame_times.push(frame_time);
+                recording.frame_ends.push(now);
             }
         }
 
         state.last_frame_instant = Some(now);
+        state.record_frame_memory();
     }
 
     /// Record a frame time for performance monitoring (manual timing version)
     /// Call this at the end of each frame (in eframe::App::update)
     pub async fn record_frame(&self, frame_time: std::time::Duration) {
-        let mut state = self.state.write().await;
+        self.record_frame_sync(frame_time);
+    }
+
+    /// Record a frame time for performance monitoring (synchronous manual timing version)
+    pub fn record_frame_sync(&self, frame_time: std::time::Duration) {
+        let mut state = self.state.write();
         let max_samples = state.max_frame_samples;
 
+        if let Some(ref profile) = state.profile_buffer {
+            let start = std::time::Instant::now()
+                .checked_sub(frame_time)
+                .unwrap_or_else(std::time::Instant::now);
+            profile.record_frame(start, frame_time);
+        }
+
         // Add to rolling window
         state.frame_times.push_back(frame_time);
         while state.frame_times.len() > max_samples {
@@ -290,6 +1109,7 @@ impl McpClient {
         // Add to recording if active
         if let Some(ref mut recording) = state.perf_recording {
             recording.frame_times.push(frame_time);
+            recording.frame_ends.push(std::time::Instant::now());
 
             // Check if recording should auto-stop
             if recording.duration_ms > 0 {
@@ -299,11 +1119,43 @@ impl McpClient {
                 }
             }
         }
+        state.record_frame_memory();
+    }
+
+    /// Record the texture memory of the root viewport (called by [`McpPlugin`])
+    pub fn record_texture_memory_sync(&self, memory: TextureMemory) {
+        self.state.write().texture_memory = Some(memory);
+    }
+
+    /// Get heap and texture memory statistics
+    ///
+    /// Heap statistics require the `alloc-tracking` feature with
+    /// `McpAllocator` installed as global allocator; the per-frame churn also
+    /// requires `record_frame_auto_sync()`. Texture memory requires
+    /// `install_plugin()`.
+    pub async fn get_memory_stats(&self) -> MemoryStats {
+        let state = self.state.read();
+        let heap = alloc::counters().map(|counters| {
+            let (frame_allocations, frame_allocated_bytes) =
+                state.frame_alloc_churn.unwrap_or((0, 0));
+            HeapStats {
+                live_bytes: counters.live_bytes(),
+                live_allocations: counters.live_allocations(),
+                total_allocations: counters.allocations,
+                total_allocated_bytes: counters.allocated_bytes,
+                frame_allocations,
+                frame_allocated_bytes,
+            }
+        });
+        MemoryStats {
+            heap,
+            textures: state.texture_memory.clone(),
+        }
     }
 
     /// Get current frame statistics
     pub async fn get_frame_stats(&self) -> FrameStats {
-        let state = self.state.read().await;
+        let state = self.state.read();
 
         if state.frame_times.is_empty() {
             return FrameStats {
@@ -312,6 +1164,7 @@ impl McpClient {
                 frame_time_min_ms: 0.0,
                 frame_time_max_ms: 0.0,
                 sample_count: 0,
+                paint: state.last_paint_stats.clone(),
             };
         }
 
@@ -332,22 +1185,80 @@ impl McpClient {
             frame_time_min_ms: min,
             frame_time_max_ms: max,
             sample_count: times.len(),
+            paint: state.last_paint_stats.clone(),
         }
     }
 
     /// Start recording performance data
     pub async fn start_perf_recording(&self, duration_ms: u64) {
-        let mut state = self.state.write().await;
+        self.start_perf_recording_with_budgets(duration_ms, Vec::new())
+            .await;
+    }
+
+    /// Start recording performance data, counting frames over the given budgets
+    ///
+    /// An empty list uses `DEFAULT_FRAME_BUDGETS_MS`. The smallest budget is
+    /// also the threshold for jank streaks.
+    pub async fn start_perf_recording_with_budgets(&self, duration_ms: u64, budgets_ms: Vec<f32>) {
+        let mut budgets_ms = if budgets_ms.is_empty() {
+            DEFAULT_FRAME_BUDGETS_MS.to_vec()
+        } else {
+            budgets_ms
+        };
+        budgets_ms.sort_by(f32::total_cmp);
+        budgets_ms.dedup();
+
+        let mut state = self.state.write();
         state.perf_recording = Some(PerfRecording {
             start_time: std::time::Instant::now(),
             frame_times: Vec::new(),
+            frame_ends: Vec::new(),
+            budgets_ms,
             duration_ms,
+            paint_stats: Vec::new(),
+            memory_samples: Vec::new(),
         });
     }
 
+    /// Start capturing span timings into the profile buffer
+    ///
+    /// Returns `false` if no profile buffer is configured (see `McpProfileLayer`).
+    pub async fn start_profiling(&self) -> bool {
+        match &self.state.read().profile_buffer {
+            Some(profile) => {
+                profile.start();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Get a page of the span timings of the current or last profiling recording.
+    ///
+    /// Returns at most `limit` Chrome trace events starting at `offset`, the
+    /// number of dropped events and the number of events of the whole trace,
+    /// or `None` if no profile buffer is configured.
+    pub async fn export_perf_trace(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> Option<(Vec<TraceEvent>, usize, usize)> {
+        let state = self.state.read();
+        state
+            .profile_buffer
+            .as_ref()
+            .map(|buffer| buffer.export(offset, limit))
+    }
+
     /// Stop recording and get the performance report
+    ///
+    /// This also stops profiling; the captured spans stay available to
+    /// `export_perf_trace`.
     pub async fn get_perf_report(&self) -> Option<PerfReport> {
-        let mut state = self.state.write().await;
+        let mut state = self.state.write();
+        if let Some(ref profile) = state.profile_buffer {
+            profile.stop();
+        }
         let recording = state.perf_recording.take()?;
 
         if recording.frame_times.is_empty() {
@@ -363,6 +1274,9 @@ impl McpClient {
             .map(|d| d.as_secs_f32() * 1000.0)
             .collect();
 
+        // Keep the recording order for jank streaks; `times_ms` gets sorted below
+        let recording_times_ms = times_ms.clone();
+
         let sum: f32 = times_ms.iter().sum();
         let avg_frame_time = sum / total_frames as f32;
         let avg_fps = if avg_frame_time > 0.0 {
@@ -386,6 +1300,26 @@ impl McpClient {
             .copied()
             .unwrap_or(0.0);
 
+        let jank_budget_ms = recording.budgets_ms.first().copied().unwrap_or(0.0);
+        let frame_ends_ms: Vec<f32> = recording
+            .frame_ends
+            .iter()
+            .map(|end| {
+                end.saturating_duration_since(recording.start_time)
+                    .as_secs_f32()
+                    * 1000.0
+            })
+            .collect();
+        let budgets = recording
+            .budgets_ms
+            .iter()
+            .map(|&budget_ms| BudgetCount {
+                budget_ms,
+                frames_over: times_ms.iter().filter(|&&t| t > budget_ms).count(),
+            })
+            .collect();
+        let jank_streaks = find_jank_streaks(&recording_times_ms, &frame_ends_ms, jank_budget_ms);
+
         Some(PerfReport {
             duration_ms,
             total_frames,
@@ -395,9 +1329,131 @@ impl McpClient {
             max_frame_time_ms: max_frame_time,
             p95_frame_time_ms: p95_frame_time,
             p99_frame_time_ms: p99_frame_time,
+            histogram: frame_time_histogram(&times_ms),
+            budgets,
+            jank_budget_ms,
+            jank_streaks,
+            paint: summarize_paint_stats(&recording.paint_stats),
+            memory: summarize_memory_samples(&recording.memory_samples),
+            textures: state.texture_memory.clone(),
         })
     }
 
+    // Time control methods
+
+    /// Freeze the time reported to egui at the time of the next frame
+    pub async fn freeze_time(&self) {
+        let mut state = self.state.write();
+        if state.time_control.is_none() {
+            state.time_control = Some(TimeControl::default());
+        }
+    }
+
+    /// Run `frames` frames advancing time by `dt_ms` each, then stay frozen.
+    ///
+    /// Returns a receiver that yields the virtual time (in seconds) once the
+    /// last step has been applied.
+    pub async fn step_frames(&self, frames: u32, dt_ms: f32) -> oneshot::Receiver<f64> {
+        let (tx, rx) = oneshot::channel();
+        let mut state = self.state.write();
+        let control = state.time_control.get_or_insert_with(TimeControl::default);
+        if frames == 0 {
+            let _ = tx.send(control.time.unwrap_or(0.0));
+        } else {
+            control.remaining_steps = frames;
+            control.step_dt = dt_ms / 1000.0;
+            control.step_sender = Some(tx);
+        }
+        rx
+    }
+
+    /// Resume real time (cancels any pending steps)
+    pub async fn resume_time(&self) {
+        self.state.write().time_control = None;
+    }
+
+    /// Get the time control state as `(frozen, virtual time in seconds)`
+    pub async fn time_state(&self) -> (bool, Option<f64>) {
+        let state = self.state.read();
+        match &state.time_control {
+            Some(control) => (true, control.time),
+            None => (false, None),
+        }
+    }
+
+    /// Override `RawInput::time` and `predicted_dt` while time is frozen.
+    ///
+    /// Call this in `raw_input_hook`. Steps only advance on root viewport
+    /// frames; other viewports see the same virtual time. While frozen
+    /// without pending steps, `predicted_dt` is 0 so animations stand still.
+    pub fn apply_time_control_sync(&self, ctx: &egui::Context, raw_input: &mut egui::RawInput) {
+        let mut state = self.state.write();
+        let Some(control) = state.time_control.as_mut() else {
+            return;
+        };
+
+        let mut time = *control.time.get_or_insert(raw_input.time.unwrap_or(0.0));
+        let mut dt = 0.0;
+        if control.remaining_steps > 0 && raw_input.viewport_id == egui::ViewportId::ROOT {
+            dt = control.step_dt;
+            time += dt as f64;
+            control.time = Some(time);
+            control.remaining_steps -= 1;
+            if control.remaining_steps == 0 {
+                if let Some(tx) = control.step_sender.take() {
+                    let _ = tx.send(time);
+                }
+            } else {
+                ctx.request_repaint();
+            }
+        }
+
+        raw_input.time = Some(time);
+        raw_input.predicted_dt = dt;
+    }
+
+    // Idle detection methods
+
+    /// Record the repaint activity of the current frame (for `wait_for_idle`).
+    ///
+    /// Call this once per frame at the end of `update`. A frame counts as busy
+    /// if the previous pass requested an immediate repaint (e.g. an animation
+    /// is running), images are still loading, or MCP inputs are still queued.
+    /// Delayed repaints (`request_repaint_after`) do not count.
+    pub fn record_idle_state_sync(&self, ctx: &egui::Context) {
+        if ctx.viewport_id() != egui::ViewportId::ROOT {
+            return;
+        }
+        let repaint_requested = ctx.requested_repaint_last_pass() || ctx.has_pending_images();
+
+        let mut state = self.state.write();
+        let busy = repaint_requested || !state.pending_inputs.is_empty();
+        let idle = &mut state.idle;
+        idle.frame_count += 1;
+        idle.idle_frames = if busy {
+            0
+        } else {
+            idle.idle_frames.saturating_add(1)
+        };
+        idle.repaint_requested = repaint_requested;
+        idle.last_frame = Some(std::time::Instant::now());
+    }
+
+    /// Get the current repaint activity
+    pub async fn get_idle_state(&self) -> IdleState {
+        let state = self.state.read();
+        IdleState {
+            frame_count: state.idle.frame_count,
+            idle_frames: state.idle.idle_frames,
+            repaint_requested: state.idle.repaint_requested,
+            pending_inputs: state.pending_inputs.len(),
+            ms_since_last_frame: state
+                .idle
+                .last_frame
+                .map(|t| t.elapsed().as_millis() as u64),
+        }
+    }
+
     /// Start the IPC server in a background task
     pub fn start_server(&self) -> tokio::task::JoinHandle<()> {
         let client = self.clone();
@@ -409,6 +1465,372 @@ impl McpClient {
     }
 }
 
+impl ClientState {
+    /// Update the per-frame allocation churn at the end of a frame
+    fn record_frame_memory(&mut self) {
+        let Some(counters) = alloc::counters() else {
+            return;
+        };
+        if let Some(last) = self.last_frame_allocs {
+            let allocations = counters.allocations - last.allocations;
+            let allocated_bytes = counters.allocated_bytes - last.allocated_bytes;
+            self.frame_alloc_churn = Some((allocations, allocated_bytes));
+            if let Some(ref mut recording) = self.perf_recording {
+                recording.memory_samples.push(MemorySample {
+                    live_bytes: counters.live_bytes(),
+                    allocations,
+                    allocated_bytes,
+                });
+            }
+        }
+        self.last_frame_allocs = Some(counters);
+    }
+
+    /// Take the input of the latency measurement once its baseline was captured
+    ///
+    /// `viewport` is the viewport whose inputs are being taken (None = any).
+    fn take_latency_input(&mut self, viewport: Option<egui::ViewportId>) -> Option<PendingInput> {
+        let target = self.latency_probe.as_ref()?.viewport_id;
+        if viewport.is_some() && self.resolve_viewport(target) != viewport {
+            return None;
+        }
+        let probe = self.latency_probe.as_mut()?;
+        probe.baseline.as_ref()?;
+        let input = probe.input.take()?;
+        probe.consumed = Some((probe.frame + 1, std::time::Instant::now()));
+        Some(input)
+    }
+
+    /// Whether the pending screenshot request targets `viewport`
+    fn screenshot_pending(&self, viewport: egui::ViewportId) -> bool {
+        self.screenshot_sender.is_some()
+            && self.resolve_viewport(self.screenshot_viewport) == Some(viewport)
+    }
+
+    /// Whether the screen recording targets `viewport`
+    fn recording_viewport(&self, viewport: egui::ViewportId) -> bool {
+        self.screen_recorder
+            .as_ref()
+            .is_some_and(|r| self.resolve_viewport(r.viewport_id) == Some(viewport))
+    }
+
+    /// Resolve a protocol viewport ID to a known egui `ViewportId` (None = root)
+    fn resolve_viewport(&self, viewport_id: Option<u64>) -> Option<egui::ViewportId> {
+        match viewport_id {
+            None => Some(egui::ViewportId::ROOT),
+            Some(id) if id == egui::ViewportId::ROOT.0.value() => Some(egui::ViewportId::ROOT),
+            Some(id) => self.viewports.keys().copied().find(|v| v.0.value() == id),
+        }
+    }
+}
+
+/// Upper bounds of the frame time histogram buckets in milliseconds
+const HISTOGRAM_BOUNDS_MS: [f32; 10] = [4.0, 8.0, 12.0, 16.6, 20.0, 25.0, 33.3, 50.0, 100.0, 250.0];
+
+/// Maximum number of jank streaks in a performance report
+const MAX_JANK_STREAKS: usize = 5;
+
+/// Count frame times per histogram bucket (the last bucket is unbounded)
+fn frame_time_histogram(times_ms: &[f32]) -> Vec<HistogramBucket> {
+    let mut min_ms = 0.0;
+    let mut buckets: Vec<HistogramBucket> = HISTOGRAM_BOUNDS_MS
+        .iter()
+        .map(|&max_ms| {
+            let bucket = HistogramBucket {
+                min_ms,
+                max_ms: Some(max_ms),
+                count: 0,
+            };
+            min_ms = max_ms;
+            bucket
+        })
+        .collect();
+    buckets.push(HistogramBucket {
+        min_ms,
+        max_ms: None,
+        count: 0,
+    });
+
+    for &time in times_ms {
+        let index = HISTOGRAM_BOUNDS_MS
+            .iter()
+            .position(|&max_ms| time < max_ms)
+            .unwrap_or(HISTOGRAM_BOUNDS_MS.len());
+        buckets[index].count += 1;
+    }
+    buckets
+}
+
+/// Find the longest runs of consecutive frames over `budget_ms`
+///
+/// `times_ms` and `frame_ends_ms` must be in recording order. Streaks are
+/// sorted by frame count, then total duration.
+fn find_jank_streaks(times_ms: &[f32], frame_ends_ms: &[f32], budget_ms: f32) -> Vec<JankStreak> {
+    let mut streaks: Vec<JankStreak> = Vec::new();
+    let mut current: Option<JankStreak> = None;
+
+    for (&time, &end) in times_ms.iter().zip(frame_ends_ms) {
+        if time <= budget_ms {
+            streaks.extend(current.take());
+            continue;
+        }
+        let streak = current.get_or_insert(JankStreak {
+            start_ms: (end - time).max(0.0),
+            duration_ms: 0.0,
+            frames: 0,
+            max_frame_time_ms: 0.0,
+        });
+        streak.duration_ms += time;
+        streak.frames += 1;
+        streak.max_frame_time_ms = streak.max_frame_time_ms.max(time);
+    }
+    streaks.extend(current);
+
+    streaks.sort_by(|a, b| {
+        b.frames
+            .cmp(&a.frames)
+            .then_with(|| b.duration_ms.total_cmp(&a.duration_ms))
+    });
+    streaks.truncate(MAX_JANK_STREAKS);
+    streaks
+}
+
+/// Aggregate recorded heap samples (None if nothing was recorded)
+fn summarize_memory_samples(samples: &[MemorySample]) -> Option<MemorySummary> {
+    let first = samples.first()?;
+    let last = samples.last()?;
+    let frames = samples.len();
+    Some(MemorySummary {
+        frames,
+        start_live_bytes: first.live_bytes,
+        end_live_bytes: last.live_bytes,
+        peak_live_bytes: samples.iter().map(|s| s.live_bytes).max().unwrap_or(0),
+        live_bytes_growth: last.live_bytes as i64 - first.live_bytes as i64,
+        avg_frame_allocations: samples.iter().map(|s| s.allocations).sum::<u64>() as f32
+            / frames as f32,
+        max_frame_allocations: samples.iter().map(|s| s.allocations).max().unwrap_or(0),
+        avg_frame_allocated_bytes: samples.iter().map(|s| s.allocated_bytes).sum::<u64>() as f32
+            / frames as f32,
+        max_frame_allocated_bytes: samples.iter().map(|s| s.allocated_bytes).max().unwrap_or(0),
+    })
+}
+
+/// Aggregate recorded paint statistics (None if nothing was recorded)
+fn summarize_paint_stats(samples: &[PaintStats]) -> Option<PaintSummary> {
+    if samples.is_empty() {
+        return None;
+    }
+    let frames = samples.len();
+
+    let total_shapes: usize = samples.iter().map(|s| s.shapes).sum();
+    let vertices: Vec<usize> = samples.iter().filter_map(|s| s.vertices).collect();
+
+    let mut cause_counts: std::collections::HashMap<&str, usize> = Default::default();
+    for sample in samples {
+        for cause in &sample.repaint_causes {
+            *cause_counts.entry(cause.as_str()).or_default() += 1;
+        }
+    }
+    let mut repaint_causes: Vec<RepaintCauseCount> = cause_counts
+        .into_iter()
+        .map(|(cause, frames)| RepaintCauseCount {
+            cause: cause.to_string(),
+            frames,
+        })
+        .collect();
+    repaint_causes.sort_by(|a, b| b.frames.cmp(&a.frames).then_with(|| a.cause.cmp(&b.cause)));
+
+    Some(PaintSummary {
+        frames,
+        avg_shapes: total_shapes as f32 / frames as f32,
+        max_shapes: samples.iter().map(|s| s.shapes).max().unwrap_or(0),
+        avg_vertices: (!vertices.is_empty())
+            .then(|| vertices.iter().sum::<usize>() as f32 / vertices.len() as f32),
+        max_vertices: vertices.iter().copied().max(),
+        frames_with_texture_changes: samples
+            .iter()
+            .filter(|s| s.textures_set > 0 || s.textures_freed > 0)
+            .count(),
+        texture_bytes: samples.iter().map(|s| s.texture_bytes).sum(),
+        repaint_causes,
+    })
+}
+
+/// Convert egui viewport info into the protocol representation
+fn convert_viewport_info(
+    id: egui::ViewportId,
+    info: &egui::ViewportInfo,
+) -> egui_mcp_protocol::ViewportInfo {
+    let convert_rect = |r: egui::Rect| egui_mcp_protocol::Rect {
+        x: r.min.x,
+        y: r.min.y,
+        width: r.width(),
+        height: r.height(),
+    };
+    egui_mcp_protocol::ViewportInfo {
+        id: id.0.value(),
+        parent: info
+            .parent
+            .filter(|_| id != egui::ViewportId::ROOT)
+            .map(|p| p.0.value()),
+        is_root: id == egui::ViewportId::ROOT,
+        title: info.title.clone(),
+        inner_rect: info.inner_rect.map(convert_rect),
+        outer_rect: info.outer_rect.map(convert_rect),
+        focused: info.focused,
+        minimized: info.minimized,
+    }
+}
+
+/// Reference a widget by its `Id`, with its rect and layer from the last pass
+fn widget_ref(ctx: &egui::Context, id: egui::Id) -> WidgetRef {
+    let response = ctx.read_response(id);
+    WidgetRef {
+        id: id.value(),
+        rect: response.as_ref().map(|r| egui_mcp_protocol::Rect {
+            x: r.rect.min.x,
+            y: r.rect.min.y,
+            width: r.rect.width(),
+            height: r.rect.height(),
+        }),
+        layer_id: response.as_ref().map(|r| r.layer_id.id.value()),
+        layer_order: response.as_ref().map(|r| format!("{:?}", r.layer_id.order)),
+        enabled: response.as_ref().map(|r| r.enabled()),
+    }
+}
+
+/// The widget the pointer hovers (the top-most one if several are hovered)
+pub(crate) fn hovered_widget(ctx: &egui::Context) -> Option<WidgetRef> {
+    let hovered = ctx.interaction_snapshot(|snapshot| snapshot.hovered.clone());
+    ctx.viewport(|vp| {
+        vp.hits
+            .contains_pointer
+            .iter()
+            .rev()
+            .find(|widget| hovered.contains(&widget.id))
+            .map(|widget| widget.id)
+    })
+    .map(|id| widget_ref(ctx, id))
+}
+
+/// Take a snapshot of egui `Context` internals: focus, hover, popups and layers
+pub fn inspect_context(ctx: &egui::Context) -> ContextInfo {
+    use egui_mcp_protocol::{LayerInfo, PointerInfo};
+
+    let convert_rect = |r: egui::Rect| egui_mcp_protocol::Rect {
+        x: r.min.x,
+        y: r.min.y,
+        width: r.width(),
+        height: r.height(),
+    };
+    let to_array = |p: egui::Pos2| [p.x, p.y];
+    let widget_ref = |id: egui::Id| widget_ref(ctx, id);
+
+    let (hovered, dragged, clicked) = ctx.interaction_snapshot(|snapshot| {
+        let mut hovered: Vec<egui::Id> = snapshot.hovered.iter().copied().collect();
+        hovered.sort_by_key(|id| id.value());
+        (hovered, snapshot.dragged, snapshot.clicked)
+    });
+
+    let pointer = ctx.input(|i| PointerInfo {
+        latest_pos: i.pointer.latest_pos().map(to_array),
+        hover_pos: i.pointer.hover_pos().map(to_array),
+        press_origin: i.pointer.press_origin().map(to_array),
+        primary_down: i.pointer.primary_down(),
+        secondary_down: i.pointer.secondary_down(),
+        middle_down: i.pointer.middle_down(),
+        layer_under_pointer: None,
+    });
+    let layer_under_pointer = pointer
+        .hover_pos
+        .and_then(|[x, y]| ctx.layer_id_at(egui::pos2(x, y)))
+        .map(|layer| layer.id.value());
+
+    let (focused, layers) = ctx.memory(|mem| {
+        let areas = mem.areas();
+        let layers = mem
+            .layer_ids()
+            .map(|layer| LayerInfo {
+                id: layer.id.value(),
+                order: format!("{:?}", layer.order),
+                visible: areas.is_visible(&layer),
+                rect: mem.area_rect(layer.id).map(convert_rect),
+                parent: areas.parent_layer(layer).map(|p| p.id.value()),
+            })
+            .collect::<Vec<_>>();
+        (mem.focused(), layers)
+    });
+
+    ContextInfo {
+        viewport_id: ctx.viewport_id().0.value(),
+        focused: focused.map(widget_ref),
+        hovered: hovered.into_iter().map(widget_ref).collect(),
+        dragged: dragged.map(widget_ref),
+        clicked: clicked.map(widget_ref),
+        pointer: PointerInfo {
+            layer_under_pointer,
+            ..pointer
+        },
+        any_popup_open: egui::Popup::is_any_open(ctx),
+        wants_pointer_input: ctx.wants_pointer_input(),
+        wants_keyboard_input: ctx.wants_keyboard_input(),
+        layers,
+    }
+}
+
+/// Encode an egui `ColorImage` (e.g. from `egui::Event::Screenshot`) as PNG
+pub fn encode_png(image: &egui::ColorImage) -> Option<Vec<u8>> {
+    use image::ImageEncoder;
+
+    let mut png_data = Vec::new();
+    let encoder = image::codecs::png::PngEncoder::new(&mut png_data);
+
+    // Convert RGBA pixels to bytes
+    let pixels: Vec<u8> = image
+        .pixels
+        .iter()
+        .flat_map(|c| [c.r(), c.g(), c.b(), c.a()])
+        .collect();
+
+    encoder
+        .write_image(
+            &pixels,
+            image.width() as u32,
+            image.height() as u32,
+            image::ExtendedColorType::Rgba8,
+        )
+        .ok()?;
+
+    Some(png_data)
+}
+
+/// Serialized size of the items of a response page, leaving room for the
+/// rest of the response below the IPC message limit
+const MAX_PAGE_BYTES: usize = egui_mcp_protocol::MAX_MESSAGE_SIZE / 2;
+
+/// Take up to `limit` items for a response page
+///
+/// Stops early once the items would serialize to more than
+/// [`MAX_PAGE_BYTES`], but always takes at least one item so paging makes
+/// progress.
+pub(crate) fn take_page<'a, T: serde::Serialize + Clone + 'a>(
+    items: impl IntoIterator<Item = &'a T>,
+    limit: usize,
+) -> Vec<T> {
+    let mut page = Vec::new();
+    let mut page_bytes = 0;
+    for item in items.into_iter().take(limit) {
+        // Separating comma included
+        let size = serde_json::to_vec(item).map_or(0, |json| json.len()) + 1;
+        if !page.is_empty() && page_bytes + size > MAX_PAGE_BYTES {
+            break;
+        }
+        page_bytes += size;
+        page.push(item.clone());
+    }
+    page
+}
+
 impl Default for McpClient {
     fn default() -> Self {
         Self::new()
@@ -565,7 +1987,7 @@ fn parse_special_key(key: &str) -> Option<egui::Key> {
 /// ```rust,ignore
 /// impl eframe::App for MyApp {
 ///     fn raw_input_hook(&mut self, ctx: &egui::Context, raw_input: &mut egui::RawInput) {
-///         let inputs = self.runtime.block_on(self.mcp_client.take_pending_inputs());
+///         let inputs = self.mcp_client.take_pending_inputs_sync();
 ///         egui_mcp_client::inject_inputs(ctx, raw_input, inputs);
 ///     }
 /// }
@@ -741,7 +2163,7 @@ pub fn inject_inputs(
 ///         // ... your UI code ...
 ///
 ///         // Draw highlights at the end
-///         let highlights = self.runtime.block_on(self.mcp_client.get_highlights());
+///         let highlights = self.mcp_client.get_highlights_sync();
 ///         egui_mcp_client::draw_highlights(ctx, &highlights);
 ///     }
 /// }
@@ -781,6 +2203,477 @@ pub fn draw_highlights(ctx: &egui::Context, highlights: &[Highlight]) {
 mod tests {
     use super::*;
 
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn test_take_pending_inputs_sync_drains_queue() {
+        let client = McpClient::new();
+        block_on(client.queue_input(PendingInput::MoveMouse { x: 1.0, y: 2.0 }));
+
+        let inputs = client.take_pending_inputs_sync();
+        assert_eq!(inputs.len(), 1);
+        assert!(client.take_pending_inputs_sync().is_empty());
+    }
+
+    #[test]
+    fn test_take_viewport_inputs_sync_routes_by_viewport() {
+        let client = McpClient::new();
+        let secondary = egui::ViewportId::from_hash_of("secondary");
+        let mut raw_input = egui::RawInput::default();
+        raw_input
+            .viewports
+            .insert(egui::ViewportId::ROOT, Default::default());
+        raw_input.viewports.insert(secondary, Default::default());
+
+        block_on(client.queue_input(PendingInput::MoveMouse { x: 1.0, y: 2.0 }));
+        block_on(client.queue_viewport_input(
+            Some(secondary.0.value()),
+            PendingInput::MoveMouse { x: 3.0, y: 4.0 },
+        ));
+
+        // Root frame only drains the root input
+        assert_eq!(client.take_viewport_inputs_sync(&raw_input).len(), 1);
+
+        raw_input.viewport_id = secondary;
+        assert_eq!(client.take_viewport_inputs_sync(&raw_input).len(), 1);
+        assert!(client.take_pending_inputs_sync().is_empty());
+
+        let viewports = block_on(client.list_viewports());
+        assert_eq!(viewports.len(), 2);
+        assert!(viewports[0].is_root);
+        assert!(block_on(client.has_viewport(secondary.0.value())));
+        assert!(!block_on(client.has_viewport(12345)));
+    }
+
+    #[test]
+    fn test_time_control_freeze_and_step() {
+        let client = McpClient::new();
+        let ctx = egui::Context::default();
+        let mut raw_input = egui::RawInput {
+            time: Some(10.0),
+            ..Default::default()
+        };
+
+        // Real time is left untouched
+        client.apply_time_control_sync(&ctx, &mut raw_input);
+        assert_eq!(raw_input.time, Some(10.0));
+
+        block_on(client.freeze_time());
+        raw_input.time = Some(11.0);
+        client.apply_time_control_sync(&ctx, &mut raw_input);
+        assert_eq!(raw_input.time, Some(11.0));
+        assert_eq!(raw_input.predicted_dt, 0.0);
+
+        let mut rx = block_on(client.step_frames(2, 500.0));
+        raw_input.time = Some(99.0);
+        client.apply_time_control_sync(&ctx, &mut raw_input);
+        assert_eq!(raw_input.time, Some(11.5));
+        assert_eq!(raw_input.predicted_dt, 0.5);
+        assert!(rx.try_recv().is_err());
+
+        client.apply_time_control_sync(&ctx, &mut raw_input);
+        assert_eq!(rx.try_recv().unwrap(), 12.0);
+
+        // Stays frozen after stepping
+        client.apply_time_control_sync(&ctx, &mut raw_input);
+        assert_eq!(raw_input.time, Some(12.0));
+
+        block_on(client.resume_time());
+        assert_eq!(block_on(client.time_state()), (false, None));
+    }
+
+    #[test]
+    fn test_record_idle_state_counts_idle_frames() {
+        let client = McpClient::new();
+        let ctx = egui::Context::default();
+
+        // egui requests a few repaints while starting up, then settles
+        for _ in 0..10 {
+            let _ = ctx.run(egui::RawInput::default(), |ctx| {
+                client.record_idle_state_sync(ctx);
+            });
+        }
+        let state = block_on(client.get_idle_state());
+        assert_eq!(state.frame_count, 10);
+        assert!(state.idle_frames > 0);
+        assert!(!state.repaint_requested);
+
+        // Queued inputs keep the UI busy
+        block_on(client.queue_input(PendingInput::MoveMouse { x: 1.0, y: 2.0 }));
+        let _ = ctx.run(egui::RawInput::default(), |ctx| {
+            client.record_idle_state_sync(ctx);
+        });
+        let state = block_on(client.get_idle_state());
+        assert_eq!(state.idle_frames, 0);
+        assert_eq!(state.pending_inputs, 1);
+    }
+
+    #[test]
+    fn test_handle_inspect_sync_reports_focus_and_layers() {
+        let client = McpClient::new();
+        let ctx = egui::Context::default();
+        let mut rx = block_on(client.request_context_info(None));
+
+        let mut button_id = None;
+        let _ = ctx.run(egui::RawInput::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                let response = ui.button("Focus me");
+                response.request_focus();
+                button_id = Some(response.id);
+            });
+            client.handle_inspect_sync(ctx);
+        });
+
+        let info = rx.try_recv().unwrap();
+        assert_eq!(info.viewport_id, egui::ViewportId::ROOT.0.value());
+        let focused = info.focused.expect("focused widget");
+        assert_eq!(focused.id, button_id.unwrap().value());
+        assert!(focused.rect.is_some());
+        assert!(info.layers.iter().any(|l| l.order == "Background"));
+        assert!(!info.any_popup_open);
+    }
+
+    #[test]
+    fn test_plugin_records_paint_stats() {
+        let client = McpClient::new();
+        let ctx = egui::Context::default();
+        ctx.add_plugin(McpPlugin::new(client.clone()).with_tessellation_stats(true));
+        block_on(client.start_perf_recording(0));
+
+        for _ in 0..2 {
+            let _ = ctx.run(egui::RawInput::default(), |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    ui.label("Hello");
+                });
+                client.record_frame_sync(std::time::Duration::from_millis(16));
+            });
+        }
+
+        let paint = block_on(client.get_frame_stats()).paint.unwrap();
+        assert!(paint.shapes > 0);
+        assert!(paint.text_shapes > 0);
+        assert!(paint.vertices.unwrap() > 0);
+
+        let summary = block_on(client.get_perf_report()).unwrap().paint.unwrap();
+        assert_eq!(summary.frames, 2);
+        // The font atlas is uploaded on the first frame
+        assert!(summary.frames_with_texture_changes >= 1);
+        assert!(summary.max_vertices.is_some());
+    }
+
+    #[test]
+    fn test_summarize_paint_stats_counts_repaint_causes() {
+        let sample = |causes: &[&str]| PaintStats {
+            shapes: 10,
+            repaint_causes: causes.iter().map(|c| c.to_string()).collect(),
+            ..Default::default()
+        };
+        let summary = summarize_paint_stats(&[
+            sample(&["plot.rs:1 animation"]),
+            sample(&["plot.rs:1 animation", "input"]),
+            sample(&[]),
+        ])
+        .unwrap();
+
+        assert_eq!(summary.frames, 3);
+        assert_eq!(summary.max_shapes, 10);
+        assert!(summary.avg_vertices.is_none());
+        assert_eq!(summary.repaint_causes[0].cause, "plot.rs:1 animation");
+        assert_eq!(summary.repaint_causes[0].frames, 2);
+        assert!(summarize_paint_stats(&[]).is_none());
+    }
+
+    #[test]
+    fn test_measure_latency_reports_changed_frame() {
+        let client = McpClient::new();
+        let ctx = egui::Context::default();
+        client.install_plugin(&ctx);
+        let mut rx =
+            block_on(client.measure_latency(None, PendingInput::Keyboard { key: "a".into() }));
+
+        let mut pressed = false;
+        for _ in 0..4 {
+            let raw_input = egui::RawInput::default();
+            pressed |= !client.take_viewport_inputs_sync(&raw_input).is_empty();
+            let _ = ctx.run(raw_input, |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    ui.label(if pressed { "pressed" } else { "waiting" });
+                });
+            });
+        }
+
+        let report = rx.try_recv().unwrap();
+        assert!(report.changed);
+        assert_eq!(report.consumed_frame, Some(1));
+        assert_eq!(report.changed_frame, Some(1));
+        assert_eq!(report.frames, Some(1));
+        assert!(report.latency_ms.is_some());
+    }
+
+    #[test]
+    fn test_cancel_latency_measurement_without_baseline() {
+        let client = McpClient::new();
+        let _rx =
+            block_on(client.measure_latency(None, PendingInput::MoveMouse { x: 0.0, y: 0.0 }));
+
+        // The input is held back until a baseline frame was captured
+        assert!(client.take_pending_inputs_sync().is_empty());
+        assert!(block_on(client.cancel_latency_measurement()).is_none());
+    }
+
+    #[test]
+    fn test_summarize_memory_samples() {
+        let sample = |live_bytes, allocations| MemorySample {
+            live_bytes,
+            allocations,
+            allocated_bytes: allocations * 100,
+        };
+        let summary =
+            summarize_memory_samples(&[sample(1000, 10), sample(5000, 30), sample(3000, 20)])
+                .unwrap();
+
+        assert_eq!(summary.frames, 3);
+        assert_eq!(summary.peak_live_bytes, 5000);
+        assert_eq!(summary.live_bytes_growth, 2000);
+        assert_eq!(summary.avg_frame_allocations, 20.0);
+        assert_eq!(summary.max_frame_allocated_bytes, 3000);
+        assert!(summarize_memory_samples(&[]).is_none());
+    }
+
+    #[test]
+    fn test_plugin_records_texture_memory() {
+        let client = McpClient::new();
+        let ctx = egui::Context::default();
+        client.install_plugin(&ctx);
+        let _ = ctx.run(egui::RawInput::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| ui.label("text"));
+        });
+
+        let stats = block_on(client.get_memory_stats());
+        let textures = stats.textures.unwrap();
+        // The font atlas is always allocated
+        assert!(textures.textures >= 1);
+        assert!(textures.bytes > 0);
+    }
+
+    #[test]
+    fn test_frame_time_histogram_buckets() {
+        let histogram = frame_time_histogram(&[1.0, 16.0, 16.6, 40.0, 1000.0]);
+
+        assert_eq!(histogram.len(), HISTOGRAM_BOUNDS_MS.len() + 1);
+        assert_eq!(histogram[0].count, 1);
+        assert_eq!(histogram[3].min_ms, 12.0);
+        assert_eq!(histogram[3].count, 1);
+        assert_eq!(histogram[4].count, 1);
+        assert_eq!(histogram[7].count, 1);
+        let last = histogram.last().unwrap();
+        assert!(last.max_ms.is_none());
+        assert_eq!(last.count, 1);
+        assert_eq!(histogram.iter().map(|b| b.count).sum::<usize>(), 5);
+    }
+
+    #[test]
+    fn test_find_jank_streaks() {
+        let times = [10.0, 20.0, 30.0, 10.0, 50.0, 10.0, 20.0, 20.0, 20.0];
+        let mut end = 0.0;
+        let ends: Vec<f32> = times
+            .iter()
+            .map(|t| {
+                end += t;
+                end
+            })
+            .collect();
+
+        let streaks = find_jank_streaks(&times, &ends, 16.6);
+
+        assert_eq!(streaks.len(), 3);
+        assert_eq!(streaks[0].frames, 3);
+        assert_eq!(streaks[0].start_ms, 130.0);
+        assert_eq!(streaks[0].duration_ms, 60.0);
+        assert_eq!(streaks[1].frames, 2);
+        assert_eq!(streaks[1].start_ms, 10.0);
+        assert_eq!(streaks[1].max_frame_time_ms, 30.0);
+        assert_eq!(streaks[2].frames, 1);
+        assert!(find_jank_streaks(&[10.0], &[10.0], 16.6).is_empty());
+    }
+
+    #[test]
+    fn test_perf_report_counts_frames_over_budget() {
+        let client = McpClient::new();
+        block_on(client.start_perf_recording_with_budgets(0, vec![33.3, 16.6]));
+        for ms in [10, 20, 40] {
+            client.record_frame_sync(std::time::Duration::from_millis(ms));
+        }
+
+        let report = block_on(client.get_perf_report()).unwrap();
+
+        assert_eq!(report.jank_budget_ms, 16.6);
+        assert_eq!(report.budgets.len(), 2);
+        assert_eq!(report.budgets[0].frames_over, 2);
+        assert_eq!(report.budgets[1].frames_over, 1);
+        assert_eq!(report.jank_streaks.len(), 1);
+        assert_eq!(report.jank_streaks[0].frames, 2);
+    }
+
+    #[test]
+    fn test_screenshot_handshake_sync() {
+        let client = McpClient::new();
+        assert!(!client.take_screenshot_request_sync());
+
+        let mut rx = block_on(client.request_screenshot());
+        assert!(client.take_screenshot_request_sync());
+
+        client.set_screenshot_sync(vec![1, 2, 3]);
+        assert_eq!(rx.try_recv().unwrap(), vec![1, 2, 3]);
+        assert!(!client.take_screenshot_request_sync());
+    }
+
+    #[cfg(feature = "software-renderer")]
+    #[test]
+    fn test_software_screenshot_in_headless_loop() {
+        let client = McpClient::new();
+        let ctx = egui::Context::default();
+        ctx.add_plugin(McpPlugin::new(client.clone()).with_software_screenshots(true));
+        let raw_input = || egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(
+                egui::Pos2::ZERO,
+                egui::vec2(80.0, 40.0),
+            )),
+            ..Default::default()
+        };
+        let ui = |ctx: &egui::Context| {
+            egui::CentralPanel::default().show(ctx, |ui| ui.label("Headless"));
+        };
+
+        // The first frame uploads the font atlas before anything is requested
+        let _ = ctx.run(raw_input(), ui);
+        let mut rx = block_on(client.request_screenshot());
+        let _ = ctx.run(raw_input(), ui);
+
+        let png = rx.try_recv().unwrap();
+        let image = image::load_from_memory(&png).unwrap();
+        assert_eq!((image.width(), image.height()), (80, 40));
+        assert!(!client.take_screenshot_request_sync());
+    }
+
+    #[test]
+    fn test_frame_shapes_pages_fit_in_a_message() {
+        let rect = egui_mcp_protocol::Rect {
+            x: 0.0,
+            y: 0.0,
+            width: 100.0,
+            height: 100.0,
+        };
+        // Long plot lines, about 4 MB of JSON
+        let path = PaintedShape {
+            layer_id: 1,
+            layer_order: "Background".to_string(),
+            clip_rect: rect,
+            shape: VectorShape::Path {
+                points: (0..10_000).map(|i| [i as f32 * 0.01, 0.5]).collect(),
+                closed: false,
+                fill: None,
+                stroke: None,
+            },
+        };
+        let client = McpClient::new();
+        block_on(client.set_frame_shapes(
+            FrameShapes {
+                viewport_id: 0,
+                screen_rect: rect,
+                pixels_per_point: 1.0,
+                shapes: vec![path; 30],
+            },
+            false,
+        ));
+
+        let mut shapes = Vec::new();
+        let mut frame_bytes = 0;
+        loop {
+            let (page, total_shapes) =
+                block_on(client.frame_shapes_page(shapes.len(), usize::MAX)).unwrap();
+            if page.shapes.is_empty() {
+                break;
+            }
+            let response = Response::FrameShapesResponse {
+                shapes: Box::new(page.clone()),
+                total_shapes,
+            };
+            let size = serde_json::to_vec(&response).unwrap().len();
+            assert!(size < egui_mcp_protocol::MAX_MESSAGE_SIZE);
+            frame_bytes += size;
+            shapes.extend(page.shapes);
+        }
+        assert!(frame_bytes > egui_mcp_protocol::MAX_MESSAGE_SIZE);
+        assert_eq!(shapes.len(), 30);
+    }
+
+    #[test]
+    fn test_frame_shapes_text_only() {
+        let rect = egui_mcp_protocol::Rect {
+            x: 0.0,
+            y: 0.0,
+            width: 100.0,
+            height: 20.0,
+        };
+        let painted = |shape| PaintedShape {
+            layer_id: 1,
+            layer_order: "Background".to_string(),
+            clip_rect: rect,
+            shape,
+        };
+        let frame = FrameShapes {
+            viewport_id: 0,
+            screen_rect: rect,
+            pixels_per_point: 1.0,
+            shapes: vec![
+                painted(VectorShape::Path {
+                    points: vec![[0.0, 0.0], [10.0, 10.0]],
+                    closed: false,
+                    fill: None,
+                    stroke: None,
+                }),
+                painted(VectorShape::Text {
+                    rect,
+                    text: "Hello".to_string(),
+                    runs: Vec::new(),
+                    angle: 0.0,
+                }),
+            ],
+        };
+        let client = McpClient::new();
+        block_on(client.set_frame_shapes(frame, true));
+
+        let (page, total_shapes) = block_on(client.frame_shapes_page(0, usize::MAX)).unwrap();
+        assert_eq!(total_shapes, 1);
+        assert!(matches!(page.shapes[0].shape, VectorShape::Text { .. }));
+    }
+
+    #[test]
+    fn test_get_highlights_sync_drops_expired() {
+        let client = McpClient::new();
+        let rect = egui::Rect::from_min_size(egui::pos2(0.0, 0.0), egui::vec2(10.0, 10.0));
+        block_on(client.add_highlight(Highlight {
+            rect,
+            color: egui::Color32::RED,
+            expires_at: None,
+        }));
+        // Expired by the time it is checked (no `Instant` subtraction, which
+        // can underflow shortly after boot)
+        block_on(client.add_highlight(Highlight {
+            rect,
+            color: egui::Color32::RED,
+            expires_at: Some(std::time::Instant::now()),
+        }));
+
+        assert_eq!(client.get_highlights_sync().len(), 1);
+    }
+
     #[test]
     fn test_parse_special_key_command_keys() {
         // Basic command keys
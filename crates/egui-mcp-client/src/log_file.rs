@@ -0,0 +1,288 @@
+//! Rotating JSONL file sink for captured logs
+//!
+//! The in-memory [`LogBuffer`](crate::LogBuffer) only keeps the last
+//! `max_entries` entries. A [`LogFileSink`] additionally appends every
+//! captured [`LogEntry`] as one JSON line to a file, rotating it when it
+//! grows past `max_bytes`:
+//!
+//! ```text
+//! app.jsonl      <- current file
+//! app.jsonl.1    <- previous file
+//! app.jsonl.2    <- oldest kept file
+//! ```
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use egui_mcp_client::{LogFileSink, McpClient, McpLogLayer};
+//!
+//! let sink = LogFileSink::new("logs/app.jsonl", 10 * 1024 * 1024, 3)?;
+//! let (mcp_layer, log_buffer) = McpLogLayer::new(1000);
+//! let mcp_layer = mcp_layer.with_file_sink(sink.clone());
+//!
+//! let mcp_client = McpClient::new()
+//!     .with_log_buffer_sync(log_buffer)
+//!     .with_log_file_sync(sink); // enables `GetLogs { history: true }`
+//! ```
+
+use egui_mcp_protocol::LogEntry;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Number of entries a history query returns when it sets no limit
+pub(crate) const DEFAULT_HISTORY_LIMIT: usize = 1000;
+
+/// Size of the entries returned by a history query, leaving room for the
+/// rest of the response below the IPC message limit
+const MAX_HISTORY_BYTES: usize = egui_mcp_protocol::MAX_MESSAGE_SIZE / 2;
+
+/// Appends log entries as JSON lines to a size-rotated file
+#[derive(Clone)]
+pub struct LogFileSink {
+    inner: Arc<Mutex<SinkState>>,
+}
+
+struct SinkState {
+    path: PathBuf,
+    /// Rotate when the current file would grow past this size
+    max_bytes: u64,
+    /// Number of files to keep, including the current one
+    max_files: usize,
+    /// Current file (None after a failed rotation; reopened on the next write)
+    file: Option<File>,
+    /// Size of the current file
+    size: u64,
+}
+
+impl LogFileSink {
+    /// Open (or create) the log file at `path`, appending to existing content
+    ///
+    /// - `max_bytes`: rotate the file when it would grow past this size
+    /// - `max_files`: number of files to keep, including the current one
+    pub fn new(
+        path: impl Into<PathBuf>,
+        max_bytes: u64,
+        max_files: usize,
+    ) -> std::io::Result<Self> {
+        let path = path.into();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = open_append(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            inner: Arc::new(Mutex::new(SinkState {
+                path,
+                max_bytes,
+                max_files: max_files.max(1),
+                file: Some(file),
+                size,
+            })),
+        })
+    }
+
+    /// Path of the current log file
+    pub fn path(&self) -> PathBuf {
+        self.inner.lock().path.clone()
+    }
+
+    /// Append an entry as a JSON line, rotating the file first if needed
+    pub fn write(&self, entry: &LogEntry) -> std::io::Result<()> {
+        let mut line = serde_json::to_vec(entry).map_err(std::io::Error::other)?;
+        line.push(b'\n');
+
+        let mut state = self.inner.lock();
+        if state.size > 0 && state.size + line.len() as u64 > state.max_bytes {
+            state.rotate()?;
+        }
+        if state.file.is_none() {
+            state.file = Some(open_append(&state.path)?);
+            state.size = 0;
+        }
+        if let Some(file) = state.file.as_mut() {
+            file.write_all(&line)?;
+        }
+        state.size += line.len() as u64;
+        Ok(())
+    }
+
+    /// Read the newest persisted entries matching `matches`, oldest first
+    ///
+    /// Returns at most `limit` entries whose JSON lines add up to at most
+    /// [`MAX_HISTORY_BYTES`], so they fit in one IPC response. The files are
+    /// opened under the sink's lock but read after releasing it, newest file
+    /// first, stopping once enough entries are found; logging is not blocked
+    /// by the read. Lines that cannot be parsed (e.g. cut off by a crash) are
+    /// skipped.
+    pub fn read_history(
+        &self,
+        matches: impl Fn(&LogEntry) -> bool,
+        limit: usize,
+    ) -> std::io::Result<Vec<LogEntry>> {
+        // Open files keep their content when they are rotated while reading
+        let files = {
+            let state = self.inner.lock();
+            let mut files = Vec::new();
+            for index in 0..state.max_files {
+                match File::open(state.file_path(index)) {
+                    Ok(file) => files.push(file),
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            files
+        };
+
+        let mut newest_first = Vec::new();
+        let mut bytes = 0;
+        for file in files {
+            let wanted = limit - newest_first.len();
+            if wanted == 0 {
+                break;
+            }
+            // The newest matching entries of this file, with their line length
+            let mut matched = VecDeque::new();
+            for line in BufReader::new(file).lines() {
+                let line = line?;
+                if let Ok(entry) = serde_json::from_str::<LogEntry>(&line)
+                    && matches(&entry)
+                {
+                    if matched.len() == wanted {
+                        matched.pop_front();
+                    }
+                    matched.push_back((line.len() + 1, entry));
+                }
+            }
+            for (size, entry) in matched.into_iter().rev() {
+                if bytes + size > MAX_HISTORY_BYTES {
+                    newest_first.reverse();
+                    return Ok(newest_first);
+                }
+                bytes += size;
+                newest_first.push(entry);
+            }
+        }
+        newest_first.reverse();
+        Ok(newest_first)
+    }
+}
+
+impl SinkState {
+    /// Path of the file with the given age (0 = current file)
+    fn file_path(&self, index: usize) -> PathBuf {
+        if index == 0 {
+            self.path.clone()
+        } else {
+            let mut name = self.path.clone().into_os_string();
+            name.push(format!(".{}", index));
+            PathBuf::from(name)
+        }
+    }
+
+    /// Shift every file one place older, dropping the oldest, and start a new file
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file = None;
+        let oldest = self.file_path(self.max_files - 1);
+        if oldest.exists() {
+            std::fs::remove_file(&oldest)?;
+        }
+        for index in (0..self.max_files - 1).rev() {
+            let from = self.file_path(index);
+            if from.exists() {
+                std::fs::rename(&from, self.file_path(index + 1))?;
+            }
+        }
+        self.file = Some(open_append(&self.path)?);
+        self.size = 0;
+        Ok(())
+    }
+}
+
+fn open_append(path: &Path) -> std::io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(seq: u64) -> LogEntry {
+        LogEntry {
+            level: "INFO".to_string(),
+            target: "test".to_string(),
+            message: format!("entry {}", seq),
+            timestamp_ms: 0,
+            seq,
+            fields: Default::default(),
+            spans: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_rotation_keeps_newest_files() {
+        let dir = std::env::temp_dir().join(format!("egui-mcp-log-file-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let line_len = serde_json::to_vec(&entry(1)).unwrap().len() as u64 + 1;
+        // Two entries per file, three files
+        let sink = LogFileSink::new(dir.join("app.jsonl"), line_len * 2, 3).unwrap();
+
+        for seq in 1..=7 {
+            sink.write(&entry(seq)).unwrap();
+        }
+
+        let history = |limit| -> Vec<u64> {
+            sink.read_history(|_| true, limit)
+                .unwrap()
+                .iter()
+                .map(|e| e.seq)
+                .collect()
+        };
+        assert_eq!(history(10), vec![3, 4, 5, 6, 7]);
+        // The newest entries, across files
+        assert_eq!(history(3), vec![5, 6, 7]);
+        assert_eq!(history(0), Vec::<u64>::new());
+        assert!(dir.join("app.jsonl.2").exists());
+        assert!(!dir.join("app.jsonl.3").exists());
+
+        // Reopening appends to the current file
+        let sink = LogFileSink::new(dir.join("app.jsonl"), line_len * 2, 3).unwrap();
+        sink.write(&entry(8)).unwrap();
+        let seqs: Vec<u64> = sink
+            .read_history(|e| e.seq % 2 == 0, 10)
+            .unwrap()
+            .iter()
+            .map(|e| e.seq)
+            .collect();
+        assert_eq!(seqs, vec![4, 6, 8]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_history_fits_in_a_message() {
+        let dir = std::env::temp_dir().join(format!("egui-mcp-log-history-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let sink = LogFileSink::new(dir.join("app.jsonl"), 1024 * 1024, 3).unwrap();
+        let message = "x".repeat(1000);
+        for seq in 1..=2000 {
+            sink.write(&LogEntry {
+                message: message.clone(),
+                ..entry(seq)
+            })
+            .unwrap();
+        }
+
+        let entries = sink.read_history(|_| true, usize::MAX).unwrap();
+        let size = serde_json::to_vec(&entries).unwrap().len();
+        assert!(size <= MAX_HISTORY_BYTES + 1);
+        assert!(entries.len() < 2000);
+        // The newest entries are kept
+        assert_eq!(entries.last().unwrap().seq, 2000);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -21,15 +21,29 @@
 //!     // ... run egui app
 //! }
 //! ```
+//!
+//! Use [`McpLogLayer::with_file_sink`] to also persist the entries to a
+//! rotating JSONL file (see [`LogFileSink`]).
+//!
+//! The layer captures every event by default. Pass [`McpLogLayer::filter`] to
+//! `McpClient::with_log_filter_sync()` to let the MCP server change which
+//! events are captured at runtime with `EnvFilter` directives.
 
-use egui_mcp_protocol::LogEntry;
-use parking_lot::Mutex;
-use std::collections::VecDeque;
+use crate::log_file::LogFileSink;
+use egui_mcp_protocol::{LogEntry, LogSpan};
+use parking_lot::{Mutex, RwLock};
+use serde_json::Value;
+use std::collections::{BTreeMap, VecDeque};
 use std::sync::Arc;
-use tracing::Subscriber;
+use std::sync::atomic::{AtomicU64, Ordering};
 use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::subscriber::Interest;
+use tracing::{Metadata, Subscriber};
+use tracing_subscriber::EnvFilter;
 use tracing_subscriber::Layer;
 use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
 
 /// Shared log buffer type
 pub type LogBuffer = Arc<Mutex<VecDeque<LogEntry>>>;
@@ -37,11 +51,50 @@ pub type LogBuffer = Arc<Mutex<VecDeque<LogEntry>>>;
 /// Default maximum message length (8KB)
 pub const DEFAULT_MAX_MESSAGE_LENGTH: usize = 8 * 1024;
 
+/// Reloadable filter deciding which events [`McpLogLayer`] captures
+///
+/// Holds an optional `EnvFilter`; without one every event is captured.
+#[derive(Clone, Default)]
+pub struct LogFilter {
+    filter: Arc<RwLock<Option<EnvFilter>>>,
+}
+
+impl LogFilter {
+    /// Replace the filter with `EnvFilter` directives (e.g. `warn,my_app::net=debug`)
+    ///
+    /// Empty directives remove the filter so every event is captured again.
+    pub fn set(&self, directives: &str) -> Result<(), String> {
+        let filter = if directives.trim().is_empty() {
+            None
+        } else {
+            Some(
+                EnvFilter::builder()
+                    .parse(directives)
+                    .map_err(|e| format!("Invalid filter directives: {}", e))?,
+            )
+        };
+        *self.filter.write() = filter;
+        // Callsites cache whether they are enabled; let them ask the new filter
+        tracing::callsite::rebuild_interest_cache();
+        Ok(())
+    }
+
+    /// Current directives (`None` = capture everything)
+    pub fn directives(&self) -> Option<String> {
+        self.filter.read().as_ref().map(|filter| filter.to_string())
+    }
+}
+
 /// A tracing Layer that captures log entries for MCP access
 pub struct McpLogLayer {
     buffer: LogBuffer,
+    filter: LogFilter,
+    /// Optional file the entries are also written to
+    file_sink: Option<LogFileSink>,
     max_entries: usize,
     max_message_length: usize,
+    /// Sequence number of the last captured entry
+    last_seq: AtomicU64,
 }
 
 impl McpLogLayer {
@@ -65,8 +118,11 @@ impl McpLogLayer {
         let buffer = Arc::new(Mutex::new(VecDeque::with_capacity(max_entries)));
         let layer = Self {
             buffer: buffer.clone(),
+            filter: LogFilter::default(),
+            file_sink: None,
             max_entries,
             max_message_length,
+            last_seq: AtomicU64::new(0),
         };
         (layer, buffer)
     }
@@ -76,6 +132,22 @@ impl McpLogLayer {
         self.buffer.clone()
     }
 
+    /// Also write every captured entry to a rotating JSONL file
+    ///
+    /// Pass a clone of the sink to `McpClient::with_log_file_sync()` to read
+    /// the persisted history with `GetLogs`.
+    pub fn with_file_sink(mut self, sink: LogFileSink) -> Self {
+        self.file_sink = Some(sink);
+        self
+    }
+
+    /// Get a handle to the reloadable filter of this layer
+    ///
+    /// Pass it to `McpClient::with_log_filter_sync()` to enable `SetLogFilter`.
+    pub fn filter(&self) -> LogFilter {
+        self.filter.clone()
+    }
+
     /// Truncate a message if it exceeds the maximum length
     fn truncate_message(&self, message: String) -> String {
         if message.len() <= self.max_message_length {
@@ -95,56 +167,189 @@ impl McpLogLayer {
     }
 }
 
-/// Visitor to extract the message field from a tracing event
+/// Visitor collecting the message and the structured fields of an event or span
 #[derive(Default)]
-struct MessageVisitor {
-    message: String,
+struct FieldVisitor {
+    message: Option<String>,
+    fields: BTreeMap<String, Value>,
 }
 
-impl Visit for MessageVisitor {
-    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
-        if field.name() == "message" {
-            self.message = format!("{:?}", value);
-            // Remove surrounding quotes if present
-            if self.message.starts_with('"') && self.message.ends_with('"') {
-                self.message = self.message[1..self.message.len() - 1].to_string();
+impl FieldVisitor {
+    fn record_value(&mut self, field: &Field, value: Value) {
+        match field.name() {
+            "message" => {
+                self.message = Some(match value {
+                    Value::String(message) => message,
+                    other => other.to_string(),
+                });
+            }
+            // Metadata added by tracing-log for `log` records
+            name if name.starts_with("log.") => {}
+            name => {
+                self.fields.insert(name.to_string(), value);
             }
         }
     }
 
+    /// The message, or all fields as `key=value` if the event has no message
+    fn message(&self) -> String {
+        self.message.clone().unwrap_or_else(|| {
+            self.fields
+                .iter()
+                .map(|(name, value)| match value {
+                    Value::String(s) => format!("{}={}", name, s),
+                    other => format!("{}={}", name, other),
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+    }
+}
+
+impl Visit for FieldVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.record_value(field, Value::String(format!("{:?}", value)));
+    }
+
     fn record_str(&mut self, field: &Field, value: &str) {
-        if field.name() == "message" {
-            self.message = value.to_string();
-        }
+        self.record_value(field, Value::String(value.to_string()));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.record_value(field, value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.record_value(field, value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.record_value(field, value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.record_value(field, value.into());
+    }
+
+    fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
+        self.record_value(field, Value::String(value.to_string()));
     }
 }
 
+/// Fields recorded on a span, stored in its extensions
+struct SpanFields(BTreeMap<String, Value>);
+
 impl<S> Layer<S> for McpLogLayer
 where
-    S: Subscriber,
+    S: Subscriber + for<'a> LookupSpan<'a>,
 {
-    fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
-        let mut visitor = MessageVisitor::default();
-        event.record(&mut visitor);
+    // The filter only decides what this layer captures, so callsites always
+    // stay enabled for the other layers. The span callbacks are forwarded so
+    // span-based directives (`[span{field=value}]=debug`) work too.
+
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        if let Some(filter) = self.filter.filter.read().as_ref() {
+            Layer::<S>::register_callsite(filter, metadata);
+        }
+        Interest::always()
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        if let Some(filter) = self.filter.filter.read().as_ref() {
+            filter.on_enter(id, ctx);
+        }
+    }
+
+    fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+        if let Some(filter) = self.filter.filter.read().as_ref() {
+            filter.on_exit(id, ctx);
+        }
+    }
 
-        // If no message field, try to get any field content
-        if visitor.message.is_empty() {
-            let mut any_visitor = AnyFieldVisitor::default();
-            event.record(&mut any_visitor);
-            visitor.message = any_visitor.fields.join(", ");
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        if let Some(filter) = self.filter.filter.read().as_ref() {
+            filter.on_close(id, ctx);
         }
+    }
 
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if let Some(filter) = self.filter.filter.read().as_ref() {
+            filter.on_new_span(attrs, id, ctx.clone());
+        }
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut visitor = FieldVisitor::default();
+        attrs.record(&mut visitor);
+        span.extensions_mut().insert(SpanFields(visitor.fields));
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(filter) = self.filter.filter.read().as_ref() {
+            filter.on_record(id, values, ctx.clone());
+        }
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut visitor = FieldVisitor::default();
+        values.record(&mut visitor);
+        if let Some(fields) = span.extensions_mut().get_mut::<SpanFields>() {
+            fields.0.extend(visitor.fields);
+        }
+    }
+
+    fn on_event(&self, event: &tracing::Event<'_>, ctx: Context<'_, S>) {
+        if let Some(filter) = self.filter.filter.read().as_ref()
+            && !filter.enabled(event.metadata(), ctx.clone())
+        {
+            return;
+        }
+
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+
+        let spans = ctx
+            .event_scope(event)
+            .map(|scope| {
+                scope
+                    .from_root()
+                    .map(|span| LogSpan {
+                        name: span.name().to_string(),
+                        target: span.metadata().target().to_string(),
+                        fields: span
+                            .extensions()
+                            .get::<SpanFields>()
+                            .map(|fields| fields.0.clone())
+                            .unwrap_or_default(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut buf = self.buffer.lock();
+        // Assigned under the lock so entries in the buffer stay in order
+        let seq = self.last_seq.fetch_add(1, Ordering::Relaxed) + 1;
         let entry = LogEntry {
             level: event.metadata().level().to_string(),
             target: event.metadata().target().to_string(),
-            message: self.truncate_message(visitor.message),
+            message: self.truncate_message(visitor.message()),
             timestamp_ms: std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .map(|d| d.as_millis() as u64)
                 .unwrap_or(0),
+            seq,
+            fields: visitor.fields,
+            spans,
         };
 
-        let mut buf = self.buffer.lock();
+        // Written under the buffer lock so the file keeps the same order
+        if let Some(sink) = &self.file_sink
+            && let Err(e) = sink.write(&entry)
+        {
+            // Not logged through tracing, which would re-enter this layer
+            eprintln!("Failed to write log file {:?}: {}", sink.path(), e);
+        }
+
         buf.push_back(entry);
         while buf.len() > self.max_entries {
             buf.pop_front();
@@ -152,31 +357,82 @@ where
     }
 }
 
-/// Visitor to capture any field content when no message field is present
-#[derive(Default)]
-struct AnyFieldVisitor {
-    fields: Vec<String>,
+/// Filters for reading the log buffer
+#[derive(Debug, Clone, Default)]
+pub struct LogQuery {
+    /// Minimum log level (TRACE, DEBUG, INFO, WARN, ERROR)
+    pub level: Option<String>,
+    /// Only entries whose target starts with this prefix
+    pub target: Option<String>,
+    /// Only entries whose target matches this regex
+    pub target_regex: Option<String>,
+    /// Only entries whose message matches this regex
+    pub message_regex: Option<String>,
+    /// Only entries with a sequence number greater than this cursor
+    pub since: Option<u64>,
+    /// Maximum number of entries (most recent ones, or the oldest after `since`)
+    pub limit: Option<usize>,
 }
 
-impl Visit for AnyFieldVisitor {
-    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
-        self.fields.push(format!("{}={:?}", field.name(), value));
-    }
-
-    fn record_str(&mut self, field: &Field, value: &str) {
-        self.fields.push(format!("{}={}", field.name(), value));
-    }
+impl LogQuery {
+    /// Select the matching entries of a log buffer
+    ///
+    /// Returns the entries (oldest first) and the cursor to pass as `since`
+    /// to continue after them, or an error if a regex is invalid. The cursor
+    /// is lower than `since` when the sequence numbers started over (the app
+    /// restarted since `since` was returned).
+    pub fn apply(&self, buffer: &VecDeque<LogEntry>) -> Result<(Vec<LogEntry>, u64), String> {
+        let matches = self.matcher()?;
+        let since = self.since.unwrap_or(0);
+        let filtered: Vec<LogEntry> = buffer
+            .iter()
+            .filter(|entry| matches(entry))
+            .cloned()
+            .collect();
+        let latest = buffer.back().map_or(since, |entry| entry.seq);
 
-    fn record_i64(&mut self, field: &Field, value: i64) {
-        self.fields.push(format!("{}={}", field.name(), value));
+        match self.limit {
+            // Paging: the oldest entries after the cursor, continuing after the last one
+            Some(n) if self.since.is_some() && filtered.len() > n => {
+                let page: Vec<LogEntry> = filtered.into_iter().take(n).collect();
+                let cursor = page.last().map_or(since, |entry| entry.seq);
+                Ok((page, cursor))
+            }
+            Some(n) => Ok((filtered.into_iter().rev().take(n).rev().collect(), latest)),
+            None => Ok((filtered, latest)),
+        }
     }
 
-    fn record_u64(&mut self, field: &Field, value: u64) {
-        self.fields.push(format!("{}={}", field.name(), value));
-    }
+    /// Predicate for the entries matching every filter except `limit`
+    ///
+    /// Returns an error if a regex is invalid.
+    pub(crate) fn matcher(&self) -> Result<impl Fn(&LogEntry) -> bool + Send + 'static, String> {
+        let compile = |pattern: &Option<String>, name: &str| {
+            pattern
+                .as_deref()
+                .map(regex::Regex::new)
+                .transpose()
+                .map_err(|e| format!("Invalid {}: {}", name, e))
+        };
+        let target_regex = compile(&self.target_regex, "target_regex")?;
+        let message_regex = compile(&self.message_regex, "message_regex")?;
+        let min_priority = self.level.as_deref().map(level_to_priority).unwrap_or(0);
+        let since = self.since.unwrap_or(0);
+        let target = self.target.clone();
 
-    fn record_bool(&mut self, field: &Field, value: bool) {
-        self.fields.push(format!("{}={}", field.name(), value));
+        Ok(move |entry: &LogEntry| {
+            entry.seq > since
+                && level_to_priority(&entry.level) >= min_priority
+                && target
+                    .as_deref()
+                    .is_none_or(|prefix| entry.target.starts_with(prefix))
+                && target_regex
+                    .as_ref()
+                    .is_none_or(|re| re.is_match(&entry.target))
+                && message_regex
+                    .as_ref()
+                    .is_none_or(|re| re.is_match(&entry.message))
+        })
     }
 }
 
@@ -195,6 +451,7 @@ pub fn level_to_priority(level: &str) -> u8 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tracing_subscriber::prelude::*;
 
     #[test]
     fn test_truncate_message_short() {
@@ -229,6 +486,101 @@ mod tests {
         assert!(truncated.is_char_boundary(truncated.len()));
     }
 
+    fn capture(f: impl FnOnce()) -> LogBuffer {
+        let (layer, buffer) = McpLogLayer::new(100);
+        let subscriber = tracing_subscriber::registry().with(layer);
+        tracing::subscriber::with_default(subscriber, f);
+        buffer
+    }
+
+    #[test]
+    fn test_captures_fields_and_spans() {
+        let buffer = capture(|| {
+            let span = tracing::info_span!("request", id = 7);
+            let _guard = span.enter();
+            tracing::info!(user = "alice", attempts = 3, "login failed");
+            tracing::warn!(retry = true);
+        });
+
+        let buf = buffer.lock();
+        assert_eq!(buf[0].message, "login failed");
+        assert_eq!(buf[0].seq, 1);
+        assert_eq!(buf[0].fields["user"], "alice");
+        assert_eq!(buf[0].fields["attempts"], 3);
+        assert!(!buf[0].fields.contains_key("message"));
+        assert_eq!(buf[0].spans.len(), 1);
+        assert_eq!(buf[0].spans[0].name, "request");
+        assert_eq!(buf[0].spans[0].fields["id"], 7);
+        assert_eq!(buf[1].message, "retry=true");
+        assert_eq!(buf[1].seq, 2);
+    }
+
+    #[test]
+    fn test_query_filters_and_cursor() {
+        let buffer = capture(|| {
+            tracing::info!(target: "app::net", "connected");
+            tracing::debug!(target: "app::ui", "clicked button");
+            tracing::warn!(target: "app::net", "timeout after 5s");
+            tracing::info!(target: "other", "hello");
+        });
+        let buf = buffer.lock();
+
+        let query = LogQuery {
+            target: Some("app::".to_string()),
+            ..Default::default()
+        };
+        let (entries, cursor) = query.apply(&buf).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(cursor, 4);
+
+        let query = LogQuery {
+            target_regex: Some("::net$".to_string()),
+            message_regex: Some(r"timeout after \d+s".to_string()),
+            ..Default::default()
+        };
+        let (entries, _) = query.apply(&buf).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].level, "WARN");
+
+        // Paging with since + limit
+        let query = LogQuery {
+            since: Some(1),
+            limit: Some(2),
+            ..Default::default()
+        };
+        let (entries, cursor) = query.apply(&buf).unwrap();
+        assert_eq!(
+            entries.iter().map(|e| e.seq).collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+        assert_eq!(cursor, 3);
+        let (entries, cursor) = LogQuery {
+            since: Some(cursor),
+            limit: Some(2),
+            ..Default::default()
+        }
+        .apply(&buf)
+        .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(cursor, 4);
+
+        // A cursor from an earlier run is answered with the lower current one
+        let (entries, cursor) = LogQuery {
+            since: Some(100),
+            ..Default::default()
+        }
+        .apply(&buf)
+        .unwrap();
+        assert!(entries.is_empty());
+        assert_eq!(cursor, 4);
+
+        let invalid = LogQuery {
+            message_regex: Some("(".to_string()),
+            ..Default::default()
+        };
+        assert!(invalid.apply(&buf).is_err());
+    }
+
     #[test]
     fn test_level_to_priority() {
         assert_eq!(level_to_priority("ERROR"), 5);
@@ -239,4 +591,43 @@ mod tests {
         assert_eq!(level_to_priority("error"), 5); // case insensitive
         assert_eq!(level_to_priority("unknown"), 0);
     }
+
+    #[test]
+    fn test_reload_filter() {
+        let (layer, buffer) = McpLogLayer::new(100);
+        let filter = layer.filter();
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::debug!(target: "app::net", "captured without filter");
+
+            filter.set("warn,app::net=debug").unwrap();
+            tracing::debug!(target: "app::net", "net debug");
+            tracing::debug!(target: "app::ui", "ui debug");
+            tracing::warn!(target: "app::ui", "ui warn");
+
+            filter.set("warn,[request]=trace").unwrap();
+            tracing::info_span!("request").in_scope(|| {
+                tracing::trace!(target: "app::ui", "in request span");
+            });
+            tracing::info!(target: "app::ui", "outside span");
+
+            filter.set("").unwrap();
+            tracing::trace!(target: "app::ui", "captured again");
+        });
+
+        let messages: Vec<String> = buffer.lock().iter().map(|e| e.message.clone()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "captured without filter",
+                "net debug",
+                "ui warn",
+                "in request span",
+                "captured again"
+            ]
+        );
+        assert_eq!(filter.directives(), None);
+        assert!(filter.set("app::net=loud").is_err());
+    }
 }
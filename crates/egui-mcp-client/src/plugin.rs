@@ -0,0 +1,217 @@
+//! egui plugin that collects data from each frame's output
+//!
+//! eframe does not hand `FullOutput` to the app, so paint statistics and the
+//! output shapes for latency measurements are gathered through egui's plugin
+//! hooks instead. The plugin also tells input recordings which widget is
+//! under the pointer and, with the `software-renderer` feature, renders
+//! screenshots (and screen recording frames) on the CPU. With the
+//! `accesskit` feature it keeps the UI tree of the last root frame for
+//! `GetUiTree`. It also answers `GetFrameShapes` with the shapes painted in
+//! each pass.
+
+use crate::McpClient;
+use egui_mcp_protocol::{PaintStats, TextureMemory};
+
+/// egui plugin that reports per-frame paint statistics to the [`McpClient`]
+///
+/// Install it once with [`McpClient::install_plugin`], or add it manually to
+/// enable tessellation statistics:
+///
+/// ```rust,ignore
+/// cc.egui_ctx
+///     .add_plugin(McpPlugin::new(mcp_client.clone()).with_tessellation_stats(true));
+/// ```
+pub struct McpPlugin {
+    client: McpClient,
+    /// Tessellate a copy of the shapes to count primitives and vertices
+    tessellation_stats: bool,
+    /// Context of the current root pass (only held between end of pass and output)
+    pass_ctx: Option<egui::Context>,
+    /// Viewport of the current pass
+    pass_viewport: Option<egui::ViewportId>,
+    /// Repaint causes of the current root pass
+    repaint_causes: Vec<String>,
+    /// CPU renderer for screenshots (None = use `ViewportCommand::Screenshot`)
+    #[cfg(feature = "software-renderer")]
+    renderer: Option<crate::SoftwareRenderer>,
+    /// Context of a pass whose output completes the pending screenshot
+    #[cfg(feature = "software-renderer")]
+    screenshot_ctx: Option<egui::Context>,
+}
+
+impl McpPlugin {
+    /// Create a plugin reporting to the given client
+    pub fn new(client: McpClient) -> Self {
+        Self {
+            client,
+            tessellation_stats: false,
+            pass_ctx: None,
+            pass_viewport: None,
+            repaint_causes: Vec::new(),
+            #[cfg(feature = "software-renderer")]
+            renderer: None,
+            #[cfg(feature = "software-renderer")]
+            screenshot_ctx: None,
+        }
+    }
+
+    /// Also count clipped primitives, vertices and indices.
+    ///
+    /// This tessellates every frame a second time, so it roughly doubles the
+    /// tessellation cost. Disabled by default.
+    pub fn with_tessellation_stats(mut self, enabled: bool) -> Self {
+        self.tessellation_stats = enabled;
+        self
+    }
+
+    /// Complete screenshot requests with [`SoftwareRenderer`](crate::SoftwareRenderer)
+    /// instead of `ViewportCommand::Screenshot`.
+    ///
+    /// Use this for headless `egui::Context::run` loops and renderers that
+    /// cannot take screenshots. Add the plugin before the first frame so the
+    /// renderer receives the font atlas, and don't call
+    /// `handle_screenshot_sync` as well.
+    #[cfg(feature = "software-renderer")]
+    pub fn with_software_screenshots(mut self, enabled: bool) -> Self {
+        self.renderer = enabled.then(crate::SoftwareRenderer::new);
+        self
+    }
+}
+
+impl egui::Plugin for McpPlugin {
+    fn debug_name(&self) -> &'static str {
+        "egui_mcp_client::McpPlugin"
+    }
+
+    fn on_end_pass(&mut self, ctx: &egui::Context) {
+        self.pass_viewport = Some(ctx.viewport_id());
+        self.client.handle_frame_shapes_sync(ctx);
+        if self.client.is_recording_input_sync() {
+            self.client
+                .record_hovered_widget_sync(ctx.viewport_id(), crate::hovered_widget(ctx));
+        }
+        #[cfg(feature = "software-renderer")]
+        if self.renderer.is_some() && self.client.screenshot_pending_for_sync(ctx.viewport_id()) {
+            self.screenshot_ctx = Some(ctx.clone());
+        }
+        // Only the root viewport is measured, like the frame timings
+        if ctx.viewport_id() != egui::ViewportId::ROOT {
+            self.pass_ctx = None;
+            return;
+        }
+        self.repaint_causes = ctx
+            .repaint_causes()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        self.pass_ctx = Some(ctx.clone());
+    }
+
+    fn output_hook(&mut self, output: &mut egui::FullOutput) {
+        if let Some(viewport) = self.pass_viewport.take() {
+            self.client
+                .record_output_shapes_sync(viewport, &output.shapes);
+            #[cfg(feature = "accesskit")]
+            if viewport == egui::ViewportId::ROOT
+                && let Some(update) = &output.platform_output.accesskit_update
+            {
+                self.client
+                    .record_ui_tree_sync(crate::accesskit_tree::ui_tree(update));
+            }
+        }
+        #[cfg(feature = "software-renderer")]
+        self.render_screenshot(output);
+
+        // Take the context so the plugin never keeps it alive between passes
+        let Some(ctx) = self.pass_ctx.take() else {
+            return;
+        };
+
+        let (textures, bytes) = ctx
+            .tex_manager()
+            .read()
+            .allocated()
+            .fold((0, 0), |(count, bytes), (_, meta)| {
+                (count + 1, bytes + meta.bytes_used())
+            });
+        self.client
+            .record_texture_memory_sync(TextureMemory { textures, bytes });
+
+        let mut stats = PaintStats {
+            shapes: output.shapes.len(),
+            ..Default::default()
+        };
+        for clipped in &output.shapes {
+            count_shape(&clipped.shape, &mut stats);
+        }
+
+        if self.tessellation_stats {
+            let primitives = ctx.tessellate(output.shapes.clone(), output.pixels_per_point);
+            let meshes = primitives.iter().filter_map(|p| match &p.primitive {
+                egui::epaint::Primitive::Mesh(mesh) => Some(mesh),
+                egui::epaint::Primitive::Callback(_) => None,
+            });
+            let (vertices, indices) = meshes.fold((0, 0), |(v, i), mesh| {
+                (v + mesh.vertices.len(), i + mesh.indices.len())
+            });
+            stats.clipped_primitives = Some(primitives.len());
+            stats.vertices = Some(vertices);
+            stats.indices = Some(indices);
+        }
+
+        stats.textures_set = output.textures_delta.set.len();
+        stats.textures_freed = output.textures_delta.free.len();
+        stats.texture_bytes = output
+            .textures_delta
+            .set
+            .iter()
+            .map(|(_, delta)| {
+                delta.image.width() * delta.image.height() * delta.image.bytes_per_pixel()
+            })
+            .sum();
+        stats.repaint_causes = std::mem::take(&mut self.repaint_causes);
+
+        self.client.record_paint_stats_sync(stats);
+    }
+}
+
+#[cfg(feature = "software-renderer")]
+impl McpPlugin {
+    /// Keep the renderer's textures in sync and complete a pending screenshot
+    fn render_screenshot(&mut self, output: &egui::FullOutput) {
+        let Some(renderer) = self.renderer.as_mut() else {
+            return;
+        };
+        renderer.set_textures(&output.textures_delta);
+        if let Some(ctx) = self.screenshot_ctx.take() {
+            let image = renderer.render_output(&ctx, output);
+            self.client
+                .complete_screenshot_sync(ctx.viewport_id(), std::sync::Arc::new(image));
+        }
+        renderer.free_textures(&output.textures_delta);
+    }
+}
+
+/// Count a shape (recursing into `Shape::Vec`) by kind
+fn count_shape(shape: &egui::Shape, stats: &mut PaintStats) {
+    match shape {
+        egui::Shape::Vec(shapes) => {
+            for shape in shapes {
+                count_shape(shape, stats);
+            }
+        }
+        egui::Shape::Path(_) => stats.path_shapes += 1,
+        egui::Shape::Text(text) => {
+            stats.text_shapes += 1;
+            stats.text_vertices += text
+                .galley
+                .rows
+                .iter()
+                .map(|row| row.visuals.mesh.vertices.len())
+                .sum::<usize>();
+        }
+        egui::Shape::Mesh(_) => stats.mesh_shapes += 1,
+        egui::Shape::Callback(_) => stats.callbacks += 1,
+        _ => {}
+    }
+}
@@ -0,0 +1,345 @@
+//! MCP Profile Layer for capturing span timings during perf recordings
+//!
+//! This module provides a tracing-subscriber Layer that records how long each
+//! tracing span was entered while a profiling recording is active. The
+//! captured spans (plus one event per frame) can be exported as Chrome trace
+//! events and opened in Perfetto.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use egui_mcp_client::McpProfileLayer;
+//! use tracing_subscriber::prelude::*;
+//!
+//! fn main() {
+//!     let (profile_layer, profile_buffer) = McpProfileLayer::new(100_000);
+//!
+//!     tracing_subscriber::registry()
+//!         .with(profile_layer)
+//!         .with(tracing_subscriber::fmt::layer())
+//!         .init();
+//!
+//!     let mcp_client = McpClient::new().with_profile_buffer_sync(profile_buffer);
+//!     // ... run egui app
+//! }
+//! ```
+//!
+//! egui's own profiling scopes use the `profiling` crate; enable its
+//! `profile-with-tracing` feature to see them as spans too.
+
+use egui_mcp_protocol::TraceEvent;
+use parking_lot::Mutex;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tracing::Subscriber;
+use tracing::span::Id;
+use tracing_subscriber::Layer;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Thread ID used for the frame track
+const FRAME_TRACK_TID: u64 = 0;
+
+/// Shared buffer for profiling data, filled by [`McpProfileLayer`]
+#[derive(Clone)]
+pub struct ProfileBuffer {
+    /// Fast check so the layer does nothing while not recording
+    recording: Arc<AtomicBool>,
+    data: Arc<Mutex<ProfileData>>,
+}
+
+struct ProfileData {
+    /// When the current or last recording started
+    start: Instant,
+    /// Captured complete events
+    events: Vec<TraceEvent>,
+    /// Maximum number of events to keep per recording
+    max_events: usize,
+    /// Events dropped because the buffer was full
+    dropped_events: usize,
+    /// Small per-recording thread IDs with their names
+    threads: HashMap<std::thread::ThreadId, (u64, String)>,
+}
+
+impl ProfileBuffer {
+    fn new(max_events: usize) -> Self {
+        Self {
+            recording: Arc::new(AtomicBool::new(false)),
+            data: Arc::new(Mutex::new(ProfileData {
+                start: Instant::now(),
+                events: Vec::new(),
+                max_events,
+                dropped_events: 0,
+                threads: HashMap::new(),
+            })),
+        }
+    }
+
+    /// Clear previous data and start capturing
+    pub fn start(&self) {
+        let mut data = self.data.lock();
+        data.start = Instant::now();
+        data.events.clear();
+        data.dropped_events = 0;
+        data.threads.clear();
+        self.recording.store(true, Ordering::Release);
+    }
+
+    /// Stop capturing (the captured data is kept for export)
+    pub fn stop(&self) {
+        self.recording.store(false, Ordering::Release);
+    }
+
+    /// Whether a recording is active
+    pub fn is_recording(&self) -> bool {
+        self.recording.load(Ordering::Acquire)
+    }
+
+    /// Record a frame on the frame track
+    pub fn record_frame(&self, start: Instant, duration: Duration) {
+        if !self.is_recording() {
+            return;
+        }
+        self.data
+            .lock()
+            .push("frame", "egui_mcp_client", FRAME_TRACK_TID, start, duration);
+    }
+
+    /// Export a page of the captured data as Chrome trace events
+    ///
+    /// Metadata events naming the threads come first, followed by the
+    /// complete events in the order they finished. Returns at most `limit`
+    /// events starting at `offset`, fewer if they would not fit in one IPC
+    /// message, together with the number of dropped events and the number of
+    /// events of the whole trace.
+    pub fn export(&self, offset: usize, limit: usize) -> (Vec<TraceEvent>, usize, usize) {
+        let data = self.data.lock();
+        let pid = std::process::id();
+        let mut threads: Vec<(u64, String)> = data.threads.values().cloned().collect();
+        threads.push((FRAME_TRACK_TID, "Frames".to_string()));
+        threads.sort();
+
+        let metadata: Vec<TraceEvent> = threads
+            .into_iter()
+            .map(|(tid, name)| TraceEvent {
+                name: "thread_name".to_string(),
+                cat: "__metadata".to_string(),
+                ph: "M".to_string(),
+                ts: 0.0,
+                dur: None,
+                pid,
+                tid,
+                args: BTreeMap::from([("name".to_string(), name)]),
+            })
+            .collect();
+        let total = metadata.len() + data.events.len();
+
+        let page = crate::take_page(metadata.iter().chain(&data.events).skip(offset), limit);
+        (page, data.dropped_events, total)
+    }
+}
+
+impl ProfileData {
+    /// Push a complete event, dropping it if the buffer is full
+    fn push(&mut self, name: &str, target: &str, tid: u64, start: Instant, duration: Duration) {
+        if self.events.len() >= self.max_events {
+            self.dropped_events += 1;
+            return;
+        }
+        let ts = start.saturating_duration_since(self.start).as_secs_f64() * 1e6;
+        self.events.push(TraceEvent {
+            name: name.to_string(),
+            cat: target.to_string(),
+            ph: "X".to_string(),
+            ts,
+            dur: Some(duration.as_secs_f64() * 1e6),
+            pid: std::process::id(),
+            tid,
+            args: BTreeMap::new(),
+        });
+    }
+
+    /// Get the per-recording thread ID of the current thread
+    fn current_tid(&mut self) -> u64 {
+        let thread = std::thread::current();
+        let next_tid = self.threads.len() as u64 + 1;
+        self.threads
+            .entry(thread.id())
+            .or_insert_with(|| {
+                let name = thread
+                    .name()
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("Thread {}", next_tid));
+                (next_tid, name)
+            })
+            .0
+    }
+}
+
+/// Enter times of a span (a span can be entered more than once)
+struct SpanEnters(Vec<Instant>);
+
+/// A tracing Layer that captures span timings for MCP perf recordings
+pub struct McpProfileLayer {
+    buffer: ProfileBuffer,
+}
+
+impl McpProfileLayer {
+    /// Create a new MCP profile layer keeping at most `max_events` spans per recording
+    ///
+    /// Returns the layer and a shared reference to the profile buffer that can
+    /// be passed to `McpClient::with_profile_buffer_sync()`.
+    pub fn new(max_events: usize) -> (Self, ProfileBuffer) {
+        let buffer = ProfileBuffer::new(max_events);
+        let layer = Self {
+            buffer: buffer.clone(),
+        };
+        (layer, buffer)
+    }
+
+    /// Get a reference to the profile buffer
+    pub fn buffer(&self) -> ProfileBuffer {
+        self.buffer.clone()
+    }
+}
+
+impl<S> Layer<S> for McpProfileLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        if !self.buffer.is_recording() {
+            return;
+        }
+        if let Some(span) = ctx.span(id) {
+            let mut extensions = span.extensions_mut();
+            match extensions.get_mut::<SpanEnters>() {
+                Some(enters) => enters.0.push(Instant::now()),
+                None => extensions.insert(SpanEnters(vec![Instant::now()])),
+            }
+        }
+    }
+
+    fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        // Always pop the enter time so a recording stopped mid-span stays balanced
+        let Some(entered) = span
+            .extensions_mut()
+            .get_mut::<SpanEnters>()
+            .and_then(|enters| enters.0.pop())
+        else {
+            return;
+        };
+        if !self.buffer.is_recording() {
+            return;
+        }
+
+        let metadata = span.metadata();
+        let mut data = self.buffer.data.lock();
+        // Spans entered before the recording started are not part of it
+        if entered < data.start {
+            return;
+        }
+        let tid = data.current_tid();
+        data.push(
+            metadata.name(),
+            metadata.target(),
+            tid,
+            entered,
+            entered.elapsed(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::prelude::*;
+
+    #[test]
+    fn test_captures_spans_only_while_recording() {
+        let (layer, buffer) = McpProfileLayer::new(100);
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info_span!("before").in_scope(|| {});
+
+            buffer.start();
+            tracing::info_span!("outer").in_scope(|| {
+                tracing::info_span!("inner").in_scope(|| {});
+            });
+            buffer.stop();
+
+            tracing::info_span!("after").in_scope(|| {});
+        });
+
+        let (events, dropped, total) = buffer.export(0, usize::MAX);
+        assert_eq!(dropped, 0);
+        assert_eq!(total, events.len());
+        let names: Vec<&str> = events
+            .iter()
+            .filter(|e| e.ph == "X")
+            .map(|e| e.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["inner", "outer"]);
+        assert!(
+            events
+                .iter()
+                .any(|e| e.ph == "M" && e.tid == FRAME_TRACK_TID)
+        );
+    }
+
+    #[test]
+    fn test_drops_events_over_limit() {
+        let (layer, buffer) = McpProfileLayer::new(2);
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        buffer.start();
+        tracing::subscriber::with_default(subscriber, || {
+            for _ in 0..3 {
+                tracing::info_span!("work").in_scope(|| {});
+            }
+        });
+        buffer.record_frame(Instant::now(), Duration::from_millis(16));
+
+        let (events, dropped, _) = buffer.export(0, usize::MAX);
+        assert_eq!(events.iter().filter(|e| e.ph == "X").count(), 2);
+        assert_eq!(dropped, 2);
+    }
+
+    #[test]
+    fn test_export_pages_fit_in_a_message() {
+        let (_layer, buffer) = McpProfileLayer::new(100_000);
+        buffer.start();
+        for _ in 0..20_000 {
+            buffer.record_frame(Instant::now(), Duration::from_millis(16));
+        }
+        buffer.stop();
+
+        let mut events = Vec::new();
+        let mut trace_bytes = 0;
+        loop {
+            let (page, dropped_events, total_events) = buffer.export(events.len(), usize::MAX);
+            if page.is_empty() {
+                break;
+            }
+            let response = egui_mcp_protocol::Response::PerfTrace {
+                events: page.clone(),
+                dropped_events,
+                total_events,
+            };
+            let size = serde_json::to_vec(&response).unwrap().len();
+            assert!(size < egui_mcp_protocol::MAX_MESSAGE_SIZE);
+            trace_bytes += size;
+            events.extend(page);
+        }
+        assert!(trace_bytes > egui_mcp_protocol::MAX_MESSAGE_SIZE);
+        // One metadata event for the frame track plus the frames
+        assert_eq!(events.len(), 20_001);
+        assert_eq!(events.iter().filter(|e| e.name == "frame").count(), 20_000);
+    }
+}
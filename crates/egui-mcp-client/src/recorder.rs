@@ -0,0 +1,388 @@
+//! Recording of real user input as a replayable script
+//!
+//! While a recording is active, [`McpClient::take_viewport_inputs_sync`]
+//! passes the events of each frame's `RawInput` (before MCP inputs are
+//! injected) to the [`InputRecorder`]. It turns the low-level events into
+//! protocol input requests:
+//!
+//! - press and release at the same spot: `ClickAt` (two quick clicks: `DoubleClick`)
+//! - press and release elsewhere: `Drag`
+//! - mouse wheel: `Scroll` (consecutive wheel events are merged)
+//! - text and special keys: `KeyboardInput`
+//! - pointer resting somewhere: `MoveMouse` (for hover effects)
+//!
+//! [`McpPlugin`](crate::McpPlugin) adds the widget under the pointer to each
+//! step. Modifier keys are not recorded.
+//!
+//! [`McpClient::take_viewport_inputs_sync`]: crate::McpClient::take_viewport_inputs_sync
+
+use egui_mcp_protocol::{InputRecording, MouseButton, RecordedStep, Request, WidgetRef};
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// A press and release further apart than this is a drag
+const CLICK_MAX_DISTANCE: f32 = 6.0;
+/// Two clicks closer in time than this are a double click
+const DOUBLE_CLICK_MS: u64 = 300;
+/// The pointer has to rest this long before the position is recorded
+const HOVER_REST_MS: u64 = 300;
+/// Wheel events closer in time than this are merged into one scroll
+const SCROLL_MERGE_MS: u64 = 150;
+/// Points per scrolled line (egui's default `line_scroll_speed`)
+const LINE_SCROLL_POINTS: f32 = 40.0;
+
+/// A pressed pointer button
+struct Press {
+    pos: egui::Pos2,
+    button: MouseButton,
+    element: Option<WidgetRef>,
+}
+
+/// Turns raw egui events into recorded input steps
+pub(crate) struct InputRecorder {
+    started: Instant,
+    steps: Vec<RecordedStep>,
+    /// Latest pointer position
+    pointer: Option<egui::Pos2>,
+    /// Pointer button held down since this press
+    press: Option<Press>,
+    /// Latest pointer move, recorded once the pointer rests
+    pending_move: Option<RecordedStep>,
+    /// Widget under the pointer per viewport (from McpPlugin)
+    hovered: HashMap<u64, WidgetRef>,
+}
+
+impl InputRecorder {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            steps: Vec::new(),
+            pointer: None,
+            press: None,
+            pending_move: None,
+            hovered: HashMap::new(),
+        }
+    }
+
+    /// Set the widget under the pointer at the end of a pass
+    pub fn set_hovered(&mut self, viewport: egui::ViewportId, element: Option<WidgetRef>) {
+        match element {
+            Some(element) => self.hovered.insert(viewport.0.value(), element),
+            None => self.hovered.remove(&viewport.0.value()),
+        };
+    }
+
+    /// Record the events of one frame's input
+    pub fn record(&mut self, raw_input: &egui::RawInput) {
+        let at_ms = self.started.elapsed().as_millis() as u64;
+        let viewport = raw_input.viewport_id;
+        let viewport_id = (viewport != egui::ViewportId::ROOT).then(|| viewport.0.value());
+        let element = self.hovered.get(&viewport.0.value()).cloned();
+        let screen_height = raw_input.screen_rect.map_or(0.0, |r| r.height());
+
+        for event in &raw_input.events {
+            match event {
+                egui::Event::PointerMoved(pos) => {
+                    self.pointer = Some(*pos);
+                    if self.press.is_none() {
+                        self.pending_move = Some(RecordedStep {
+                            at_ms,
+                            request: Request::MoveMouse {
+                                x: pos.x,
+                                y: pos.y,
+                                viewport_id,
+                            },
+                            element: element.clone(),
+                        });
+                    }
+                }
+                egui::Event::PointerButton {
+                    pos,
+                    button,
+                    pressed,
+                    ..
+                } => {
+                    let Some(button) = convert_pointer_button(*button) else {
+                        continue;
+                    };
+                    // Clicks and drags move the pointer themselves
+                    self.pending_move = None;
+                    if *pressed {
+                        self.press = Some(Press {
+                            pos: *pos,
+                            button,
+                            element: element.clone(),
+                        });
+                    } else if let Some(press) = self.press.take() {
+                        self.release(press, *pos, at_ms, viewport_id);
+                    }
+                }
+                egui::Event::MouseWheel { unit, delta, .. } => {
+                    let Some(pos) = self.pointer else {
+                        continue;
+                    };
+                    let delta = match unit {
+                        egui::MouseWheelUnit::Point => *delta,
+                        egui::MouseWheelUnit::Line => *delta * LINE_SCROLL_POINTS,
+                        egui::MouseWheelUnit::Page => *delta * screen_height,
+                    };
+                    self.pending_move = None;
+                    self.scroll(pos, delta, at_ms, viewport_id, element.clone());
+                }
+                egui::Event::Text(text) | egui::Event::Paste(text) => {
+                    self.push_key(text.clone(), at_ms, viewport_id, element.clone());
+                }
+                egui::Event::Copy => {
+                    self.push_key("Copy".to_string(), at_ms, viewport_id, element.clone());
+                }
+                egui::Event::Cut => {
+                    self.push_key("Cut".to_string(), at_ms, viewport_id, element.clone());
+                }
+                // Letters and digits are recorded by their `Text` event
+                egui::Event::Key {
+                    key, pressed: true, ..
+                } if key.name().chars().count() > 1
+                    && crate::parse_special_key(key.name()).is_some() =>
+                {
+                    self.push_key(key.name().to_string(), at_ms, viewport_id, element.clone());
+                }
+                _ => {}
+            }
+        }
+
+        if self
+            .pending_move
+            .as_ref()
+            .is_some_and(|step| at_ms.saturating_sub(step.at_ms) >= HOVER_REST_MS)
+        {
+            self.steps.extend(self.pending_move.take());
+        }
+    }
+
+    /// Stop recording and return the steps
+    pub fn finish(mut self) -> InputRecording {
+        self.steps.extend(self.pending_move.take());
+        InputRecording {
+            duration_ms: self.started.elapsed().as_millis() as u64,
+            steps: self.steps,
+        }
+    }
+
+    /// Record a click, double click or drag for a released button
+    fn release(&mut self, press: Press, pos: egui::Pos2, at_ms: u64, viewport_id: Option<u64>) {
+        if press.pos.distance(pos) > CLICK_MAX_DISTANCE {
+            self.steps.push(RecordedStep {
+                at_ms,
+                request: Request::Drag {
+                    start_x: press.pos.x,
+                    start_y: press.pos.y,
+                    end_x: pos.x,
+                    end_y: pos.y,
+                    button: press.button,
+                    viewport_id,
+                },
+                element: press.element,
+            });
+            return;
+        }
+
+        // A second click right after a click at the same spot is a double click
+        if let Some(last) = self.steps.last_mut()
+            && let Request::ClickAt {
+                x,
+                y,
+                button,
+                viewport_id: last_viewport,
+            } = &last.request
+            && *button == press.button
+            && *last_viewport == viewport_id
+            && egui::pos2(*x, *y).distance(press.pos) <= CLICK_MAX_DISTANCE
+            && at_ms.saturating_sub(last.at_ms) <= DOUBLE_CLICK_MS
+        {
+            last.request = Request::DoubleClick {
+                x: *x,
+                y: *y,
+                button: press.button,
+                viewport_id,
+            };
+            return;
+        }
+
+        self.steps.push(RecordedStep {
+            at_ms,
+            request: Request::ClickAt {
+                x: press.pos.x,
+                y: press.pos.y,
+                button: press.button,
+                viewport_id,
+            },
+            element: press.element,
+        });
+    }
+
+    /// Record a scroll, merging it into the previous one if it continues it
+    fn scroll(
+        &mut self,
+        pos: egui::Pos2,
+        delta: egui::Vec2,
+        at_ms: u64,
+        viewport_id: Option<u64>,
+        element: Option<WidgetRef>,
+    ) {
+        if let Some(last) = self.steps.last_mut()
+            && let Request::Scroll {
+                x,
+                y,
+                delta_x,
+                delta_y,
+                viewport_id: last_viewport,
+            } = &mut last.request
+            && *last_viewport == viewport_id
+            && egui::pos2(*x, *y) == pos
+            && at_ms.saturating_sub(last.at_ms) <= SCROLL_MERGE_MS
+        {
+            *delta_x += delta.x;
+            *delta_y += delta.y;
+            last.at_ms = at_ms;
+            return;
+        }
+
+        self.steps.push(RecordedStep {
+            at_ms,
+            request: Request::Scroll {
+                x: pos.x,
+                y: pos.y,
+                delta_x: delta.x,
+                delta_y: delta.y,
+                viewport_id,
+            },
+            element,
+        });
+    }
+
+    fn push_key(
+        &mut self,
+        key: String,
+        at_ms: u64,
+        viewport_id: Option<u64>,
+        element: Option<WidgetRef>,
+    ) {
+        // Keep a hover that happened before typing
+        self.steps.extend(self.pending_move.take());
+        self.steps.push(RecordedStep {
+            at_ms,
+            request: Request::KeyboardInput { key, viewport_id },
+            element,
+        });
+    }
+}
+
+fn convert_pointer_button(button: egui::PointerButton) -> Option<MouseButton> {
+    match button {
+        egui::PointerButton::Primary => Some(MouseButton::Left),
+        egui::PointerButton::Secondary => Some(MouseButton::Right),
+        egui::PointerButton::Middle => Some(MouseButton::Middle),
+        egui::PointerButton::Extra1 | egui::PointerButton::Extra2 => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(events: Vec<egui::Event>) -> egui::RawInput {
+        egui::RawInput {
+            events,
+            ..Default::default()
+        }
+    }
+
+    fn button(x: f32, y: f32, pressed: bool) -> egui::Event {
+        egui::Event::PointerButton {
+            pos: egui::pos2(x, y),
+            button: egui::PointerButton::Primary,
+            pressed,
+            modifiers: egui::Modifiers::NONE,
+        }
+    }
+
+    #[test]
+    fn test_records_clicks_drags_and_keys() {
+        let mut recorder = InputRecorder::new();
+        recorder.set_hovered(
+            egui::ViewportId::ROOT,
+            Some(WidgetRef {
+                id: 7,
+                rect: None,
+                layer_id: None,
+                layer_order: None,
+                enabled: Some(true),
+            }),
+        );
+        recorder.record(&input(vec![
+            egui::Event::PointerMoved(egui::pos2(10.0, 10.0)),
+            button(10.0, 10.0, true),
+            button(11.0, 10.0, false),
+            button(10.0, 10.0, true),
+            button(10.0, 10.0, false),
+        ]));
+        recorder.record(&input(vec![
+            button(20.0, 20.0, true),
+            egui::Event::PointerMoved(egui::pos2(80.0, 20.0)),
+            button(80.0, 20.0, false),
+            egui::Event::Text("a".to_string()),
+            egui::Event::Key {
+                key: egui::Key::A,
+                physical_key: None,
+                pressed: true,
+                repeat: false,
+                modifiers: egui::Modifiers::NONE,
+            },
+            egui::Event::Key {
+                key: egui::Key::Enter,
+                physical_key: None,
+                pressed: true,
+                repeat: false,
+                modifiers: egui::Modifiers::NONE,
+            },
+        ]));
+        let recording = recorder.finish();
+
+        let requests: Vec<&Request> = recording.steps.iter().map(|s| &s.request).collect();
+        assert_eq!(requests.len(), 4, "{:?}", requests);
+        assert!(matches!(requests[0], Request::DoubleClick { x, .. } if *x == 10.0));
+        assert!(matches!(requests[1], Request::Drag { end_x, .. } if *end_x == 80.0));
+        assert!(matches!(requests[2], Request::KeyboardInput { key, .. } if key == "a"));
+        assert!(matches!(requests[3], Request::KeyboardInput { key, .. } if key == "Enter"));
+        assert_eq!(recording.steps[0].element.as_ref().unwrap().id, 7);
+    }
+
+    #[test]
+    fn test_merges_scrolls_and_keeps_last_hover() {
+        let mut recorder = InputRecorder::new();
+        recorder.record(&input(vec![
+            egui::Event::PointerMoved(egui::pos2(5.0, 5.0)),
+            egui::Event::PointerMoved(egui::pos2(50.0, 60.0)),
+            egui::Event::MouseWheel {
+                unit: egui::MouseWheelUnit::Line,
+                delta: egui::vec2(0.0, -1.0),
+                modifiers: egui::Modifiers::NONE,
+            },
+            egui::Event::MouseWheel {
+                unit: egui::MouseWheelUnit::Point,
+                delta: egui::vec2(0.0, -10.0),
+                modifiers: egui::Modifiers::NONE,
+            },
+            egui::Event::PointerMoved(egui::pos2(100.0, 100.0)),
+        ]));
+        let recording = recorder.finish();
+
+        let requests: Vec<&Request> = recording.steps.iter().map(|s| &s.request).collect();
+        assert_eq!(requests.len(), 2, "{:?}", requests);
+        assert!(matches!(
+            requests[0],
+            Request::Scroll { x, delta_y, .. } if *x == 50.0 && *delta_y == -50.0
+        ));
+        assert!(matches!(requests[1], Request::MoveMouse { x, .. } if *x == 100.0));
+    }
+}
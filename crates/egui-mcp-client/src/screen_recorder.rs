@@ -0,0 +1,161 @@
+//! Screen recording as a sequence of timestamped screenshots
+//!
+//! While a recording is active, [`McpClient::handle_screenshot_sync`] (or
+//! the software renderer of [`McpPlugin`](crate::McpPlugin)) takes a
+//! screenshot of the target viewport every `1 / fps` seconds through the
+//! regular screenshot path and hands it to the [`ScreenRecorder`]. The UI
+//! thread only passes on the image; a background thread encodes the frames
+//! as PNG. Capturing stops after `max_duration_ms`.
+//!
+//! [`McpClient::handle_screenshot_sync`]: crate::McpClient::handle_screenshot_sync
+
+use std::sync::Arc;
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// A captured frame: time since the recording started (ms) and PNG data
+pub(crate) type EncodedFrame = (u64, Vec<u8>);
+
+/// Captures screenshots of a viewport at a fixed rate
+pub(crate) struct ScreenRecorder {
+    /// Target viewport (None = root)
+    pub viewport_id: Option<u64>,
+    started: Instant,
+    interval: Duration,
+    max_duration: Duration,
+    next_capture: Instant,
+    /// Whether a `ViewportCommand::Screenshot` for the next frame is in flight
+    pub command_sent: bool,
+    /// Images to encode, with their timestamp
+    sender: mpsc::Sender<(u64, Arc<egui::ColorImage>)>,
+    encoder: JoinHandle<Vec<EncodedFrame>>,
+}
+
+impl ScreenRecorder {
+    /// Start the encoder thread
+    ///
+    /// Fails if `fps` is not a positive finite number or the thread cannot
+    /// be spawned.
+    pub fn new(viewport_id: Option<u64>, fps: f32, max_duration_ms: u64) -> std::io::Result<Self> {
+        let interval = Duration::try_from_secs_f32(1.0 / fps)
+            .ok()
+            .filter(|_| fps.is_finite() && fps > 0.0)
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("Invalid fps: {}", fps),
+                )
+            })?;
+        let (sender, receiver) = mpsc::channel::<(u64, Arc<egui::ColorImage>)>();
+        let encoder = std::thread::Builder::new()
+            .name("egui-mcp-screen-recorder".to_string())
+            .spawn(move || {
+                receiver
+                    .into_iter()
+                    .filter_map(|(timestamp_ms, image)| match crate::encode_png(&image) {
+                        Some(png) => Some((timestamp_ms, png)),
+                        None => {
+                            tracing::error!("Failed to encode recorded frame as PNG");
+                            None
+                        }
+                    })
+                    .collect()
+            })?;
+        let now = Instant::now();
+        Ok(Self {
+            viewport_id,
+            started: now,
+            interval,
+            max_duration: Duration::from_millis(max_duration_ms),
+            next_capture: now,
+            command_sent: false,
+            sender,
+            encoder,
+        })
+    }
+
+    /// Whether capturing stopped because `max_duration_ms` passed
+    pub fn is_finished(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.started) >= self.max_duration
+    }
+
+    /// Whether the next frame should be captured now
+    pub fn capture_due(&self, now: Instant) -> bool {
+        !self.is_finished(now) && now >= self.next_capture
+    }
+
+    /// Time until the next frame is due (None once finished)
+    pub fn next_capture_in(&self, now: Instant) -> Option<Duration> {
+        (!self.is_finished(now)).then(|| self.next_capture.saturating_duration_since(now))
+    }
+
+    /// Add a captured image as the next frame
+    pub fn add_frame(&mut self, image: Arc<egui::ColorImage>) {
+        let now = Instant::now();
+        self.command_sent = false;
+        if self.is_finished(now) {
+            return;
+        }
+        // Skip missed frames instead of catching up with a burst
+        self.next_capture = (self.next_capture + self.interval).max(now);
+        let timestamp_ms = now.saturating_duration_since(self.started).as_millis() as u64;
+        let _ = self.sender.send((timestamp_ms, image));
+    }
+
+    /// Stop recording and wait for the encoded frames
+    ///
+    /// Returns the frames and the length of the recording in milliseconds.
+    pub fn finish(self) -> (Vec<EncodedFrame>, u64) {
+        let duration_ms = self.started.elapsed().min(self.max_duration).as_millis() as u64;
+        drop(self.sender);
+        let frames = self.encoder.join().unwrap_or_else(|_| {
+            tracing::error!("Screen recorder thread panicked");
+            Vec::new()
+        });
+        (frames, duration_ms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frames_are_paced_and_encoded() {
+        let mut recorder = ScreenRecorder::new(None, 10.0, 10_000).unwrap();
+        let now = Instant::now();
+        assert!(recorder.capture_due(now));
+
+        let image = Arc::new(egui::ColorImage::filled([4, 2], egui::Color32::RED));
+        recorder.add_frame(image.clone());
+        // The next frame is due 100ms later
+        assert!(!recorder.capture_due(Instant::now()));
+        assert!(recorder.next_capture_in(Instant::now()).unwrap() <= Duration::from_millis(100));
+        recorder.add_frame(image);
+
+        let (frames, _) = recorder.finish();
+        assert_eq!(frames.len(), 2);
+        assert!(frames[0].0 <= frames[1].0);
+        assert!(frames[0].1.starts_with(b"\x89PNG"));
+    }
+
+    #[test]
+    fn test_capture_stops_after_max_duration() {
+        let recorder = ScreenRecorder::new(None, 10.0, 0).unwrap();
+        let now = Instant::now();
+        assert!(recorder.is_finished(now));
+        assert!(!recorder.capture_due(now));
+        assert_eq!(recorder.next_capture_in(now), None);
+        let (frames, duration_ms) = recorder.finish();
+        assert!(frames.is_empty());
+        assert_eq!(duration_ms, 0);
+    }
+
+    #[test]
+    fn test_invalid_fps_is_rejected() {
+        for fps in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            assert!(ScreenRecorder::new(None, fps, 1_000).is_err(), "{}", fps);
+        }
+    }
+}
@@ -5,10 +5,13 @@
 //! - Coordinate-based input
 //! - Keyboard input
 //! - Scroll events
+//! - Time control (freeze/step/resume)
 
-use crate::{McpClient, PendingInput};
+use crate::{LogQuery, McpClient, PendingInput};
 use base64::Engine;
-use egui_mcp_protocol::{ProtocolError, Request, Response, read_request, write_response};
+use egui_mcp_protocol::{
+    ProtocolError, RecordedFrame, Request, Response, read_request, write_response,
+};
 use std::time::Duration;
 use tokio::net::{UnixListener, UnixStream};
 
@@ -74,12 +77,23 @@ impl IpcServer {
 
     /// Handle a single request
     async fn handle_request(request: &Request, client: &McpClient) -> Response {
+        if let Some(viewport_id) = Self::target_viewport(request)
+            && !client.has_viewport(viewport_id).await
+        {
+            return Response::Error {
+                message: format!(
+                    "Unknown viewport {}. Use ListViewports to get valid viewport IDs",
+                    viewport_id
+                ),
+            };
+        }
+
         match request {
             Request::Ping => Response::Pong,
 
-            Request::TakeScreenshot => {
+            Request::TakeScreenshot { viewport_id } => {
                 // Request a screenshot and get a receiver (event-driven)
-                let rx = client.request_screenshot().await;
+                let rx = client.request_viewport_screenshot(*viewport_id).await;
 
                 // Wait for the screenshot with timeout (no polling needed)
                 match tokio::time::timeout(Duration::from_secs(5), rx).await {
@@ -99,76 +113,44 @@ impl IpcServer {
                 }
             }
 
-            Request::ClickAt { x, y, button } => {
-                client
-                    .queue_input(PendingInput::Click {
-                        x: *x,
-                        y: *y,
-                        button: *button,
-                    })
-                    .await;
-                Response::Success
-            }
-
-            Request::MoveMouse { x, y } => {
-                client
-                    .queue_input(PendingInput::MoveMouse { x: *x, y: *y })
-                    .await;
-                Response::Success
-            }
-
-            Request::KeyboardInput { key } => {
-                client
-                    .queue_input(PendingInput::Keyboard { key: key.clone() })
-                    .await;
+            Request::ClickAt { .. }
+            | Request::MoveMouse { .. }
+            | Request::KeyboardInput { .. }
+            | Request::Scroll { .. }
+            | Request::Drag { .. }
+            | Request::DoubleClick { .. } => {
+                if let Some(input) = Self::pending_input(request) {
+                    client
+                        .queue_viewport_input(Self::target_viewport(request), input)
+                        .await;
+                }
                 Response::Success
             }
 
-            Request::Scroll {
-                x,
-                y,
-                delta_x,
-                delta_y,
-            } => {
-                client
-                    .queue_input(PendingInput::Scroll {
-                        x: *x,
-                        y: *y,
-                        delta_x: *delta_x,
-                        delta_y: *delta_y,
-                    })
+            Request::MeasureLatency { input, timeout_ms } => {
+                let Some(pending) = Self::pending_input(input) else {
+                    return Response::Error {
+                        message: "MeasureLatency input must be ClickAt, DoubleClick, MoveMouse, KeyboardInput, Scroll or Drag".to_string(),
+                    };
+                };
+                let rx = client
+                    .measure_latency(Self::target_viewport(input), pending)
                     .await;
-                Response::Success
-            }
 
-            Request::Drag {
-                start_x,
-                start_y,
-                end_x,
-                end_y,
-                button,
-            } => {
-                client
-                    .queue_input(PendingInput::Drag {
-                        start_x: *start_x,
-                        start_y: *start_y,
-                        end_x: *end_x,
-                        end_y: *end_y,
-                        button: *button,
-                    })
-                    .await;
-                Response::Success
+                match tokio::time::timeout(Duration::from_millis(*timeout_ms), rx).await {
+                    Ok(Ok(report)) => Response::Latency { report },
+                    _ => match client.cancel_latency_measurement().await {
+                        Some(report) => Response::Latency { report },
+                        None => Response::Error {
+                            message: "No frame output observed: the egui app must call install_plugin() and keep repainting".to_string(),
+                        },
+                    },
+                }
             }
 
-            Request::DoubleClick { x, y, button } => {
-                client
-                    .queue_input(PendingInput::DoubleClick {
-                        x: *x,
-                        y: *y,
-                        button: *button,
-                    })
-                    .await;
-                Response::Success
+            Request::ListViewports => {
+                let viewports = client.list_viewports().await;
+                Response::Viewports { viewports }
             }
 
             Request::TakeScreenshotRegion {
@@ -176,9 +158,10 @@ impl IpcServer {
                 y,
                 width,
                 height,
+                viewport_id,
             } => {
                 // Request a screenshot and get a receiver (event-driven)
-                let rx = client.request_screenshot().await;
+                let rx = client.request_viewport_screenshot(*viewport_id).await;
 
                 // Wait for the screenshot with timeout (no polling needed)
                 match tokio::time::timeout(Duration::from_secs(5), rx).await {
@@ -239,9 +222,32 @@ impl IpcServer {
                 Response::Success
             }
 
-            Request::GetLogs { level, limit } => {
-                let entries = client.get_logs(level.as_deref(), *limit).await;
-                Response::Logs { entries }
+            Request::GetLogs {
+                level,
+                limit,
+                target,
+                target_regex,
+                message_regex,
+                since,
+                history,
+            } => {
+                let query = LogQuery {
+                    level: level.clone(),
+                    target: target.clone(),
+                    target_regex: target_regex.clone(),
+                    message_regex: message_regex.clone(),
+                    since: *since,
+                    limit: *limit,
+                };
+                let result = if *history {
+                    client.query_log_history(&query).await
+                } else {
+                    client.query_logs(&query).await
+                };
+                match result {
+                    Ok((entries, cursor)) => Response::Logs { entries, cursor },
+                    Err(message) => Response::Error { message },
+                }
             }
 
             Request::ClearLogs => {
@@ -249,23 +255,278 @@ impl IpcServer {
                 Response::Success
             }
 
+            Request::StartInputRecording => {
+                client.start_input_recording().await;
+                Response::Success
+            }
+
+            Request::GetUiTree => match client.get_ui_tree().await {
+                Some(tree) => Response::UiTreeResponse { tree },
+                None => Response::Error {
+                    message: "UI tree not available. Enable the `accesskit` feature of egui-mcp-client, install McpPlugin and enable AccessKit (or use HeadlessHarness)".to_string(),
+                },
+            },
+
+            Request::StopInputRecording => match client.stop_input_recording().await {
+                Some(recording) => Response::InputRecordingResponse { recording },
+                None => Response::Error {
+                    message: "No input recording in progress".to_string(),
+                },
+            },
+
+            Request::StartScreenRecording {
+                fps,
+                max_duration_ms,
+                viewport_id,
+            } => {
+                if !fps.is_finite() || *fps <= 0.0 {
+                    return Response::Error {
+                        message: format!("Invalid fps: {}", fps),
+                    };
+                }
+                match client
+                    .start_screen_recording(*viewport_id, *fps, *max_duration_ms)
+                    .await
+                {
+                    Ok(()) => Response::Success,
+                    Err(e) => Response::Error {
+                        message: format!("Failed to start screen recording: {}", e),
+                    },
+                }
+            }
+
+            Request::StopScreenRecording => match client.stop_screen_recording().await {
+                Some((frames, duration_ms)) => Response::ScreenRecordingStopped {
+                    frames,
+                    duration_ms,
+                },
+                None => Response::Error {
+                    message: "No screen recording in progress".to_string(),
+                },
+            },
+
+            Request::TakeRecordedFrame => Response::RecordedFrameResponse {
+                frame: client.take_recorded_frame().await.map(|(timestamp_ms, png)| {
+                    RecordedFrame {
+                        timestamp_ms,
+                        data: base64::engine::general_purpose::STANDARD.encode(&png),
+                    }
+                }),
+            },
+
+            Request::SetLogFilter { directives } => {
+                match client.set_log_filter(directives).await {
+                    Ok(()) => Response::Success,
+                    Err(message) => Response::Error { message },
+                }
+            }
+
             Request::GetFrameStats => {
                 let stats = client.get_frame_stats().await;
                 Response::FrameStatsResponse { stats }
             }
 
-            Request::StartPerfRecording { duration_ms } => {
-                client.start_perf_recording(*duration_ms).await;
+            Request::StartPerfRecording {
+                duration_ms,
+                profile,
+                budgets_ms,
+            } => {
+                if *profile && !client.start_profiling().await {
+                    return Response::Error {
+                        message: "Profiling is not enabled: the egui app must register McpProfileLayer and call with_profile_buffer_sync()".to_string(),
+                    };
+                }
+                client
+                    .start_perf_recording_with_budgets(*duration_ms, budgets_ms.clone())
+                    .await;
                 Response::Success
             }
 
+            Request::ExportPerfTrace { offset, limit } => {
+                match client.export_perf_trace(*offset, *limit).await {
+                    Some((events, dropped_events, total_events)) => Response::PerfTrace {
+                        events,
+                        dropped_events,
+                        total_events,
+                    },
+                    None => Response::Error {
+                        message: "Profiling is not enabled: the egui app must register McpProfileLayer and call with_profile_buffer_sync()".to_string(),
+                    },
+                }
+            }
+
+            Request::GetMemoryStats => {
+                let stats = client.get_memory_stats().await;
+                Response::MemoryStatsResponse { stats }
+            }
+
             Request::GetPerfReport => {
                 let report = client.get_perf_report().await;
                 Response::PerfReportResponse { report }
             }
+
+            Request::FreezeTime => {
+                client.freeze_time().await;
+                let (frozen, time) = client.time_state().await;
+                Response::TimeState { frozen, time }
+            }
+
+            Request::StepFrames { frames, dt_ms } => {
+                if !dt_ms.is_finite() || *dt_ms < 0.0 {
+                    return Response::Error {
+                        message: format!("Invalid dt_ms: {}", dt_ms),
+                    };
+                }
+                let rx = client.step_frames(*frames, *dt_ms).await;
+
+                // Wait for the frames to run with timeout
+                match tokio::time::timeout(Duration::from_secs(5), rx).await {
+                    Ok(Ok(time)) => Response::TimeState {
+                        frozen: true,
+                        time: Some(time),
+                    },
+                    Ok(Err(_)) => Response::Error {
+                        message: "Frame stepping was cancelled".to_string(),
+                    },
+                    Err(_) => Response::Error {
+                        message: "Step timeout: the egui app did not run the requested frames within 5 seconds. Make sure it calls apply_time_control_sync() in raw_input_hook".to_string(),
+                    },
+                }
+            }
+
+            Request::InspectContext { viewport_id } => {
+                let rx = client.request_context_info(*viewport_id).await;
+
+                // Wait for the next frame of the target viewport with timeout
+                match tokio::time::timeout(Duration::from_secs(5), rx).await {
+                    Ok(Ok(info)) => Response::ContextInfoResponse {
+                        info: Box::new(info),
+                    },
+                    Ok(Err(_)) => Response::Error {
+                        message: "Context inspection was cancelled".to_string(),
+                    },
+                    Err(_) => Response::Error {
+                        message: "Inspect timeout: the egui app did not answer within 5 seconds. Make sure it calls handle_inspect_sync() in update".to_string(),
+                    },
+                }
+            }
+
+            Request::GetFrameShapes {
+                viewport_id,
+                offset,
+                limit,
+                text_only,
+            } => {
+                // The first page captures the frame, the others page through it
+                let captured = if *offset == 0 {
+                    let rx = client.request_frame_shapes(*viewport_id).await;
+
+                    // Wait for the next frame of the target viewport with timeout
+                    match tokio::time::timeout(Duration::from_secs(5), rx).await {
+                        Ok(Ok(shapes)) => {
+                            client.set_frame_shapes(shapes, *text_only).await;
+                            Ok(())
+                        }
+                        Ok(Err(_)) => Err("Frame shape request was cancelled".to_string()),
+                        Err(_) => Err("Frame shapes timeout: the egui app did not render a frame within 5 seconds. Make sure McpPlugin is installed (install_plugin)".to_string()),
+                    }
+                } else {
+                    Ok(())
+                };
+                match captured {
+                    Ok(()) => match client.frame_shapes_page(*offset, *limit).await {
+                        Some((shapes, total_shapes)) => Response::FrameShapesResponse {
+                            shapes: Box::new(shapes),
+                            total_shapes,
+                        },
+                        None => Response::Error {
+                            message: "No frame shapes captured. Request offset 0 first".to_string(),
+                        },
+                    },
+                    Err(message) => Response::Error { message },
+                }
+            }
+
+            Request::GetIdleState => {
+                let state = client.get_idle_state().await;
+                Response::IdleStateResponse { state }
+            }
+
+            Request::ResumeTime => {
+                client.resume_time().await;
+                Response::TimeState {
+                    frozen: false,
+                    time: None,
+                }
+            }
+        }
+    }
+
+    /// Get the target viewport of a screenshot or input request
+    fn target_viewport(request: &Request) -> Option<u64> {
+        match request {
+            Request::TakeScreenshot { viewport_id }
+            | Request::TakeScreenshotRegion { viewport_id, .. }
+            | Request::ClickAt { viewport_id, .. }
+            | Request::KeyboardInput { viewport_id, .. }
+            | Request::Scroll { viewport_id, .. }
+            | Request::MoveMouse { viewport_id, .. }
+            | Request::Drag { viewport_id, .. }
+            | Request::DoubleClick { viewport_id, .. }
+            | Request::InspectContext { viewport_id }
+            | Request::GetFrameShapes { viewport_id, .. }
+            | Request::StartScreenRecording { viewport_id, .. } => *viewport_id,
+            Request::MeasureLatency { input, .. } => Self::target_viewport(input),
+            _ => None,
         }
     }
 
+    /// Convert an input request into the input queued for the app
+    fn pending_input(request: &Request) -> Option<PendingInput> {
+        let input = match request {
+            Request::ClickAt { x, y, button, .. } => PendingInput::Click {
+                x: *x,
+                y: *y,
+                button: *button,
+            },
+            Request::DoubleClick { x, y, button, .. } => PendingInput::DoubleClick {
+                x: *x,
+                y: *y,
+                button: *button,
+            },
+            Request::MoveMouse { x, y, .. } => PendingInput::MoveMouse { x: *x, y: *y },
+            Request::KeyboardInput { key, .. } => PendingInput::Keyboard { key: key.clone() },
+            Request::Scroll {
+                x,
+                y,
+                delta_x,
+                delta_y,
+                ..
+            } => PendingInput::Scroll {
+                x: *x,
+                y: *y,
+                delta_x: *delta_x,
+                delta_y: *delta_y,
+            },
+            Request::Drag {
+                start_x,
+                start_y,
+                end_x,
+                end_y,
+                button,
+                ..
+            } => PendingInput::Drag {
+                start_x: *start_x,
+                start_y: *start_y,
+                end_x: *end_x,
+                end_y: *end_y,
+                button: *button,
+            },
+            _ => return None,
+        };
+        Some(input)
+    }
+
     /// Crop a PNG screenshot to the specified region
     fn crop_screenshot(
         png_data: &[u8],
@@ -0,0 +1,383 @@
+//! CPU renderer for screenshots without a GPU or window
+//!
+//! Enabled with the `software-renderer` cargo feature. `ViewportCommand::Screenshot`
+//! needs a real eframe window with a GPU-backed renderer. [`SoftwareRenderer`]
+//! instead rasterizes the tessellated meshes of a frame on the CPU, keeping
+//! its own copy of egui's textures (font atlas, images) from each frame's
+//! `TexturesDelta`.
+//!
+//! The easiest way to use it is through [`McpPlugin`](crate::McpPlugin), which
+//! completes pending screenshot requests from the frame output:
+//!
+//! ```rust,ignore
+//! // Before the first frame, so the renderer sees the font atlas
+//! ctx.add_plugin(McpPlugin::new(mcp_client.clone()).with_software_screenshots(true));
+//! ```
+//!
+//! Blending follows egui's GPU backends: premultiplied alpha in gamma space.
+//! Paint callbacks (custom GPU rendering) are skipped.
+
+use egui::epaint::{ClippedPrimitive, ImageData, Mesh, Primitive, TextureId, Vertex};
+use egui::{Color32, ColorImage, TextureFilter, TextureOptions, TextureWrapMode, TexturesDelta};
+use std::collections::HashMap;
+
+/// Rasterizes egui frames into RGBA images on the CPU
+#[derive(Default)]
+pub struct SoftwareRenderer {
+    textures: HashMap<TextureId, Texture>,
+}
+
+struct Texture {
+    image: ColorImage,
+    options: TextureOptions,
+}
+
+/// Pixel bounds `[min_x, min_y, max_x, max_y)` of a clip rectangle
+type ClipBounds = [usize; 4];
+
+impl SoftwareRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply the texture uploads of a frame (call before [`Self::render`])
+    pub fn set_textures(&mut self, delta: &TexturesDelta) {
+        for (id, image_delta) in &delta.set {
+            let ImageData::Color(image) = &image_delta.image;
+            match image_delta.pos {
+                None => {
+                    self.textures.insert(
+                        *id,
+                        Texture {
+                            image: (**image).clone(),
+                            options: image_delta.options,
+                        },
+                    );
+                }
+                Some([x, y]) => {
+                    let Some(texture) = self.textures.get_mut(id) else {
+                        tracing::debug!("Partial update of unknown texture {:?}", id);
+                        continue;
+                    };
+                    texture.options = image_delta.options;
+                    let target_width = texture.image.width();
+                    for row in 0..image.height() {
+                        let start = (y + row) * target_width + x;
+                        let source = &image.pixels[row * image.width()..(row + 1) * image.width()];
+                        if let Some(target) =
+                            texture.image.pixels.get_mut(start..start + source.len())
+                        {
+                            target.copy_from_slice(source);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Free the textures released in a frame (call after [`Self::render`])
+    pub fn free_textures(&mut self, delta: &TexturesDelta) {
+        for id in &delta.free {
+            self.textures.remove(id);
+        }
+    }
+
+    /// Rasterize tessellated primitives into an image of `size` pixels
+    pub fn render(
+        &self,
+        primitives: &[ClippedPrimitive],
+        pixels_per_point: f32,
+        size: [usize; 2],
+        clear_color: Color32,
+    ) -> ColorImage {
+        let mut target = ColorImage::filled(size, clear_color);
+        for primitive in primitives {
+            let clip = primitive.clip_rect;
+            let to_px =
+                |v: f32, max: usize| ((v * pixels_per_point).round().max(0.0) as usize).min(max);
+            let bounds = [
+                to_px(clip.min.x, size[0]),
+                to_px(clip.min.y, size[1]),
+                to_px(clip.max.x, size[0]),
+                to_px(clip.max.y, size[1]),
+            ];
+            if bounds[0] >= bounds[2] || bounds[1] >= bounds[3] {
+                continue;
+            }
+            match &primitive.primitive {
+                Primitive::Mesh(mesh) => {
+                    self.draw_mesh(&mut target, mesh, bounds, pixels_per_point)
+                }
+                Primitive::Callback(_) => {
+                    tracing::debug!("Software renderer skips paint callbacks");
+                }
+            }
+        }
+        target
+    }
+
+    /// Tessellate and rasterize a frame's output of `ctx` at its viewport size
+    ///
+    /// Only renders; texture updates are left to the caller.
+    pub fn render_output(&self, ctx: &egui::Context, output: &egui::FullOutput) -> ColorImage {
+        let pixels_per_point = output.pixels_per_point;
+        let rect = ctx.viewport_rect();
+        let size = [
+            (rect.width() * pixels_per_point).round() as usize,
+            (rect.height() * pixels_per_point).round() as usize,
+        ];
+        let primitives = ctx.tessellate(output.shapes.clone(), pixels_per_point);
+        let clear_color = ctx.style().visuals.panel_fill;
+        self.render(&primitives, pixels_per_point, size, clear_color)
+    }
+
+    fn draw_mesh(
+        &self,
+        target: &mut ColorImage,
+        mesh: &Mesh,
+        clip: ClipBounds,
+        pixels_per_point: f32,
+    ) {
+        // Missing user textures are drawn with their vertex colors only
+        let texture = self.textures.get(&mesh.texture_id);
+        for triangle in mesh.indices.chunks_exact(3) {
+            let vertex = |i: u32| mesh.vertices.get(i as usize);
+            if let (Some(a), Some(b), Some(c)) = (
+                vertex(triangle[0]),
+                vertex(triangle[1]),
+                vertex(triangle[2]),
+            ) {
+                draw_triangle(target, [a, b, c], texture, clip, pixels_per_point);
+            }
+        }
+    }
+}
+
+fn draw_triangle(
+    target: &mut ColorImage,
+    vertices: [&Vertex; 3],
+    texture: Option<&Texture>,
+    clip: ClipBounds,
+    pixels_per_point: f32,
+) {
+    let [a, b, c] = vertices.map(|v| v.pos * pixels_per_point);
+    let area = edge(a, b, c);
+    if area.abs() <= f32::EPSILON {
+        return;
+    }
+    // Make the winding consistent so that interior points have positive edge values
+    let (vertices, [a, b, c]) = if area < 0.0 {
+        ([vertices[0], vertices[2], vertices[1]], [a, c, b])
+    } else {
+        (vertices, [a, b, c])
+    };
+    let area = area.abs();
+
+    let min_x = (a.x.min(b.x).min(c.x).floor().max(0.0) as usize).max(clip[0]);
+    let min_y = (a.y.min(b.y).min(c.y).floor().max(0.0) as usize).max(clip[1]);
+    let max_x = (a.x.max(b.x).max(c.x).ceil().max(0.0) as usize).min(clip[2]);
+    let max_y = (a.y.max(b.y).max(c.y).ceil().max(0.0) as usize).min(clip[3]);
+
+    let colors = vertices.map(|v| rgba(v.color));
+    let width = target.width();
+    for y in min_y..max_y {
+        for x in min_x..max_x {
+            let p = egui::pos2(x as f32 + 0.5, y as f32 + 0.5);
+            let w = [edge(b, c, p), edge(c, a, p), edge(a, b, p)];
+            let edges = [(b, c), (c, a), (a, b)];
+            // Top-left rule: pixels exactly on a shared edge are drawn once
+            let inside = w
+                .iter()
+                .zip(edges)
+                .all(|(&w, (from, to))| w > 0.0 || (w == 0.0 && is_top_left(from, to)));
+            if !inside {
+                continue;
+            }
+            let w = w.map(|w| w / area);
+
+            let mut color = [0.0; 4];
+            for (channel, value) in color.iter_mut().enumerate() {
+                *value = w[0] * colors[0][channel]
+                    + w[1] * colors[1][channel]
+                    + w[2] * colors[2][channel];
+            }
+            if let Some(texture) = texture {
+                let uv = vertices[0].uv.to_vec2() * w[0]
+                    + vertices[1].uv.to_vec2() * w[1]
+                    + vertices[2].uv.to_vec2() * w[2];
+                let texel = sample(texture, uv.x, uv.y);
+                for (value, texel) in color.iter_mut().zip(texel) {
+                    *value *= texel;
+                }
+            }
+
+            let pixel = &mut target.pixels[y * width + x];
+            *pixel = blend(*pixel, color);
+        }
+    }
+}
+
+/// Twice the signed area of the triangle `a, b, p` (positive if `p` is right of `a -> b`)
+fn edge(a: egui::Pos2, b: egui::Pos2, p: egui::Pos2) -> f32 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+/// Whether the edge `from -> to` is a top or left edge of a positively wound triangle
+fn is_top_left(from: egui::Pos2, to: egui::Pos2) -> bool {
+    let d = to - from;
+    (d.y == 0.0 && d.x > 0.0) || d.y < 0.0
+}
+
+/// Premultiplied color as normalized floats
+fn rgba(color: Color32) -> [f32; 4] {
+    color.to_array().map(|c| c as f32 / 255.0)
+}
+
+/// Blend a premultiplied source color over a pixel
+fn blend(dst: Color32, src: [f32; 4]) -> Color32 {
+    let dst = rgba(dst);
+    let out: [u8; 4] = std::array::from_fn(|i| {
+        ((src[i] + dst[i] * (1.0 - src[3])).clamp(0.0, 1.0) * 255.0).round() as u8
+    });
+    Color32::from_rgba_premultiplied(out[0], out[1], out[2], out[3])
+}
+
+/// Sample a texture at normalized coordinates
+fn sample(texture: &Texture, u: f32, v: f32) -> [f32; 4] {
+    let [width, height] = texture.image.size;
+    if width == 0 || height == 0 {
+        return [1.0; 4];
+    }
+    let wrap = texture.options.wrap_mode;
+    let texel = |x: i64, y: i64| {
+        let x = wrap_coord(x, width, wrap);
+        let y = wrap_coord(y, height, wrap);
+        rgba(texture.image.pixels[y * width + x])
+    };
+
+    let x = u * width as f32;
+    let y = v * height as f32;
+    match texture.options.magnification {
+        TextureFilter::Nearest => texel(x.floor() as i64, y.floor() as i64),
+        TextureFilter::Linear => {
+            let (x, y) = (x - 0.5, y - 0.5);
+            let (x0, y0) = (x.floor(), y.floor());
+            let (fx, fy) = (x - x0, y - y0);
+            let (x0, y0) = (x0 as i64, y0 as i64);
+            let [t00, t10, t01, t11] = [
+                texel(x0, y0),
+                texel(x0 + 1, y0),
+                texel(x0, y0 + 1),
+                texel(x0 + 1, y0 + 1),
+            ];
+            std::array::from_fn(|i| {
+                let top = t00[i] + (t10[i] - t00[i]) * fx;
+                let bottom = t01[i] + (t11[i] - t01[i]) * fx;
+                top + (bottom - top) * fy
+            })
+        }
+    }
+}
+
+/// Map a texel coordinate into `0..size` according to the wrap mode
+fn wrap_coord(coord: i64, size: usize, wrap: TextureWrapMode) -> usize {
+    let size = size as i64;
+    let coord = match wrap {
+        TextureWrapMode::ClampToEdge => coord.clamp(0, size - 1),
+        TextureWrapMode::Repeat => coord.rem_euclid(size),
+        TextureWrapMode::MirroredRepeat => {
+            let period = coord.rem_euclid(2 * size);
+            if period < size {
+                period
+            } else {
+                2 * size - 1 - period
+            }
+        }
+    };
+    coord as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_frame(ctx: &egui::Context, size: egui::Vec2) -> egui::FullOutput {
+        let raw_input = egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(egui::Pos2::ZERO, size)),
+            ..Default::default()
+        };
+        ctx.run(raw_input, |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.painter().rect_filled(
+                    egui::Rect::from_min_size(egui::pos2(10.0, 10.0), egui::vec2(20.0, 10.0)),
+                    0.0,
+                    Color32::RED,
+                );
+                ui.painter().rect_filled(
+                    egui::Rect::from_min_size(egui::pos2(10.0, 30.0), egui::vec2(20.0, 10.0)),
+                    0.0,
+                    Color32::from_rgba_unmultiplied(0, 0, 255, 128),
+                );
+                ui.label("Hello");
+            });
+        })
+    }
+
+    #[test]
+    fn test_render_filled_rects_and_text() {
+        let ctx = egui::Context::default();
+        let mut renderer = SoftwareRenderer::new();
+        let output = run_frame(&ctx, egui::vec2(100.0, 60.0));
+        renderer.set_textures(&output.textures_delta);
+        let image = renderer.render_output(&ctx, &output);
+        renderer.free_textures(&output.textures_delta);
+
+        assert_eq!(image.size, [100, 60]);
+        let pixel = |x: usize, y: usize| image.pixels[y * image.width() + x];
+        let background = ctx.style().visuals.panel_fill;
+
+        assert_eq!(pixel(1, 58), background);
+        assert_eq!(pixel(20, 15), Color32::RED);
+        // Half-transparent blue over the panel background
+        let blended = pixel(20, 35);
+        assert!(blended.b() > background.b() && blended.r() < 128);
+        // Some text pixels differ from the background (font atlas was sampled)
+        assert!(
+            image
+                .pixels
+                .iter()
+                .any(|&p| p != background && p != Color32::RED && p != blended)
+        );
+    }
+
+    #[test]
+    fn test_adjacent_triangles_draw_shared_edge_once() {
+        let renderer = SoftwareRenderer::new();
+        let mut mesh = Mesh::default();
+        let color = Color32::from_rgba_premultiplied(0, 0, 0, 128);
+        // A quad whose diagonal passes through pixel centers
+        mesh.add_colored_rect(
+            egui::Rect::from_min_max(egui::pos2(0.5, 0.5), egui::pos2(4.5, 4.5)),
+            color,
+        );
+        mesh.texture_id = TextureId::User(0);
+        let primitive = ClippedPrimitive {
+            clip_rect: egui::Rect::EVERYTHING,
+            primitive: Primitive::Mesh(mesh),
+        };
+
+        let image = renderer.render(&[primitive], 1.0, [5, 5], Color32::WHITE);
+        let inside: Vec<Color32> = (1..4).map(|i| image.pixels[i * 5 + i]).collect();
+        assert!(inside.iter().all(|&p| p == image.pixels[5 + 2]));
+    }
+
+    #[test]
+    fn test_wrap_coord() {
+        assert_eq!(wrap_coord(-1, 4, TextureWrapMode::ClampToEdge), 0);
+        assert_eq!(wrap_coord(5, 4, TextureWrapMode::ClampToEdge), 3);
+        assert_eq!(wrap_coord(5, 4, TextureWrapMode::Repeat), 1);
+        assert_eq!(wrap_coord(-1, 4, TextureWrapMode::Repeat), 3);
+        assert_eq!(wrap_coord(4, 4, TextureWrapMode::MirroredRepeat), 3);
+        assert_eq!(wrap_coord(-1, 4, TextureWrapMode::MirroredRepeat), 0);
+    }
+}
@@ -0,0 +1,406 @@
+//! Paint output of a frame as vector shapes
+//!
+//! Pixel screenshots depend on fonts, GPU drivers and scaling. The shapes
+//! egui painted are deterministic, so [`frame_shapes`] converts them into
+//! [`FrameShapes`] (rects, paths, text runs with positions) that can be
+//! diffed or turned into SVG. Shapes are read per layer at the end of the
+//! pass, before egui drains them into `FullOutput`, so each shape keeps its
+//! layer.
+
+use egui::Pos2;
+use egui::epaint::{
+    ClippedShape, Color32, ColorMode, Galley, Mesh, PathStroke, Shape, Stroke, TextShape,
+};
+use egui_mcp_protocol::{FrameShapes, PaintedShape, Rect, TextRun, VectorShape, VectorStroke};
+
+/// Collect the shapes painted so far in this pass, back to front
+///
+/// Call this at the end of the pass (e.g. from a plugin's `on_end_pass`).
+pub(crate) fn frame_shapes(ctx: &egui::Context) -> FrameShapes {
+    let screen_rect = ctx.viewport_rect();
+    let mut layers: Vec<egui::LayerId> = ctx.memory(|mem| mem.layer_ids().collect());
+    // Same order as egui paints: by layer order, then by area order (stable sort)
+    layers.sort_by_key(|layer| layer.order);
+
+    let mut shapes = Vec::new();
+    for &layer in &layers {
+        let transform = ctx.layer_transform_to_global(layer);
+        ctx.graphics(|graphics| {
+            let Some(list) = graphics.get(layer) else {
+                return;
+            };
+            for clipped in list.all_entries() {
+                let ClippedShape {
+                    mut clip_rect,
+                    mut shape,
+                } = clipped.clone();
+                if let Some(transform) = transform {
+                    clip_rect = transform.mul_rect(clip_rect);
+                    shape.transform(transform);
+                }
+                let clip_rect = clip_rect.intersect(screen_rect);
+                if !clip_rect.is_positive() {
+                    continue;
+                }
+                let mut converted = Vec::new();
+                convert_shape(&shape, &mut converted);
+                shapes.extend(converted.into_iter().map(|shape| PaintedShape {
+                    layer_id: layer.id.value(),
+                    layer_order: format!("{:?}", layer.order),
+                    clip_rect: rect(clip_rect),
+                    shape,
+                }));
+            }
+        });
+    }
+
+    FrameShapes {
+        viewport_id: ctx.viewport_id().0.value(),
+        screen_rect: rect(screen_rect),
+        pixels_per_point: ctx.pixels_per_point(),
+        shapes,
+    }
+}
+
+/// Convert a shape (flattening `Shape::Vec`), skipping invisible ones
+fn convert_shape(shape: &Shape, out: &mut Vec<VectorShape>) {
+    match shape {
+        Shape::Noop => {}
+        Shape::Vec(shapes) => {
+            for shape in shapes {
+                convert_shape(shape, out);
+            }
+        }
+        Shape::Circle(circle) => push_visible(out, circle.fill, circle.stroke, |fill, stroke| {
+            VectorShape::Circle {
+                center: point(circle.center),
+                radius: circle.radius,
+                fill,
+                stroke,
+            }
+        }),
+        Shape::Ellipse(ellipse) => {
+            push_visible(out, ellipse.fill, ellipse.stroke, |fill, stroke| {
+                VectorShape::Ellipse {
+                    center: point(ellipse.center),
+                    radius: [ellipse.radius.x, ellipse.radius.y],
+                    fill,
+                    stroke,
+                }
+            })
+        }
+        Shape::LineSegment { points, stroke } => {
+            push_visible(out, Color32::TRANSPARENT, *stroke, |fill, stroke| {
+                VectorShape::Path {
+                    points: points.iter().copied().map(point).collect(),
+                    closed: false,
+                    fill,
+                    stroke,
+                }
+            })
+        }
+        Shape::Path(path) => push_visible(
+            out,
+            path.fill,
+            solid_stroke(&path.stroke),
+            |fill, stroke| VectorShape::Path {
+                points: path.points.iter().copied().map(point).collect(),
+                closed: path.closed,
+                fill,
+                stroke,
+            },
+        ),
+        Shape::QuadraticBezier(bezier) => push_visible(
+            out,
+            bezier.fill,
+            solid_stroke(&bezier.stroke),
+            |fill, stroke| VectorShape::Path {
+                points: bezier.flatten(None).into_iter().map(point).collect(),
+                closed: bezier.closed,
+                fill,
+                stroke,
+            },
+        ),
+        Shape::CubicBezier(bezier) => push_visible(
+            out,
+            bezier.fill,
+            solid_stroke(&bezier.stroke),
+            |fill, stroke| VectorShape::Path {
+                points: bezier.flatten(None).into_iter().map(point).collect(),
+                closed: bezier.closed,
+                fill,
+                stroke,
+            },
+        ),
+        Shape::Rect(shape) => push_visible(out, shape.fill, shape.stroke, |fill, stroke| {
+            VectorShape::Rect {
+                rect: rect(shape.rect),
+                corner_radius: shape.corner_radius.average(),
+                fill,
+                stroke,
+            }
+        }),
+        Shape::Text(text) => {
+            if !text.galley.is_empty() {
+                out.push(text_shape(text));
+            }
+        }
+        Shape::Mesh(mesh) => {
+            if !mesh.is_empty() {
+                out.push(mesh_shape(mesh));
+            }
+        }
+        Shape::Callback(callback) => out.push(VectorShape::Callback {
+            rect: rect(callback.rect),
+        }),
+    }
+}
+
+/// Push a shape unless both its fill and stroke are invisible
+fn push_visible(
+    out: &mut Vec<VectorShape>,
+    fill: Color32,
+    stroke: Stroke,
+    build: impl FnOnce(Option<String>, Option<VectorStroke>) -> VectorShape,
+) {
+    let fill = (fill.a() > 0).then(|| fill.to_hex());
+    let stroke = (!stroke.is_empty()).then(|| VectorStroke {
+        width: stroke.width,
+        color: stroke.color.to_hex(),
+    });
+    if fill.is_some() || stroke.is_some() {
+        out.push(build(fill, stroke));
+    }
+}
+
+/// Path strokes with a color callback are reported with a transparent color
+fn solid_stroke(stroke: &PathStroke) -> Stroke {
+    match stroke.color {
+        ColorMode::Solid(color) => Stroke::new(stroke.width, color),
+        ColorMode::UV(_) => Stroke::new(stroke.width, Color32::TRANSPARENT),
+    }
+}
+
+fn text_shape(shape: &TextShape) -> VectorShape {
+    VectorShape::Text {
+        rect: rect(shape.visual_bounding_rect()),
+        text: shape.galley.text().to_string(),
+        runs: text_runs(shape),
+        angle: shape.angle,
+    }
+}
+
+/// Split the rows of a text shape into runs of glyphs with the same layout section
+fn text_runs(shape: &TextShape) -> Vec<TextRun> {
+    let galley: &Galley = &shape.galley;
+    let text = &galley.job.text;
+    let sections = &galley.job.sections;
+    let section_at = |byte: usize| {
+        sections
+            .iter()
+            .position(|s| s.byte_range.contains(&byte))
+            .unwrap_or(sections.len().saturating_sub(1))
+    };
+
+    let mut chars = text.char_indices().peekable();
+    let mut runs = Vec::new();
+    for placed_row in &galley.rows {
+        let row_rect = placed_row.rect().translate(shape.pos.to_vec2());
+        let mut current: Option<(usize, String, f32, f32)> = None;
+
+        for glyph in &placed_row.row.glyphs {
+            // Glyphs map to the characters of the text in order; the
+            // overflow character of elided text has no character
+            let byte = match chars.peek() {
+                Some(&(byte, chr)) if chr == glyph.chr => {
+                    chars.next();
+                    byte
+                }
+                _ => text.len().saturating_sub(1),
+            };
+            let section = section_at(byte);
+            let left = shape.pos.x + placed_row.pos.x + glyph.pos.x;
+            let right = left + glyph.advance_width;
+            match current.as_mut() {
+                Some((index, run_text, _, max_x)) if *index == section => {
+                    run_text.push(glyph.chr);
+                    *max_x = right;
+                }
+                _ => {
+                    if let Some(run) = current.take() {
+                        runs.push(text_run(shape, run, row_rect));
+                    }
+                    current = Some((section, glyph.chr.to_string(), left, right));
+                }
+            }
+        }
+        if let Some(run) = current.take() {
+            runs.push(text_run(shape, run, row_rect));
+        }
+        if placed_row.row.ends_with_newline {
+            chars.next();
+        }
+    }
+    runs.retain(|run| !run.text.trim().is_empty());
+    runs
+}
+
+fn text_run(
+    shape: &TextShape,
+    (section, text, min_x, max_x): (usize, String, f32, f32),
+    row_rect: egui::Rect,
+) -> TextRun {
+    let format = shape
+        .galley
+        .job
+        .sections
+        .get(section)
+        .map(|s| s.format.clone())
+        .unwrap_or_default();
+    let color = match shape.override_text_color {
+        Some(color) => color,
+        None if format.color == Color32::PLACEHOLDER => shape.fallback_color,
+        None => format.color,
+    };
+    TextRun {
+        text,
+        rect: rect(egui::Rect::from_x_y_ranges(
+            min_x..=max_x,
+            row_rect.y_range(),
+        )),
+        color: color.gamma_multiply(shape.opacity_factor).to_hex(),
+        font_size: format.font_id.size,
+        font_family: format.font_id.family.to_string(),
+    }
+}
+
+fn mesh_shape(mesh: &Mesh) -> VectorShape {
+    VectorShape::Mesh {
+        rect: rect(mesh.calc_bounds()),
+        triangles: mesh.indices.len() / 3,
+        textured: mesh.texture_id != egui::TextureId::default(),
+    }
+}
+
+fn point(pos: Pos2) -> [f32; 2] {
+    [pos.x, pos.y]
+}
+
+fn rect(r: egui::Rect) -> Rect {
+    Rect {
+        x: r.min.x,
+        y: r.min.y,
+        width: r.width(),
+        height: r.height(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shapes_of(mut ui: impl FnMut(&egui::Context)) -> FrameShapes {
+        let ctx = egui::Context::default();
+        let shapes = std::sync::Arc::new(parking_lot::Mutex::new(None));
+        let captured = shapes.clone();
+        ctx.on_end_pass(
+            "test",
+            std::sync::Arc::new(move |ctx| *captured.lock() = Some(frame_shapes(ctx))),
+        );
+        let raw_input = egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(
+                Pos2::ZERO,
+                egui::vec2(200.0, 100.0),
+            )),
+            ..Default::default()
+        };
+        // Areas are invisible in their first (sizing) pass
+        let _ = ctx.run(raw_input.clone(), &mut ui);
+        let _ = ctx.run(raw_input, &mut ui);
+        shapes.lock().take().unwrap()
+    }
+
+    #[test]
+    fn test_frame_shapes_keep_layers_and_text_positions() {
+        let shapes = shapes_of(|ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.painter()
+                    .circle_filled(egui::pos2(50.0, 50.0), 10.0, Color32::RED);
+                ui.label("Hello world");
+            });
+            egui::Area::new(egui::Id::new("popup"))
+                .order(egui::Order::Foreground)
+                .fixed_pos(egui::pos2(100.0, 60.0))
+                .show(ctx, |ui| ui.label("On top"));
+        });
+
+        assert_eq!(shapes.screen_rect.width, 200.0);
+        let circle = shapes
+            .shapes
+            .iter()
+            .find(|s| matches!(s.shape, VectorShape::Circle { .. }))
+            .unwrap();
+        assert_eq!(circle.layer_order, "Background");
+        let VectorShape::Circle { center, fill, .. } = &circle.shape else {
+            unreachable!()
+        };
+        assert_eq!(*center, [50.0, 50.0]);
+        assert_eq!(fill.as_deref(), Some("#ff0000ff"));
+
+        let runs: Vec<(&PaintedShape, &TextRun)> = shapes
+            .shapes
+            .iter()
+            .filter_map(|s| match &s.shape {
+                VectorShape::Text { runs, .. } => Some(runs.iter().map(move |run| (s, run))),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+        let (_, hello) = runs.iter().find(|(_, r)| r.text == "Hello world").unwrap();
+        assert!(hello.rect.x >= 0.0 && hello.rect.width > 0.0 && hello.font_size > 0.0);
+        assert_eq!(hello.font_family, "Proportional");
+
+        // The foreground area is painted last, at its position
+        let (top_shape, top) = runs.last().unwrap();
+        assert_eq!(top.text, "On top");
+        assert_eq!(top_shape.layer_order, "Foreground");
+        assert!(top.rect.x >= 100.0 && top.rect.y >= 60.0);
+    }
+
+    #[test]
+    fn test_text_runs_split_by_format() {
+        let shapes = shapes_of(|ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                let mut job = egui::text::LayoutJob::default();
+                job.append(
+                    "Red ",
+                    0.0,
+                    egui::TextFormat::simple(egui::FontId::proportional(12.0), Color32::RED),
+                );
+                job.append(
+                    "Big",
+                    0.0,
+                    egui::TextFormat::simple(egui::FontId::monospace(20.0), Color32::BLUE),
+                );
+                ui.label(job);
+            });
+        });
+        let runs: Vec<&TextRun> = shapes
+            .shapes
+            .iter()
+            .filter_map(|s| match &s.shape {
+                VectorShape::Text { runs, .. } => Some(runs),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].text, "Red ");
+        assert_eq!(runs[0].color, "#ff0000ff");
+        assert_eq!(runs[0].font_size, 12.0);
+        assert_eq!(runs[1].text, "Big");
+        assert_eq!(runs[1].font_family, "Monospace");
+        // Glyph positions are rounded to pixels
+        assert!(runs[1].rect.x >= runs[0].rect.x + runs[0].rect.width - 1.0);
+    }
+}
@@ -8,7 +8,7 @@
 //! require direct client integration (screenshots, coordinate-based input, etc.).
 
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 /// Default socket path for IPC communication
@@ -19,6 +19,28 @@ pub fn default_socket_path() -> PathBuf {
     runtime_dir.join("egui-mcp.sock")
 }
 
+/// Crash file written next to the socket when the application panics
+pub fn crash_file_path(socket_path: &Path) -> PathBuf {
+    socket_path.with_extension("crash.json")
+}
+
+/// Details of a panic in the egui application, read from the crash file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    /// Panic message
+    pub message: String,
+    /// Source location of the panic (file:line:column)
+    pub location: Option<String>,
+    /// Name of the panicking thread
+    pub thread: Option<String>,
+    /// Captured backtrace
+    pub backtrace: String,
+    /// When the panic happened (milliseconds since the Unix epoch)
+    pub timestamp_ms: u64,
+    /// Process ID of the application
+    pub pid: u32,
+}
+
 /// Information about a UI node (used for AT-SPI responses)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NodeInfo {
@@ -60,8 +82,59 @@ pub struct UiTree {
     pub nodes: Vec<NodeInfo>,
 }
 
+impl UiTree {
+    /// Find nodes whose label contains `pattern` (or equals it if `exact`)
+    pub fn find_by_label(&self, pattern: &str, exact: bool) -> Vec<NodeInfo> {
+        self.nodes
+            .iter()
+            .filter(|node| match &node.label {
+                Some(label) if exact => label == pattern,
+                Some(label) => label.contains(pattern),
+                None => false,
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Find nodes whose role contains `role` (case-insensitive)
+    pub fn find_by_role(&self, role: &str) -> Vec<NodeInfo> {
+        let role = role.to_lowercase();
+        self.nodes
+            .iter()
+            .filter(|node| node.role.to_lowercase().contains(&role))
+            .cloned()
+            .collect()
+    }
+
+    /// Get a node by ID
+    pub fn get(&self, id: u64) -> Option<&NodeInfo> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+}
+
+/// Information about an egui viewport (native window)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViewportInfo {
+    /// Viewport ID (`egui::ViewportId` hash value)
+    pub id: u64,
+    /// Parent viewport ID (None for the root viewport)
+    pub parent: Option<u64>,
+    /// Whether this is the root viewport
+    pub is_root: bool,
+    /// Window title
+    pub title: Option<String>,
+    /// Inner rectangle (content area) in screen coordinates
+    pub inner_rect: Option<Rect>,
+    /// Outer rectangle (including decorations) in screen coordinates
+    pub outer_rect: Option<Rect>,
+    /// Whether the window has keyboard focus
+    pub focused: Option<bool>,
+    /// Whether the window is minimized
+    pub minimized: Option<bool>,
+}
+
 /// Mouse button for click operations
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MouseButton {
     Left,
     Right,
@@ -79,6 +152,27 @@ pub struct LogEntry {
     pub message: String,
     /// Timestamp in milliseconds since UNIX epoch
     pub timestamp_ms: u64,
+    /// Sequence number, increasing by one per captured entry (use as `since` cursor)
+    #[serde(default)]
+    pub seq: u64,
+    /// Structured fields of the event (without `message`)
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub fields: std::collections::BTreeMap<String, serde_json::Value>,
+    /// Spans that were active when the event was recorded, outermost first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub spans: Vec<LogSpan>,
+}
+
+/// A span in the context of a log entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogSpan {
+    /// Span name
+    pub name: String,
+    /// Target module/crate
+    pub target: String,
+    /// Fields recorded on the span
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub fields: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// Frame statistics for performance monitoring
@@ -94,8 +188,166 @@ pub struct FrameStats {
     pub frame_time_max_ms: f32,
     /// Number of frames sampled
     pub sample_count: usize,
+    /// Paint statistics of the last frame (None unless the MCP plugin is installed)
+    #[serde(default)]
+    pub paint: Option<PaintStats>,
+}
+
+/// Paint statistics of a single frame
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PaintStats {
+    /// Number of clipped shapes in the frame output
+    pub shapes: usize,
+    /// Number of text shapes
+    pub text_shapes: usize,
+    /// Number of path shapes
+    pub path_shapes: usize,
+    /// Number of mesh shapes
+    pub mesh_shapes: usize,
+    /// Number of paint callbacks
+    pub callbacks: usize,
+    /// Vertices in text shapes
+    pub text_vertices: usize,
+    /// Clipped primitives after tessellation (None unless tessellation stats are enabled)
+    pub clipped_primitives: Option<usize>,
+    /// Vertices after tessellation (None unless tessellation stats are enabled)
+    pub vertices: Option<usize>,
+    /// Indices after tessellation (None unless tessellation stats are enabled)
+    pub indices: Option<usize>,
+    /// Textures created or updated
+    pub textures_set: usize,
+    /// Textures freed
+    pub textures_freed: usize,
+    /// Bytes of texture data uploaded
+    pub texture_bytes: usize,
+    /// Why this frame was painted (`file:line reason`, from `ctx.repaint_causes()`)
+    pub repaint_causes: Vec<String>,
+}
+
+/// How often a repaint cause triggered a frame during a recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepaintCauseCount {
+    /// Repaint cause (`file:line reason`)
+    pub cause: String,
+    /// Number of frames it triggered
+    pub frames: usize,
+}
+
+/// Aggregated paint statistics over a recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaintSummary {
+    /// Number of frames with paint statistics
+    pub frames: usize,
+    /// Average number of shapes per frame
+    pub avg_shapes: f32,
+    /// Maximum number of shapes in a frame
+    pub max_shapes: usize,
+    /// Average vertices per frame (None unless tessellation stats are enabled)
+    pub avg_vertices: Option<f32>,
+    /// Maximum vertices in a frame (None unless tessellation stats are enabled)
+    pub max_vertices: Option<usize>,
+    /// Frames that created, updated or freed textures
+    pub frames_with_texture_changes: usize,
+    /// Total bytes of texture data uploaded
+    pub texture_bytes: usize,
+    /// Repaint causes, most frequent first
+    pub repaint_causes: Vec<RepaintCauseCount>,
+}
+
+/// Number of recorded frames within a frame time range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistogramBucket {
+    /// Lower bound in milliseconds (inclusive)
+    pub min_ms: f32,
+    /// Upper bound in milliseconds (exclusive, None = unbounded)
+    pub max_ms: Option<f32>,
+    /// Number of frames in this range
+    pub count: usize,
 }
 
+/// Number of frames exceeding a frame time budget
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetCount {
+    /// Frame time budget in milliseconds
+    pub budget_ms: f32,
+    /// Number of frames that took longer than the budget
+    pub frames_over: usize,
+}
+
+/// A run of consecutive frames over the jank budget
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JankStreak {
+    /// Start of the first frame, in milliseconds since the recording started
+    pub start_ms: f32,
+    /// Total time of the frames in the streak in milliseconds
+    pub duration_ms: f32,
+    /// Number of frames in the streak
+    pub frames: usize,
+    /// Longest frame time in the streak in milliseconds
+    pub max_frame_time_ms: f32,
+}
+
+/// Heap allocation statistics from the counting allocator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeapStats {
+    /// Bytes currently allocated
+    pub live_bytes: u64,
+    /// Allocations not freed yet
+    pub live_allocations: u64,
+    /// Total allocations since the app started
+    pub total_allocations: u64,
+    /// Total bytes allocated since the app started
+    pub total_allocated_bytes: u64,
+    /// Allocations during the last frame
+    pub frame_allocations: u64,
+    /// Bytes allocated during the last frame
+    pub frame_allocated_bytes: u64,
+}
+
+/// Memory used by egui's textures
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextureMemory {
+    /// Number of allocated textures
+    pub textures: usize,
+    /// Bytes of texture data (width × height × bytes per pixel)
+    pub bytes: usize,
+}
+
+/// Memory statistics of the application
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryStats {
+    /// Heap statistics (None unless the counting allocator is installed)
+    pub heap: Option<HeapStats>,
+    /// Texture memory of the root viewport (None unless the MCP plugin is installed)
+    pub textures: Option<TextureMemory>,
+}
+
+/// Memory usage over a recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemorySummary {
+    /// Number of frames with heap statistics
+    pub frames: usize,
+    /// Live heap bytes after the first recorded frame
+    pub start_live_bytes: u64,
+    /// Live heap bytes after the last recorded frame
+    pub end_live_bytes: u64,
+    /// Highest live heap bytes at the end of a frame
+    pub peak_live_bytes: u64,
+    /// Change in live heap bytes over the recording
+    pub live_bytes_growth: i64,
+    /// Average allocations per frame
+    pub avg_frame_allocations: f32,
+    /// Maximum allocations in a frame
+    pub max_frame_allocations: u64,
+    /// Average bytes allocated per frame
+    pub avg_frame_allocated_bytes: f32,
+    /// Maximum bytes allocated in a frame
+    pub max_frame_allocated_bytes: u64,
+}
+
+/// Frame time budgets used when a recording does not specify any (60 and 30 FPS)
+pub const DEFAULT_FRAME_BUDGETS_MS: [f32; 2] = [16.6, 33.3];
+
 /// Performance report from a recording session
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PerfReport {
@@ -115,6 +367,302 @@ pub struct PerfReport {
     pub p95_frame_time_ms: f32,
     /// 99th percentile frame time in milliseconds
     pub p99_frame_time_ms: f32,
+    /// Frame time histogram, one bucket per range including empty ones
+    #[serde(default)]
+    pub histogram: Vec<HistogramBucket>,
+    /// Frames over each budget, smallest budget first
+    #[serde(default)]
+    pub budgets: Vec<BudgetCount>,
+    /// Budget in milliseconds a frame must exceed to count as jank (the smallest budget)
+    #[serde(default)]
+    pub jank_budget_ms: f32,
+    /// Longest runs of consecutive janky frames, longest first
+    #[serde(default)]
+    pub jank_streaks: Vec<JankStreak>,
+    /// Aggregated paint statistics (None unless the MCP plugin is installed)
+    #[serde(default)]
+    pub paint: Option<PaintSummary>,
+    /// Heap usage over the recording (None unless the counting allocator is installed)
+    #[serde(default)]
+    pub memory: Option<MemorySummary>,
+    /// Texture memory at the end of the recording (None unless the MCP plugin is installed)
+    #[serde(default)]
+    pub textures: Option<TextureMemory>,
+}
+
+/// Result of an input-to-visible-change latency measurement
+///
+/// Frame indices count output frames of the target viewport, starting at 0
+/// for the baseline frame captured before the input was injected.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatencyReport {
+    /// Whether the output changed before the timeout
+    pub changed: bool,
+    /// Index of the frame that consumed the input (None = never consumed)
+    pub consumed_frame: Option<u64>,
+    /// Milliseconds from injection until the input was consumed
+    pub consume_ms: Option<f32>,
+    /// Index of the first frame whose output differed from the baseline
+    pub changed_frame: Option<u64>,
+    /// Frames from the consuming frame to the changed frame (1 = same frame)
+    pub frames: Option<u64>,
+    /// Milliseconds from injection until the changed frame's output
+    pub latency_ms: Option<f32>,
+}
+
+/// Repaint activity of the application, used to detect when the UI has settled
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdleState {
+    /// Number of root frames recorded so far
+    pub frame_count: u64,
+    /// Consecutive frames without a requested repaint (0 = busy)
+    pub idle_frames: u32,
+    /// Whether the last frame requested an immediate repaint (e.g. animation in progress)
+    pub repaint_requested: bool,
+    /// Number of MCP inputs still waiting to be injected
+    pub pending_inputs: usize,
+    /// Milliseconds since the last recorded frame (None if no frame yet)
+    pub ms_since_last_frame: Option<u64>,
+}
+
+/// A widget referenced by its egui `Id`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WidgetRef {
+    /// egui `Id` value
+    pub id: u64,
+    /// Widget rectangle from the last pass (None if the widget was not seen)
+    pub rect: Option<Rect>,
+    /// `Id` value of the layer the widget is in
+    pub layer_id: Option<u64>,
+    /// Order of the layer the widget is in (e.g., "Middle", "Foreground")
+    pub layer_order: Option<String>,
+    /// Whether the widget is enabled
+    pub enabled: Option<bool>,
+}
+
+/// A step of an input recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedStep {
+    /// Time since the recording started in milliseconds
+    pub at_ms: u64,
+    /// Input request that reproduces the step (ClickAt, DoubleClick, Drag,
+    /// MoveMouse, Scroll or KeyboardInput)
+    pub request: Request,
+    /// Widget under the pointer when the input happened
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub element: Option<WidgetRef>,
+}
+
+/// Real user input captured by an input recording, replayable as requests
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InputRecording {
+    /// Length of the recording in milliseconds
+    pub duration_ms: u64,
+    /// Recorded steps in order
+    pub steps: Vec<RecordedStep>,
+}
+
+/// A frame of a screen recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    /// Time since the recording started in milliseconds
+    pub timestamp_ms: u64,
+    /// Base64 encoded PNG data
+    pub data: String,
+}
+
+/// Stroke of a painted shape
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VectorStroke {
+    /// Width in points
+    pub width: f32,
+    /// Color as `#rrggbbaa`
+    pub color: String,
+}
+
+/// A run of text with a single format in one row of a text shape
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextRun {
+    /// Text of the run
+    pub text: String,
+    /// Logical rectangle of the run in screen points
+    pub rect: Rect,
+    /// Text color as `#rrggbbaa`
+    pub color: String,
+    /// Font size in points
+    pub font_size: f32,
+    /// Font family (e.g., "Proportional", "Monospace")
+    pub font_family: String,
+}
+
+/// Geometry of a painted shape in screen points
+///
+/// Colors are `#rrggbbaa` strings, fills and strokes are omitted when invisible.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum VectorShape {
+    /// Rectangle with rounded corners
+    Rect {
+        rect: Rect,
+        /// Average corner radius
+        corner_radius: f32,
+        fill: Option<String>,
+        stroke: Option<VectorStroke>,
+    },
+    /// Circle
+    Circle {
+        center: [f32; 2],
+        radius: f32,
+        fill: Option<String>,
+        stroke: Option<VectorStroke>,
+    },
+    /// Ellipse with radii along the x and y axes
+    Ellipse {
+        center: [f32; 2],
+        radius: [f32; 2],
+        fill: Option<String>,
+        stroke: Option<VectorStroke>,
+    },
+    /// Polyline or polygon (line segments and flattened Bézier curves too)
+    Path {
+        points: Vec<[f32; 2]>,
+        closed: bool,
+        fill: Option<String>,
+        stroke: Option<VectorStroke>,
+    },
+    /// Laid out text
+    Text {
+        /// Bounding rectangle of the text
+        rect: Rect,
+        /// Full text
+        text: String,
+        /// Text runs row by row
+        runs: Vec<TextRun>,
+        /// Rotation in radians around the top-left corner
+        angle: f32,
+    },
+    /// Triangle mesh (e.g. images)
+    Mesh {
+        /// Bounding rectangle of the vertices
+        rect: Rect,
+        /// Number of triangles
+        triangles: usize,
+        /// Whether the mesh samples a user texture (an image)
+        textured: bool,
+    },
+    /// Custom painting by a backend callback
+    Callback { rect: Rect },
+}
+
+/// A painted shape with the layer it was painted on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaintedShape {
+    /// `Id` value of the layer
+    pub layer_id: u64,
+    /// Layer order: "Background", "Middle", "Foreground", "Tooltip" or "Debug"
+    pub layer_order: String,
+    /// Clip rectangle (limited to the screen)
+    pub clip_rect: Rect,
+    #[serde(flatten)]
+    pub shape: VectorShape,
+}
+
+/// Paint output of a frame as vector shapes, back to front
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrameShapes {
+    /// Viewport the frame belongs to
+    pub viewport_id: u64,
+    /// Screen rectangle in points
+    pub screen_rect: Rect,
+    /// Physical pixels per point
+    pub pixels_per_point: f32,
+    /// Shapes in paint order
+    pub shapes: Vec<PaintedShape>,
+}
+
+/// An egui layer (area, window, popup, tooltip)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayerInfo {
+    /// `Id` value of the layer
+    pub id: u64,
+    /// Layer order: "Background", "Middle", "Foreground", "Tooltip" or "Debug"
+    pub order: String,
+    /// Whether the layer was visible this or the last pass
+    pub visible: bool,
+    /// Area rectangle (None for layers without an area, e.g. the background)
+    pub rect: Option<Rect>,
+    /// `Id` value of the parent layer if this is a sublayer
+    pub parent: Option<u64>,
+}
+
+/// Pointer state as seen by egui
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PointerInfo {
+    /// Latest known pointer position
+    pub latest_pos: Option<[f32; 2]>,
+    /// Position used for hovering (None if the pointer left the window)
+    pub hover_pos: Option<[f32; 2]>,
+    /// Position where the current press started
+    pub press_origin: Option<[f32; 2]>,
+    /// Whether the primary button is down
+    pub primary_down: bool,
+    /// Whether the secondary button is down
+    pub secondary_down: bool,
+    /// Whether the middle button is down
+    pub middle_down: bool,
+    /// `Id` value of the top-most interactable layer under the pointer
+    pub layer_under_pointer: Option<u64>,
+}
+
+/// Snapshot of egui `Context` internals for debugging interactions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextInfo {
+    /// Viewport the snapshot was taken in
+    pub viewport_id: u64,
+    /// Widget with keyboard focus
+    pub focused: Option<WidgetRef>,
+    /// Widgets the pointer hovers (usually 0-1)
+    pub hovered: Vec<WidgetRef>,
+    /// Widget being dragged
+    pub dragged: Option<WidgetRef>,
+    /// Widget clicked this pass
+    pub clicked: Option<WidgetRef>,
+    /// Pointer state
+    pub pointer: PointerInfo,
+    /// Whether any popup, menu or combo box is open
+    pub any_popup_open: bool,
+    /// Whether egui wants pointer input (pointer over an area or dragging)
+    pub wants_pointer_input: bool,
+    /// Whether egui wants keyboard input (a text edit has focus)
+    pub wants_keyboard_input: bool,
+    /// Layers back-to-front (top-most last)
+    pub layers: Vec<LayerInfo>,
+}
+
+/// A Chrome trace event (as understood by Perfetto and `chrome://tracing`)
+///
+/// Field names follow the Chrome trace event format, so a list of these can be
+/// serialized directly as `{"traceEvents": [...]}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceEvent {
+    /// Event name (span name, "frame", or metadata name like "thread_name")
+    pub name: String,
+    /// Category (span target)
+    pub cat: String,
+    /// Phase: "X" for complete events, "M" for metadata
+    pub ph: String,
+    /// Start timestamp in microseconds since the recording started
+    pub ts: f64,
+    /// Duration in microseconds (complete events only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dur: Option<f64>,
+    /// Process ID
+    pub pid: u32,
+    /// Thread ID (small per-recording number)
+    pub tid: u64,
+    /// Extra arguments (e.g., thread name for metadata events)
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub args: std::collections::BTreeMap<String, String>,
 }
 
 /// Request types for IPC communication
@@ -128,7 +676,10 @@ pub enum Request {
     Ping,
 
     /// Request a screenshot of the application window
-    TakeScreenshot,
+    TakeScreenshot {
+        /// Target viewport ID (None = root viewport)
+        viewport_id: Option<u64>,
+    },
 
     /// Request a screenshot of a specific region of the application window
     TakeScreenshotRegion {
@@ -140,6 +691,8 @@ pub enum Request {
         width: f32,
         /// Height of the region
         height: f32,
+        /// Target viewport ID (None = root viewport)
+        viewport_id: Option<u64>,
     },
 
     /// Click at specific screen coordinates
@@ -150,12 +703,16 @@ pub enum Request {
         y: f32,
         /// Mouse button to click
         button: MouseButton,
+        /// Target viewport ID (None = root viewport)
+        viewport_id: Option<u64>,
     },
 
     /// Send keyboard input
     KeyboardInput {
         /// Key to press (e.g., "Enter", "Tab", "a", "Ctrl+C")
         key: String,
+        /// Target viewport ID (None = root viewport)
+        viewport_id: Option<u64>,
     },
 
     /// Scroll at specific coordinates
@@ -168,6 +725,8 @@ pub enum Request {
         delta_x: f32,
         /// Vertical scroll delta
         delta_y: f32,
+        /// Target viewport ID (None = root viewport)
+        viewport_id: Option<u64>,
     },
 
     /// Move mouse to specific coordinates (for hover effects)
@@ -176,6 +735,8 @@ pub enum Request {
         x: f32,
         /// Y coordinate (relative to window)
         y: f32,
+        /// Target viewport ID (None = root viewport)
+        viewport_id: Option<u64>,
     },
 
     /// Drag from one position to another
@@ -190,6 +751,8 @@ pub enum Request {
         end_y: f32,
         /// Mouse button to use
         button: MouseButton,
+        /// Target viewport ID (None = root viewport)
+        viewport_id: Option<u64>,
     },
 
     /// Double click at specific screen coordinates
@@ -200,8 +763,13 @@ pub enum Request {
         y: f32,
         /// Mouse button to click
         button: MouseButton,
+        /// Target viewport ID (None = root viewport)
+        viewport_id: Option<u64>,
     },
 
+    /// List the viewports (native windows) of the application
+    ListViewports,
+
     /// Highlight an element with a colored border
     HighlightElement {
         /// Bounding box x coordinate
@@ -227,12 +795,40 @@ pub enum Request {
         /// If None, returns all levels
         level: Option<String>,
         /// Maximum number of entries to return
+        ///
+        /// Without `since` the most recent entries are returned; with `since`
+        /// the oldest entries after the cursor, so the next page can follow.
         limit: Option<usize>,
+        /// Only entries whose target starts with this prefix
+        #[serde(default)]
+        target: Option<String>,
+        /// Only entries whose target matches this regex
+        #[serde(default)]
+        target_regex: Option<String>,
+        /// Only entries whose message matches this regex
+        #[serde(default)]
+        message_regex: Option<String>,
+        /// Only entries with a sequence number greater than this cursor
+        #[serde(default)]
+        since: Option<u64>,
+        /// Read the persisted log files instead of the in-memory buffer
+        ///
+        /// Returns the newest `limit` entries (default 1000), as many as fit
+        /// in one message. `since` is rejected, as the files span several
+        /// runs of the app and sequence numbers start over with each run.
+        #[serde(default)]
+        history: bool,
     },
 
     /// Clear the log buffer
     ClearLogs,
 
+    /// Change which events the log layer captures
+    SetLogFilter {
+        /// `EnvFilter` directives (e.g. "warn,my_app::net=debug"); empty = capture everything
+        directives: String,
+    },
+
     /// Get current frame statistics
     GetFrameStats,
 
@@ -240,10 +836,117 @@ pub enum Request {
     StartPerfRecording {
         /// Duration to record in milliseconds (0 = until stopped)
         duration_ms: u64,
+        /// Also capture tracing span timings (requires `McpProfileLayer`)
+        #[serde(default)]
+        profile: bool,
+        /// Frame time budgets in milliseconds (empty = `DEFAULT_FRAME_BUDGETS_MS`)
+        #[serde(default)]
+        budgets_ms: Vec<f32>,
     },
 
     /// Stop and get performance report
     GetPerfReport,
+
+    /// Get heap and texture memory statistics
+    GetMemoryStats,
+
+    /// Get a page of the span timings captured by the current or last
+    /// profiling recording
+    ///
+    /// A long recording does not fit in one message, so the events are
+    /// fetched in pages; a page may hold fewer than `limit` events to stay
+    /// under [`MAX_MESSAGE_SIZE`].
+    ExportPerfTrace {
+        /// Index of the first event to return (the number already received)
+        #[serde(default)]
+        offset: usize,
+        /// Maximum number of events to return
+        limit: usize,
+    },
+
+    /// Inject an input and measure how long it takes to change the output
+    ///
+    /// The input must be one of the input requests (`ClickAt`, `DoubleClick`,
+    /// `MoveMouse`, `KeyboardInput`, `Scroll` or `Drag`) and is routed to its
+    /// `viewport_id`.
+    MeasureLatency {
+        /// Input request to inject
+        input: Box<Request>,
+        /// Maximum time to wait for a change in milliseconds
+        timeout_ms: u64,
+    },
+
+    /// Freeze the time reported to egui (animations, carets and tooltip delays stop)
+    FreezeTime,
+
+    /// Run a number of frames with a fixed time step, then stay frozen
+    ///
+    /// Freezes time first if it is not frozen yet.
+    StepFrames {
+        /// Number of frames to run
+        frames: u32,
+        /// Time advanced per frame in milliseconds (also used as `predicted_dt`)
+        dt_ms: f32,
+    },
+
+    /// Resume real time
+    ResumeTime,
+
+    /// Get the repaint activity of the application
+    GetIdleState,
+
+    /// Inspect egui `Context` internals (focus, hover, popups, layers)
+    InspectContext {
+        /// Target viewport ID (None = root viewport)
+        viewport_id: Option<u64>,
+    },
+
+    /// Start recording real user input (replaces a running recording)
+    StartInputRecording,
+
+    /// Stop recording user input and return the recording
+    StopInputRecording,
+
+    /// Get the UI tree built from the app's AccessKit output
+    GetUiTree,
+
+    /// Get a page of the paint output of the next frame as vector shapes
+    ///
+    /// A frame can hold more shapes than fit in one message. `offset` 0
+    /// captures the next frame of the viewport; higher offsets page through
+    /// that captured frame. A page may hold fewer than `limit` shapes to stay
+    /// under [`MAX_MESSAGE_SIZE`].
+    GetFrameShapes {
+        /// Target viewport ID (None = root viewport)
+        viewport_id: Option<u64>,
+        /// Index of the first shape to return (the number already received)
+        #[serde(default)]
+        offset: usize,
+        /// Maximum number of shapes to return
+        limit: usize,
+        /// Only keep text shapes (applies when the frame is captured)
+        #[serde(default)]
+        text_only: bool,
+    },
+
+    /// Start capturing screenshots at a fixed rate (replaces a running recording)
+    StartScreenRecording {
+        /// Frames per second
+        fps: f32,
+        /// Stop capturing after this many milliseconds
+        max_duration_ms: u64,
+        /// Target viewport ID (None = root viewport)
+        viewport_id: Option<u64>,
+    },
+
+    /// Stop the screen recording; the frames are then taken with `TakeRecordedFrame`
+    StopScreenRecording,
+
+    /// Take the oldest frame of the stopped screen recording
+    ///
+    /// A whole recording does not fit in one message, so frames are fetched
+    /// one at a time.
+    TakeRecordedFrame,
 }
 
 /// Response types for IPC communication
@@ -271,6 +974,12 @@ pub enum Response {
     Logs {
         /// Log entries (oldest first)
         entries: Vec<LogEntry>,
+        /// Cursor to pass as `since` to get the entries after these
+        ///
+        /// Lower than the `since` that was sent if the app restarted in
+        /// between and its sequence numbers started over.
+        #[serde(default)]
+        cursor: u64,
     },
 
     /// Frame statistics response
@@ -284,6 +993,91 @@ pub enum Response {
         /// Performance report (None if not recording or no data)
         report: Option<PerfReport>,
     },
+
+    /// Viewport list response
+    Viewports {
+        /// Known viewports (root first)
+        viewports: Vec<ViewportInfo>,
+    },
+
+    /// Time control state response
+    TimeState {
+        /// Whether time is frozen
+        frozen: bool,
+        /// Current virtual time in seconds (None if not frozen or no frame seen yet)
+        time: Option<f64>,
+    },
+
+    /// Idle state response
+    IdleStateResponse {
+        /// Current repaint activity
+        state: IdleState,
+    },
+
+    /// Performance trace response
+    PerfTrace {
+        /// Trace events (frames and spans) of the requested page
+        events: Vec<TraceEvent>,
+        /// Number of events dropped because the profile buffer was full
+        dropped_events: usize,
+        /// Number of events of the whole trace
+        #[serde(default)]
+        total_events: usize,
+    },
+
+    /// Input recording response
+    InputRecordingResponse {
+        /// The recorded steps
+        recording: InputRecording,
+    },
+
+    /// Memory statistics response
+    MemoryStatsResponse {
+        /// Memory statistics
+        stats: MemoryStats,
+    },
+
+    /// Latency measurement response
+    Latency {
+        /// Measurement result
+        report: LatencyReport,
+    },
+
+    /// Context inspection response
+    ContextInfoResponse {
+        /// Snapshot of the egui context (boxed to keep `Response` small)
+        info: Box<ContextInfo>,
+    },
+
+    /// Frame shapes response
+    FrameShapesResponse {
+        /// The frame with the shapes of the requested page (boxed to keep
+        /// `Response` small)
+        shapes: Box<FrameShapes>,
+        /// Number of shapes of the whole frame
+        #[serde(default)]
+        total_shapes: usize,
+    },
+
+    /// UI tree response
+    UiTreeResponse {
+        /// Tree of the root viewport
+        tree: UiTree,
+    },
+
+    /// Stopped screen recording response
+    ScreenRecordingStopped {
+        /// Number of captured frames
+        frames: usize,
+        /// Length of the recording in milliseconds
+        duration_ms: u64,
+    },
+
+    /// Recorded frame response
+    RecordedFrameResponse {
+        /// Oldest remaining frame (None once all frames were taken)
+        frame: Option<RecordedFrame>,
+    },
 }
 
 /// Protocol errors
@@ -401,12 +1195,19 @@ mod tests {
         assert!(path.to_string_lossy().contains("egui-mcp.sock"));
     }
 
+    #[test]
+    fn test_crash_file_path() {
+        let path = crash_file_path(Path::new("/run/user/1000/egui-mcp.sock"));
+        assert_eq!(path, Path::new("/run/user/1000/egui-mcp.crash.json"));
+    }
+
     #[test]
     fn test_click_at_request() {
         let req = Request::ClickAt {
             x: 100.0,
             y: 200.0,
             button: MouseButton::Left,
+            viewport_id: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("ClickAt"));
@@ -417,6 +1218,7 @@ mod tests {
     fn test_keyboard_input_request() {
         let req = Request::KeyboardInput {
             key: "Enter".to_string(),
+            viewport_id: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("KeyboardInput"));
@@ -431,6 +1233,7 @@ mod tests {
             end_x: 100.0,
             end_y: 200.0,
             button: MouseButton::Left,
+            viewport_id: Some(7),
         };
         let json = serde_json::to_string(&req).unwrap();
         let decoded: Request = serde_json::from_str(&json).unwrap();
@@ -440,6 +1243,7 @@ mod tests {
             end_x,
             end_y,
             button,
+            viewport_id,
         } = decoded
         {
             assert_eq!(start_x, 10.0);
@@ -447,6 +1251,7 @@ mod tests {
             assert_eq!(end_x, 100.0);
             assert_eq!(end_y, 200.0);
             assert!(matches!(button, MouseButton::Left));
+            assert_eq!(viewport_id, Some(7));
         } else {
             panic!("Expected Drag request");
         }
@@ -459,6 +1264,7 @@ mod tests {
             y: 60.0,
             delta_x: -10.0,
             delta_y: 20.0,
+            viewport_id: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         let decoded: Request = serde_json::from_str(&json).unwrap();
@@ -467,6 +1273,7 @@ mod tests {
             y,
             delta_x,
             delta_y,
+            ..
         } = decoded
         {
             assert_eq!(x, 50.0);
@@ -478,6 +1285,90 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_request_without_viewport_id_defaults_to_root() {
+        let decoded: Request = serde_json::from_str(r#"{"type":"TakeScreenshot"}"#).unwrap();
+        assert!(matches!(
+            decoded,
+            Request::TakeScreenshot { viewport_id: None }
+        ));
+
+        let decoded: Request =
+            serde_json::from_str(r#"{"type":"MoveMouse","x":1.0,"y":2.0}"#).unwrap();
+        assert!(matches!(
+            decoded,
+            Request::MoveMouse {
+                viewport_id: None,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_response_roundtrip_viewports() {
+        let resp = Response::Viewports {
+            viewports: vec![ViewportInfo {
+                id: 42,
+                parent: None,
+                is_root: true,
+                title: Some("Main".to_string()),
+                inner_rect: Some(Rect {
+                    x: 0.0,
+                    y: 0.0,
+                    width: 500.0,
+                    height: 600.0,
+                }),
+                outer_rect: None,
+                focused: Some(true),
+                minimized: None,
+            }],
+        };
+        let json = serde_json::to_string(&resp).unwrap();
+        let decoded: Response = serde_json::from_str(&json).unwrap();
+        if let Response::Viewports { viewports } = decoded {
+            assert_eq!(viewports.len(), 1);
+            assert_eq!(viewports[0].id, 42);
+            assert!(viewports[0].is_root);
+            assert_eq!(viewports[0].title.as_deref(), Some("Main"));
+        } else {
+            panic!("Expected Viewports response");
+        }
+    }
+
+    #[test]
+    fn test_request_roundtrip_step_frames() {
+        let req = Request::StepFrames {
+            frames: 3,
+            dt_ms: 16.0,
+        };
+        let json = serde_json::to_string(&req).unwrap();
+        let decoded: Request = serde_json::from_str(&json).unwrap();
+
+        if let Request::StepFrames { frames, dt_ms } = decoded {
+            assert_eq!(frames, 3);
+            assert_eq!(dt_ms, 16.0);
+        } else {
+            panic!("Expected StepFrames request");
+        }
+    }
+
+    #[test]
+    fn test_response_roundtrip_time_state() {
+        let resp = Response::TimeState {
+            frozen: true,
+            time: Some(1.5),
+        };
+        let json = serde_json::to_string(&resp).unwrap();
+        let decoded: Response = serde_json::from_str(&json).unwrap();
+
+        if let Response::TimeState { frozen, time } = decoded {
+            assert!(frozen);
+            assert_eq!(time, Some(1.5));
+        } else {
+            panic!("Expected TimeState response");
+        }
+    }
+
     #[test]
     fn test_response_roundtrip_screenshot() {
         let resp = Response::Screenshot {
@@ -494,6 +1385,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_screen_recording_roundtrip() {
+        let req = Request::StartScreenRecording {
+            fps: 10.0,
+            max_duration_ms: 5000,
+            viewport_id: None,
+        };
+        let json = serde_json::to_string(&req).unwrap();
+        let decoded: Request = serde_json::from_str(&json).unwrap();
+        assert!(matches!(
+            decoded,
+            Request::StartScreenRecording {
+                max_duration_ms: 5000,
+                viewport_id: None,
+                ..
+            }
+        ));
+
+        let resp = Response::RecordedFrameResponse {
+            frame: Some(RecordedFrame {
+                timestamp_ms: 100,
+                data: "base64data".to_string(),
+            }),
+        };
+        let json = serde_json::to_string(&resp).unwrap();
+        let decoded: Response = serde_json::from_str(&json).unwrap();
+        match decoded {
+            Response::RecordedFrameResponse { frame: Some(frame) } => {
+                assert_eq!(frame.timestamp_ms, 100);
+                assert_eq!(frame.data, "base64data");
+            }
+            _ => panic!("Expected RecordedFrameResponse"),
+        }
+    }
+
     #[test]
     fn test_response_roundtrip_error() {
         let resp = Response::Error {
@@ -546,6 +1472,13 @@ mod tests {
             target: "my_app".to_string(),
             message: "Hello world".to_string(),
             timestamp_ms: 1234567890,
+            seq: 7,
+            fields: [("user_id".to_string(), serde_json::json!(42))].into(),
+            spans: vec![LogSpan {
+                name: "request".to_string(),
+                target: "my_app".to_string(),
+                fields: Default::default(),
+            }],
         };
         let json = serde_json::to_string(&entry).unwrap();
         let decoded: LogEntry = serde_json::from_str(&json).unwrap();
@@ -554,6 +1487,34 @@ mod tests {
         assert_eq!(decoded.target, "my_app");
         assert_eq!(decoded.message, "Hello world");
         assert_eq!(decoded.timestamp_ms, 1234567890);
+        assert_eq!(decoded.seq, 7);
+        assert_eq!(decoded.fields["user_id"], 42);
+        assert_eq!(decoded.spans[0].name, "request");
+        assert!(!json.contains("\"fields\":{}"));
+    }
+
+    #[test]
+    fn test_get_logs_without_filters() {
+        let json = r#"{"type":"GetLogs","level":"INFO","limit":10}"#;
+        let req: Request = serde_json::from_str(json).unwrap();
+        assert!(matches!(
+            req,
+            Request::GetLogs {
+                since: None,
+                target: None,
+                history: false,
+                ..
+            }
+        ));
+
+        let json = r#"{"type":"Logs","entries":[{"level":"INFO","target":"app","message":"hi","timestamp_ms":1}]}"#;
+        match serde_json::from_str::<Response>(json).unwrap() {
+            Response::Logs { entries, cursor } => {
+                assert_eq!(cursor, 0);
+                assert!(entries[0].fields.is_empty());
+            }
+            _ => panic!("Expected Logs"),
+        }
     }
 
     #[test]
@@ -564,12 +1525,274 @@ mod tests {
             frame_time_min_ms: 15.0,
             frame_time_max_ms: 20.0,
             sample_count: 100,
+            paint: Some(PaintStats {
+                shapes: 12,
+                repaint_causes: vec!["src/main.rs:10 animation".to_string()],
+                ..Default::default()
+            }),
         };
         let json = serde_json::to_string(&stats).unwrap();
         let decoded: FrameStats = serde_json::from_str(&json).unwrap();
 
         assert_eq!(decoded.fps, 60.0);
         assert_eq!(decoded.sample_count, 100);
+        let paint = decoded.paint.unwrap();
+        assert_eq!(paint.shapes, 12);
+        assert!(paint.vertices.is_none());
+        assert_eq!(paint.repaint_causes.len(), 1);
+    }
+
+    #[test]
+    fn test_frame_stats_without_paint_stats() {
+        let json = r#"{"fps":60.0,"frame_time_ms":16.67,"frame_time_min_ms":15.0,"frame_time_max_ms":20.0,"sample_count":100}"#;
+        let decoded: FrameStats = serde_json::from_str(json).unwrap();
+        assert!(decoded.paint.is_none());
+    }
+
+    #[test]
+    fn test_idle_state_serialization() {
+        let state = IdleState {
+            frame_count: 42,
+            idle_frames: 3,
+            repaint_requested: false,
+            pending_inputs: 0,
+            ms_since_last_frame: None,
+        };
+        let json = serde_json::to_string(&state).unwrap();
+        let decoded: IdleState = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(decoded.frame_count, 42);
+        assert_eq!(decoded.idle_frames, 3);
+        assert!(!decoded.repaint_requested);
+        assert!(decoded.ms_since_last_frame.is_none());
+    }
+
+    #[test]
+    fn test_response_roundtrip_context_info() {
+        let resp = Response::ContextInfoResponse {
+            info: Box::new(ContextInfo {
+                viewport_id: 1,
+                focused: Some(WidgetRef {
+                    id: 42,
+                    rect: None,
+                    layer_id: Some(7),
+                    layer_order: Some("Background".to_string()),
+                    enabled: Some(true),
+                }),
+                hovered: vec![],
+                dragged: None,
+                clicked: None,
+                pointer: PointerInfo {
+                    latest_pos: Some([1.0, 2.0]),
+                    hover_pos: Some([1.0, 2.0]),
+                    press_origin: None,
+                    primary_down: false,
+                    secondary_down: false,
+                    middle_down: false,
+                    layer_under_pointer: Some(7),
+                },
+                any_popup_open: false,
+                wants_pointer_input: true,
+                wants_keyboard_input: false,
+                layers: vec![LayerInfo {
+                    id: 7,
+                    order: "Background".to_string(),
+                    visible: true,
+                    rect: None,
+                    parent: None,
+                }],
+            }),
+        };
+        let json = serde_json::to_string(&resp).unwrap();
+        let decoded: Response = serde_json::from_str(&json).unwrap();
+
+        if let Response::ContextInfoResponse { info } = decoded {
+            assert_eq!(info.focused.unwrap().id, 42);
+            assert_eq!(info.pointer.latest_pos, Some([1.0, 2.0]));
+            assert_eq!(info.layers.len(), 1);
+        } else {
+            panic!("Expected ContextInfoResponse");
+        }
+    }
+
+    #[test]
+    fn test_trace_event_chrome_format() {
+        let event = TraceEvent {
+            name: "layout".to_string(),
+            cat: "my_app::ui".to_string(),
+            ph: "X".to_string(),
+            ts: 10.0,
+            dur: Some(2.5),
+            pid: 1,
+            tid: 0,
+            args: Default::default(),
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["ph"], "X");
+        assert_eq!(json["dur"], 2.5);
+        assert!(json.get("args").is_none());
+
+        let metadata = TraceEvent { dur: None, ..event };
+        let json = serde_json::to_value(&metadata).unwrap();
+        assert!(json.get("dur").is_none());
+    }
+
+    #[test]
+    fn test_start_perf_recording_defaults_to_no_profile() {
+        let json = r#"{"type":"StartPerfRecording","duration_ms":0}"#;
+        let req: Request = serde_json::from_str(json).unwrap();
+        assert!(matches!(
+            req,
+            Request::StartPerfRecording {
+                duration_ms: 0,
+                profile: false,
+                ref budgets_ms,
+            } if budgets_ms.is_empty()
+        ));
+    }
+
+    #[test]
+    fn test_request_roundtrip_measure_latency() {
+        let req = Request::MeasureLatency {
+            input: Box::new(Request::ClickAt {
+                x: 10.0,
+                y: 20.0,
+                button: MouseButton::Left,
+                viewport_id: None,
+            }),
+            timeout_ms: 1000,
+        };
+        let json = serde_json::to_string(&req).unwrap();
+        let decoded: Request = serde_json::from_str(&json).unwrap();
+        match decoded {
+            Request::MeasureLatency { input, timeout_ms } => {
+                assert_eq!(timeout_ms, 1000);
+                assert!(matches!(*input, Request::ClickAt { x, y, .. } if x == 10.0 && y == 20.0));
+            }
+            _ => panic!("Expected MeasureLatency"),
+        }
+    }
+
+    #[test]
+    fn test_ui_tree_search() {
+        let node = |id: u64, role: &str, label: Option<&str>| NodeInfo {
+            id,
+            role: role.to_string(),
+            label: label.map(str::to_string),
+            value: None,
+            bounds: None,
+            children: Vec::new(),
+            toggled: None,
+            disabled: false,
+            focused: false,
+        };
+        let tree = UiTree {
+            roots: vec![1],
+            nodes: vec![
+                node(1, "Window", None),
+                node(2, "Button", Some("Save")),
+                node(3, "Button", Some("Save as...")),
+                node(4, "CheckBox", Some("Autosave")),
+            ],
+        };
+
+        let ids = |nodes: Vec<NodeInfo>| nodes.iter().map(|n| n.id).collect::<Vec<_>>();
+        assert_eq!(ids(tree.find_by_label("Save", false)), vec![2, 3]);
+        assert_eq!(ids(tree.find_by_label("Save", true)), vec![2]);
+        assert_eq!(ids(tree.find_by_role("button")), vec![2, 3]);
+        assert_eq!(tree.get(4).unwrap().label.as_deref(), Some("Autosave"));
+        assert!(tree.get(5).is_none());
+    }
+
+    #[test]
+    fn test_painted_shape_is_flat() {
+        let shape = PaintedShape {
+            layer_id: 7,
+            layer_order: "Middle".to_string(),
+            clip_rect: Rect {
+                x: 0.0,
+                y: 0.0,
+                width: 100.0,
+                height: 50.0,
+            },
+            shape: VectorShape::Circle {
+                center: [10.0, 20.0],
+                radius: 5.0,
+                fill: Some("#ff0000ff".to_string()),
+                stroke: None,
+            },
+        };
+        let json = serde_json::to_value(&shape).unwrap();
+        assert_eq!(json["kind"], "circle");
+        assert_eq!(json["layer_id"], 7);
+        assert_eq!(json["radius"], 5.0);
+
+        let parsed: PaintedShape = serde_json::from_value(json).unwrap();
+        assert!(matches!(parsed.shape, VectorShape::Circle { radius, .. } if radius == 5.0));
+    }
+
+    #[test]
+    fn test_input_recording_script_format() {
+        let json = r#"{
+            "duration_ms": 1200,
+            "steps": [
+                {"at_ms": 300, "request": {"type": "ClickAt", "x": 10.0, "y": 20.0, "button": "Left", "viewport_id": null},
+                 "element": {"id": 42, "rect": {"x": 0.0, "y": 10.0, "width": 50.0, "height": 20.0}, "layer_id": 1, "layer_order": "Middle", "enabled": true}},
+                {"at_ms": 900, "request": {"type": "KeyboardInput", "key": "Enter", "viewport_id": null}}
+            ]
+        }"#;
+        let recording: InputRecording = serde_json::from_str(json).unwrap();
+        assert_eq!(recording.steps.len(), 2);
+        assert_eq!(recording.steps[0].element.as_ref().unwrap().id, 42);
+        assert!(matches!(
+            &recording.steps[1].request,
+            Request::KeyboardInput { key, .. } if key == "Enter"
+        ));
+
+        let json = serde_json::to_string(&recording).unwrap();
+        assert_eq!(json.matches("\"element\"").count(), 1);
+    }
+
+    #[test]
+    fn test_perf_report_without_histogram_fields() {
+        let json = r#"{
+            "duration_ms": 1000,
+            "total_frames": 60,
+            "avg_fps": 60.0,
+            "avg_frame_time_ms": 16.6,
+            "min_frame_time_ms": 15.0,
+            "max_frame_time_ms": 40.0,
+            "p95_frame_time_ms": 17.0,
+            "p99_frame_time_ms": 40.0
+        }"#;
+        let report: PerfReport = serde_json::from_str(json).unwrap();
+        assert!(report.histogram.is_empty());
+        assert!(report.budgets.is_empty());
+        assert!(report.jank_streaks.is_empty());
+        assert!(report.paint.is_none());
+        assert!(report.memory.is_none());
+    }
+
+    #[test]
+    fn test_response_roundtrip_memory_stats() {
+        let resp = Response::MemoryStatsResponse {
+            stats: MemoryStats {
+                heap: None,
+                textures: Some(TextureMemory {
+                    textures: 2,
+                    bytes: 4096,
+                }),
+            },
+        };
+        let json = serde_json::to_string(&resp).unwrap();
+        let decoded: Response = serde_json::from_str(&json).unwrap();
+        match decoded {
+            Response::MemoryStatsResponse { stats } => {
+                assert!(stats.heap.is_none());
+                assert_eq!(stats.textures.unwrap().bytes, 4096);
+            }
+            _ => panic!("Expected MemoryStatsResponse"),
+        }
     }
 
     #[test]
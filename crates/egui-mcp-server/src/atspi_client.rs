@@ -341,59 +341,9 @@ impl AtspiClient {
         exact: bool,
     ) -> Result<Vec<NodeInfo>, BoxError> {
         let tree = self.get_ui_tree_by_app_name(app_name).await?;
-        let Some(tree) = tree else {
-            return Ok(vec![]);
-        };
-
-        let results: Vec<NodeInfo> = tree
-            .nodes
-            .iter()
-            .filter(|node| {
-                if let Some(label) = &node.label {
-                    if exact {
-                        label == pattern
-                    } else {
-                        label.contains(pattern)
-                    }
-                } else {
-                    false
-                }
-            })
-            .cloned()
-            .collect();
-
-        Ok(results)
-    }
-
-    /// Find UI elements by role
-    pub async fn find_by_role(
-        &self,
-        app_name: &str,
-        role: &str,
-    ) -> Result<Vec<NodeInfo>, BoxError> {
-        let tree = self.get_ui_tree_by_app_name(app_name).await?;
-        let Some(tree) = tree else {
-            return Ok(vec![]);
-        };
-
-        let results: Vec<NodeInfo> = tree
-            .nodes
-            .iter()
-            .filter(|node| node.role.to_lowercase().contains(&role.to_lowercase()))
-            .cloned()
-            .collect();
-
-        Ok(results)
-    }
-
-    /// Get a specific element by ID
-    pub async fn get_element(&self, app_name: &str, id: u64) -> Result<Option<NodeInfo>, BoxError> {
-        let tree = self.get_ui_tree_by_app_name(app_name).await?;
-        if let Some(tree) = tree {
-            Ok(tree.nodes.into_iter().find(|n| n.id == id))
-        } else {
-            Ok(None)
-        }
+        Ok(tree
+            .map(|tree| tree.find_by_label(pattern, exact))
+            .unwrap_or_default())
     }
 
     // ========================================================================
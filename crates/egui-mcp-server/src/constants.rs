@@ -11,6 +11,49 @@ pub const DEFAULT_WAIT_TIMEOUT_MS: u64 = 5000;
 /// Polling interval for wait operations in milliseconds
 pub const WAIT_POLL_INTERVAL_MS: u64 = 100;
 
+/// Default number of consecutive idle frames for wait_for_idle
+pub const DEFAULT_IDLE_STABLE_FRAMES: u32 = 3;
+
+/// Polling interval for wait_for_idle in milliseconds (about one frame)
+pub const IDLE_POLL_INTERVAL_MS: u64 = 16;
+
+/// Time without any frame after which an idle app is considered settled,
+/// for apps that stop repainting entirely when nothing changes
+pub const IDLE_QUIET_PERIOD_MS: u64 = 200;
+
+/// Polling interval for forwarding app logs as MCP notifications in milliseconds
+pub const LOG_STREAM_POLL_INTERVAL_MS: u64 = 250;
+
+/// Default timeout for measure_latency in milliseconds
+pub const DEFAULT_LATENCY_TIMEOUT_MS: u64 = 2000;
+
+/// Default directory for golden screenshot baselines (relative to the working directory)
+pub const DEFAULT_BASELINE_DIR: &str = "egui-mcp-baselines";
+
+/// Default similarity score a screenshot needs to match its baseline
+pub const DEFAULT_BASELINE_THRESHOLD: f64 = 0.99;
+
+/// Default JPEG quality for screenshots
+pub const DEFAULT_JPEG_QUALITY: u8 = 80;
+
+/// Default frame rate of screen recordings
+pub const DEFAULT_RECORDING_FPS: f32 = 10.0;
+
+/// Maximum frame rate of screen recordings
+pub const MAX_RECORDING_FPS: f32 = 30.0;
+
+/// Default length limit of screen recordings in milliseconds
+pub const DEFAULT_RECORDING_DURATION_MS: u64 = 10_000;
+
+/// Maximum length limit of screen recordings in milliseconds
+pub const MAX_RECORDING_DURATION_MS: u64 = 60_000;
+
+/// Maximum number of trace events fetched per IPC request by export_perf_trace
+pub const PERF_TRACE_PAGE_SIZE: usize = 2_000;
+
+/// Maximum number of shapes fetched per IPC request for vector screenshots
+pub const FRAME_SHAPES_PAGE_SIZE: usize = 2_000;
+
 /// Default highlight color (red with semi-transparency)
 #[allow(dead_code)]
 pub const DEFAULT_HIGHLIGHT_COLOR: [u8; 4] = [255, 0, 0, DEFAULT_COLOR_ALPHA];
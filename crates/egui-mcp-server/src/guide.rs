@@ -103,9 +103,11 @@ STEP 3: Run your application
 ENVIRONMENT VARIABLES
 --------------------------------------------------------------------------------
 
-  EGUI_MCP_APP_NAME    (Required) Target application's window title
-  XDG_RUNTIME_DIR      Runtime directory for IPC socket (WSL: /mnt/wslg/runtime-dir)
-  RUST_LOG             Log level (e.g., "info", "debug")
+  EGUI_MCP_APP_NAME          (Required) Target application's window title
+  XDG_RUNTIME_DIR            Runtime directory for IPC socket (WSL: /mnt/wslg/runtime-dir)
+  RUST_LOG                   Log level (e.g., "info", "debug")
+  EGUI_MCP_BASELINE_DIR      Directory for save_baseline/check_baseline (default: ./egui-mcp-baselines)
+  EGUI_MCP_UPDATE_BASELINES  Set to 1 to accept new captures in check_baseline
 
 --------------------------------------------------------------------------------
 AVAILABLE MCP TOOLS
@@ -128,8 +130,29 @@ Interaction:
 
 Screenshots:
   - take_screenshot   Capture the application window
+  - annotated_screenshot  Screenshot with numbered boxes and an element ID legend
   - compare_screenshots  Compare two screenshots for similarity
   - diff_screenshots     Generate visual diff between screenshots
+  - save_baseline        Save a named golden screenshot (app, element or region)
+  - check_baseline       Recapture and compare against a golden screenshot
+  - start_screen_recording  Record the screen at a fixed frame rate
+  - stop_screen_recording   Write the recording as GIF, APNG or PNG frames
+
+Inspection:
+  - inspect_context   Show egui focus, hover, open popups and layer order
+
+Waiting:
+  - wait_for_element  Wait for an element to appear/disappear
+  - wait_for_state    Wait for an element state change
+  - wait_for_idle     Wait until animations and repaints have settled
+
+Time Control:
+  - freeze_time       Freeze time (animations, carets, tooltips stand still)
+  - step_frames       Run N frames with a fixed time step
+  - resume_time       Resume real time
+
+Viewports:
+  - list_viewports    List native windows (pass viewport_id to screenshot/input tools)
 
 For more information, visit: https://github.com/dijdzv/egui-mcp
 
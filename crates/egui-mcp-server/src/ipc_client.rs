@@ -7,12 +7,17 @@
 //! - Scroll events
 //!
 //! Note: UI tree access and element-based interactions are handled via AT-SPI.
+//! `GetUiTree` is only used as a fallback for apps without AT-SPI (e.g. headless).
 
+use crate::constants::{FRAME_SHAPES_PAGE_SIZE, PERF_TRACE_PAGE_SIZE};
 use egui_mcp_protocol::{
-    FrameStats, LogEntry, MouseButton, PerfReport, ProtocolError, Request, Response,
-    default_socket_path, read_response, write_request,
+    ContextInfo, CrashReport, FrameShapes, FrameStats, IdleState, InputRecording, LatencyReport,
+    LogEntry, MemoryStats, MouseButton, PerfReport, ProtocolError, RecordedFrame, Request,
+    Response, TraceEvent, UiTree, ViewportInfo, crash_file_path, default_socket_path,
+    read_response, write_request,
 };
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use tokio::net::UnixStream;
 use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
 use tokio::sync::Mutex;
@@ -23,6 +28,18 @@ struct CachedConnection {
     writer: OwnedWriteHalf,
 }
 
+/// Filters for [`IpcClient::get_logs`] (see `Request::GetLogs`)
+#[derive(Debug, Clone, Default)]
+pub struct LogFilter {
+    pub level: Option<String>,
+    pub limit: Option<usize>,
+    pub target: Option<String>,
+    pub target_regex: Option<String>,
+    pub message_regex: Option<String>,
+    pub since: Option<u64>,
+    pub history: bool,
+}
+
 /// IPC client for communicating with egui applications
 ///
 /// This client maintains a cached connection to reduce connection overhead.
@@ -30,6 +47,8 @@ struct CachedConnection {
 pub struct IpcClient {
     socket_path: PathBuf,
     connection: Mutex<Option<CachedConnection>>,
+    /// When the last connection was established (milliseconds since the Unix epoch, 0 = never)
+    connected_at_ms: AtomicU64,
 }
 
 impl IpcClient {
@@ -43,6 +62,7 @@ impl IpcClient {
         Self {
             socket_path,
             connection: Mutex::new(None),
+            connected_at_ms: AtomicU64::new(0),
         }
     }
 
@@ -55,6 +75,7 @@ impl IpcClient {
             let stream = UnixStream::connect(&self.socket_path).await?;
             let (reader, writer) = stream.into_split();
             *guard = Some(CachedConnection { reader, writer });
+            self.connected_at_ms.store(now_ms(), Ordering::Relaxed);
         }
         Ok(guard)
     }
@@ -72,7 +93,15 @@ impl IpcClient {
             Err(_) => {
                 // Connection failed, clear it and try once more with a fresh connection
                 *self.connection.lock().await = None;
-                self.try_send_request(request).await
+                let result = self.try_send_request(request).await;
+                // Report a panic instead of the bare I/O error if the app crashed
+                match (result, self.recent_crash()) {
+                    (Err(_), Some(crash)) => Err(ProtocolError::Io(std::io::Error::new(
+                        std::io::ErrorKind::ConnectionAborted,
+                        crash_summary(&crash),
+                    ))),
+                    (result, _) => result,
+                }
             }
         }
     }
@@ -108,8 +137,13 @@ impl IpcClient {
 
     /// Take a screenshot of the egui application
     /// Returns (base64_data, format)
-    pub async fn take_screenshot(&self) -> Result<(String, String), ProtocolError> {
-        let response = self.send_request(&Request::TakeScreenshot).await?;
+    pub async fn take_screenshot(
+        &self,
+        viewport_id: Option<u64>,
+    ) -> Result<(String, String), ProtocolError> {
+        let response = self
+            .send_request(&Request::TakeScreenshot { viewport_id })
+            .await?;
         match response {
             Response::Screenshot { data, format } => Ok((data, format)),
             Response::Error { message } => Err(ProtocolError::Io(std::io::Error::other(message))),
@@ -121,9 +155,150 @@ impl IpcClient {
     }
 
     /// Click at specific coordinates
-    pub async fn click_at(&self, x: f32, y: f32, button: MouseButton) -> Result<(), ProtocolError> {
+    pub async fn click_at(
+        &self,
+        x: f32,
+        y: f32,
+        button: MouseButton,
+        viewport_id: Option<u64>,
+    ) -> Result<(), ProtocolError> {
+        let response = self
+            .send_request(&Request::ClickAt {
+                x,
+                y,
+                button,
+                viewport_id,
+            })
+            .await?;
+        match response {
+            Response::Success => Ok(()),
+            Response::Error { message } => Err(ProtocolError::Io(std::io::Error::other(message))),
+            _ => Err(ProtocolError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Unexpected response",
+            ))),
+        }
+    }
+
+    /// Send a recorded input request (ClickAt, Drag, KeyboardInput, ...) as is
+    pub async fn send_input(&self, request: &Request) -> Result<(), ProtocolError> {
+        let response = self.send_request(request).await?;
+        match response {
+            Response::Success => Ok(()),
+            Response::Error { message } => Err(ProtocolError::Io(std::io::Error::other(message))),
+            _ => Err(ProtocolError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Unexpected response",
+            ))),
+        }
+    }
+
+    /// Get the UI tree built from the app's AccessKit output
+    pub async fn get_ui_tree(&self) -> Result<UiTree, ProtocolError> {
+        let response = self.send_request(&Request::GetUiTree).await?;
+        match response {
+            Response::UiTreeResponse { tree } => Ok(tree),
+            Response::Error { message } => Err(ProtocolError::Io(std::io::Error::other(message))),
+            _ => Err(ProtocolError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Unexpected response",
+            ))),
+        }
+    }
+
+    /// Get the shapes painted in the next frame of a viewport (None = root)
+    ///
+    /// The shapes are fetched page by page and concatenated. With `text_only`
+    /// the app only sends the text shapes.
+    pub async fn get_frame_shapes(
+        &self,
+        viewport_id: Option<u64>,
+        text_only: bool,
+    ) -> Result<FrameShapes, ProtocolError> {
+        let (mut frame, total_shapes) = self
+            .get_frame_shapes_page(viewport_id, 0, text_only)
+            .await?;
+        while frame.shapes.len() < total_shapes {
+            let (page, _) = self
+                .get_frame_shapes_page(viewport_id, frame.shapes.len(), text_only)
+                .await?;
+            if page.shapes.is_empty() {
+                break;
+            }
+            frame.shapes.extend(page.shapes);
+        }
+        Ok(frame)
+    }
+
+    /// Get a page of frame shapes and the number of shapes of the whole frame
+    ///
+    /// Offset 0 captures the next frame, higher offsets page through it.
+    async fn get_frame_shapes_page(
+        &self,
+        viewport_id: Option<u64>,
+        offset: usize,
+        text_only: bool,
+    ) -> Result<(FrameShapes, usize), ProtocolError> {
+        let response = self
+            .send_request(&Request::GetFrameShapes {
+                viewport_id,
+                offset,
+                limit: FRAME_SHAPES_PAGE_SIZE,
+                text_only,
+            })
+            .await?;
+        match response {
+            Response::FrameShapesResponse {
+                shapes,
+                total_shapes,
+            } => Ok((*shapes, total_shapes)),
+            Response::Error { message } => Err(ProtocolError::Io(std::io::Error::other(message))),
+            _ => Err(ProtocolError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Unexpected response",
+            ))),
+        }
+    }
+
+    /// Start recording real user input in the egui application
+    pub async fn start_input_recording(&self) -> Result<(), ProtocolError> {
+        let response = self.send_request(&Request::StartInputRecording).await?;
+        match response {
+            Response::Success => Ok(()),
+            Response::Error { message } => Err(ProtocolError::Io(std::io::Error::other(message))),
+            _ => Err(ProtocolError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Unexpected response",
+            ))),
+        }
+    }
+
+    /// Stop recording user input and get the recording
+    pub async fn stop_input_recording(&self) -> Result<InputRecording, ProtocolError> {
+        let response = self.send_request(&Request::StopInputRecording).await?;
+        match response {
+            Response::InputRecordingResponse { recording } => Ok(recording),
+            Response::Error { message } => Err(ProtocolError::Io(std::io::Error::other(message))),
+            _ => Err(ProtocolError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Unexpected response",
+            ))),
+        }
+    }
+
+    /// Start capturing screenshots at `fps` for at most `max_duration_ms`
+    pub async fn start_screen_recording(
+        &self,
+        fps: f32,
+        max_duration_ms: u64,
+        viewport_id: Option<u64>,
+    ) -> Result<(), ProtocolError> {
         let response = self
-            .send_request(&Request::ClickAt { x, y, button })
+            .send_request(&Request::StartScreenRecording {
+                fps,
+                max_duration_ms,
+                viewport_id,
+            })
             .await?;
         match response {
             Response::Success => Ok(()),
@@ -135,11 +310,45 @@ impl IpcClient {
         }
     }
 
+    /// Stop the screen recording and get the frame count and duration in milliseconds
+    pub async fn stop_screen_recording(&self) -> Result<(usize, u64), ProtocolError> {
+        let response = self.send_request(&Request::StopScreenRecording).await?;
+        match response {
+            Response::ScreenRecordingStopped {
+                frames,
+                duration_ms,
+            } => Ok((frames, duration_ms)),
+            Response::Error { message } => Err(ProtocolError::Io(std::io::Error::other(message))),
+            _ => Err(ProtocolError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Unexpected response",
+            ))),
+        }
+    }
+
+    /// Take the oldest frame of the stopped screen recording (None once all were taken)
+    pub async fn take_recorded_frame(&self) -> Result<Option<RecordedFrame>, ProtocolError> {
+        let response = self.send_request(&Request::TakeRecordedFrame).await?;
+        match response {
+            Response::RecordedFrameResponse { frame } => Ok(frame),
+            Response::Error { message } => Err(ProtocolError::Io(std::io::Error::other(message))),
+            _ => Err(ProtocolError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Unexpected response",
+            ))),
+        }
+    }
+
     /// Send keyboard input
-    pub async fn keyboard_input(&self, key: &str) -> Result<(), ProtocolError> {
+    pub async fn keyboard_input(
+        &self,
+        key: &str,
+        viewport_id: Option<u64>,
+    ) -> Result<(), ProtocolError> {
         let response = self
             .send_request(&Request::KeyboardInput {
                 key: key.to_string(),
+                viewport_id,
             })
             .await?;
         match response {
@@ -159,6 +368,7 @@ impl IpcClient {
         y: f32,
         delta_x: f32,
         delta_y: f32,
+        viewport_id: Option<u64>,
     ) -> Result<(), ProtocolError> {
         let response = self
             .send_request(&Request::Scroll {
@@ -166,6 +376,7 @@ impl IpcClient {
                 y,
                 delta_x,
                 delta_y,
+                viewport_id,
             })
             .await?;
         match response {
@@ -179,8 +390,15 @@ impl IpcClient {
     }
 
     /// Move mouse to specific coordinates
-    pub async fn move_mouse(&self, x: f32, y: f32) -> Result<(), ProtocolError> {
-        let response = self.send_request(&Request::MoveMouse { x, y }).await?;
+    pub async fn move_mouse(
+        &self,
+        x: f32,
+        y: f32,
+        viewport_id: Option<u64>,
+    ) -> Result<(), ProtocolError> {
+        let response = self
+            .send_request(&Request::MoveMouse { x, y, viewport_id })
+            .await?;
         match response {
             Response::Success => Ok(()),
             Response::Error { message } => Err(ProtocolError::Io(std::io::Error::other(message))),
@@ -199,6 +417,7 @@ impl IpcClient {
         end_x: f32,
         end_y: f32,
         button: MouseButton,
+        viewport_id: Option<u64>,
     ) -> Result<(), ProtocolError> {
         let response = self
             .send_request(&Request::Drag {
@@ -207,6 +426,7 @@ impl IpcClient {
                 end_x,
                 end_y,
                 button,
+                viewport_id,
             })
             .await?;
         match response {
@@ -225,9 +445,15 @@ impl IpcClient {
         x: f32,
         y: f32,
         button: MouseButton,
+        viewport_id: Option<u64>,
     ) -> Result<(), ProtocolError> {
         let response = self
-            .send_request(&Request::DoubleClick { x, y, button })
+            .send_request(&Request::DoubleClick {
+                x,
+                y,
+                button,
+                viewport_id,
+            })
             .await?;
         match response {
             Response::Success => Ok(()),
@@ -248,6 +474,7 @@ impl IpcClient {
         y: f32,
         width: f32,
         height: f32,
+        viewport_id: Option<u64>,
     ) -> Result<(String, String), ProtocolError> {
         let response = self
             .send_request(&Request::TakeScreenshotRegion {
@@ -255,6 +482,7 @@ impl IpcClient {
                 y,
                 width,
                 height,
+                viewport_id,
             })
             .await?;
         match response {
@@ -267,6 +495,19 @@ impl IpcClient {
         }
     }
 
+    /// List the viewports (native windows) of the egui application
+    pub async fn list_viewports(&self) -> Result<Vec<ViewportInfo>, ProtocolError> {
+        let response = self.send_request(&Request::ListViewports).await?;
+        match response {
+            Response::Viewports { viewports } => Ok(viewports),
+            Response::Error { message } => Err(ProtocolError::Io(std::io::Error::other(message))),
+            _ => Err(ProtocolError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Unexpected response",
+            ))),
+        }
+    }
+
     /// Highlight an element with a colored border
     pub async fn highlight_element(
         &self,
@@ -315,17 +556,51 @@ impl IpcClient {
         self.socket_path.exists()
     }
 
+    /// Read the crash report written by the app's panic hook, if any
+    pub fn last_crash(&self) -> Option<CrashReport> {
+        let data = std::fs::read(crash_file_path(&self.socket_path)).ok()?;
+        serde_json::from_slice(&data).ok()
+    }
+
+    /// The crash report if the app panicked after the last connection was made
+    pub fn recent_crash(&self) -> Option<CrashReport> {
+        let connected_at = self.connected_at_ms.load(Ordering::Relaxed);
+        self.last_crash()
+            .filter(|crash| connected_at > 0 && crash.timestamp_ms >= connected_at)
+    }
+
     /// Get log entries from the egui application
-    pub async fn get_logs(
-        &self,
-        level: Option<String>,
-        limit: Option<usize>,
-    ) -> Result<Vec<LogEntry>, ProtocolError> {
+    ///
+    /// Returns the entries and the cursor to pass as `since` next time.
+    pub async fn get_logs(&self, filter: LogFilter) -> Result<(Vec<LogEntry>, u64), ProtocolError> {
         let response = self
-            .send_request(&Request::GetLogs { level, limit })
+            .send_request(&Request::GetLogs {
+                level: filter.level,
+                limit: filter.limit,
+                target: filter.target,
+                target_regex: filter.target_regex,
+                message_regex: filter.message_regex,
+                since: filter.since,
+                history: filter.history,
+            })
             .await?;
         match response {
-            Response::Logs { entries } => Ok(entries),
+            Response::Logs { entries, cursor } => Ok((entries, cursor)),
+            Response::Error { message } => Err(ProtocolError::Io(std::io::Error::other(message))),
+            _ => Err(ProtocolError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Unexpected response",
+            ))),
+        }
+    }
+
+    /// Change which events the egui application's log layer captures
+    pub async fn set_log_filter(&self, directives: String) -> Result<(), ProtocolError> {
+        let response = self
+            .send_request(&Request::SetLogFilter { directives })
+            .await?;
+        match response {
+            Response::Success => Ok(()),
             Response::Error { message } => Err(ProtocolError::Io(std::io::Error::other(message))),
             _ => Err(ProtocolError::Io(std::io::Error::new(
                 std::io::ErrorKind::InvalidData,
@@ -361,9 +636,18 @@ impl IpcClient {
     }
 
     /// Start recording performance data
-    pub async fn start_perf_recording(&self, duration_ms: u64) -> Result<(), ProtocolError> {
+    pub async fn start_perf_recording(
+        &self,
+        duration_ms: u64,
+        profile: bool,
+        budgets_ms: Vec<f32>,
+    ) -> Result<(), ProtocolError> {
         let response = self
-            .send_request(&Request::StartPerfRecording { duration_ms })
+            .send_request(&Request::StartPerfRecording {
+                duration_ms,
+                profile,
+                budgets_ms,
+            })
             .await?;
         match response {
             Response::Success => Ok(()),
@@ -387,6 +671,148 @@ impl IpcClient {
             ))),
         }
     }
+
+    /// Get the span timings of the current or last profiling recording
+    ///
+    /// The events are fetched page by page and concatenated.
+    pub async fn export_perf_trace(&self) -> Result<(Vec<TraceEvent>, usize), ProtocolError> {
+        let mut events = Vec::new();
+        loop {
+            let request = Request::ExportPerfTrace {
+                offset: events.len(),
+                limit: PERF_TRACE_PAGE_SIZE,
+            };
+            match self.send_request(&request).await? {
+                Response::PerfTrace {
+                    events: page,
+                    dropped_events,
+                    total_events,
+                } => {
+                    let done = page.is_empty() || events.len() + page.len() >= total_events;
+                    events.extend(page);
+                    if done {
+                        return Ok((events, dropped_events));
+                    }
+                }
+                Response::Error { message } => {
+                    return Err(ProtocolError::Io(std::io::Error::other(message)));
+                }
+                _ => {
+                    return Err(ProtocolError::Io(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        "Unexpected response",
+                    )));
+                }
+            }
+        }
+    }
+
+    /// Freeze the time reported to egui; returns the frozen virtual time if known
+    pub async fn freeze_time(&self) -> Result<Option<f64>, ProtocolError> {
+        let response = self.send_request(&Request::FreezeTime).await?;
+        match response {
+            Response::TimeState { time, .. } => Ok(time),
+            Response::Error { message } => Err(ProtocolError::Io(std::io::Error::other(message))),
+            _ => Err(ProtocolError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Unexpected response",
+            ))),
+        }
+    }
+
+    /// Run frames with a fixed time step; returns the virtual time afterwards
+    pub async fn step_frames(&self, frames: u32, dt_ms: f32) -> Result<f64, ProtocolError> {
+        let response = self
+            .send_request(&Request::StepFrames { frames, dt_ms })
+            .await?;
+        match response {
+            Response::TimeState { time, .. } => Ok(time.unwrap_or(0.0)),
+            Response::Error { message } => Err(ProtocolError::Io(std::io::Error::other(message))),
+            _ => Err(ProtocolError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Unexpected response",
+            ))),
+        }
+    }
+
+    /// Resume real time
+    pub async fn resume_time(&self) -> Result<(), ProtocolError> {
+        let response = self.send_request(&Request::ResumeTime).await?;
+        match response {
+            Response::TimeState { .. } => Ok(()),
+            Response::Error { message } => Err(ProtocolError::Io(std::io::Error::other(message))),
+            _ => Err(ProtocolError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Unexpected response",
+            ))),
+        }
+    }
+
+    /// Get the repaint activity of the egui application
+    pub async fn get_idle_state(&self) -> Result<IdleState, ProtocolError> {
+        let response = self.send_request(&Request::GetIdleState).await?;
+        match response {
+            Response::IdleStateResponse { state } => Ok(state),
+            Response::Error { message } => Err(ProtocolError::Io(std::io::Error::other(message))),
+            _ => Err(ProtocolError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Unexpected response",
+            ))),
+        }
+    }
+
+    /// Get heap and texture memory statistics
+    pub async fn get_memory_stats(&self) -> Result<MemoryStats, ProtocolError> {
+        let response = self.send_request(&Request::GetMemoryStats).await?;
+        match response {
+            Response::MemoryStatsResponse { stats } => Ok(stats),
+            Response::Error { message } => Err(ProtocolError::Io(std::io::Error::other(message))),
+            _ => Err(ProtocolError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Unexpected response",
+            ))),
+        }
+    }
+
+    /// Inject an input and measure how long it takes to change the output
+    pub async fn measure_latency(
+        &self,
+        input: Request,
+        timeout_ms: u64,
+    ) -> Result<LatencyReport, ProtocolError> {
+        let response = self
+            .send_request(&Request::MeasureLatency {
+                input: Box::new(input),
+                timeout_ms,
+            })
+            .await?;
+        match response {
+            Response::Latency { report } => Ok(report),
+            Response::Error { message } => Err(ProtocolError::Io(std::io::Error::other(message))),
+            _ => Err(ProtocolError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Unexpected response",
+            ))),
+        }
+    }
+
+    /// Inspect egui `Context` internals (focus, hover, popups, layers)
+    pub async fn inspect_context(
+        &self,
+        viewport_id: Option<u64>,
+    ) -> Result<ContextInfo, ProtocolError> {
+        let response = self
+            .send_request(&Request::InspectContext { viewport_id })
+            .await?;
+        match response {
+            Response::ContextInfoResponse { info } => Ok(*info),
+            Response::Error { message } => Err(ProtocolError::Io(std::io::Error::other(message))),
+            _ => Err(ProtocolError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Unexpected response",
+            ))),
+        }
+    }
 }
 
 impl Default for IpcClient {
@@ -394,3 +820,22 @@ impl Default for IpcClient {
         Self::new()
     }
 }
+
+/// Current time in milliseconds since the Unix epoch
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// One-line description of a crash
+pub fn crash_summary(crash: &CrashReport) -> String {
+    match &crash.location {
+        Some(location) => format!(
+            "egui application crashed: {} at {}",
+            crash.message, location
+        ),
+        None => format!("egui application crashed: {}", crash.message),
+    }
+}
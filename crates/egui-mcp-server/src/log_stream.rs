@@ -0,0 +1,180 @@
+//! Live forwarding of app logs as MCP logging notifications
+//!
+//! After the client initialized the session, a background task polls the
+//! app's log buffer with a `since` cursor and sends every new entry at or
+//! above the level set through `logging/setLevel` as `notifications/message`.
+
+use crate::constants::LOG_STREAM_POLL_INTERVAL_MS;
+use crate::ipc_client::{IpcClient, LogFilter};
+use egui_mcp_protocol::LogEntry;
+use rmcp::model::{LoggingLevel, LoggingMessageNotificationParam};
+use rmcp::{Peer, RoleServer};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// Level forwarded until the client calls `logging/setLevel`
+const DEFAULT_LEVEL: LoggingLevel = LoggingLevel::Warning;
+
+/// Forwards app log entries to the MCP client
+#[derive(Clone)]
+pub struct LogStream {
+    /// Minimum level to forward
+    level: Arc<RwLock<LoggingLevel>>,
+    /// Whether the forwarding task was started
+    started: Arc<AtomicBool>,
+}
+
+impl LogStream {
+    pub fn new() -> Self {
+        Self {
+            level: Arc::new(RwLock::new(DEFAULT_LEVEL)),
+            started: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Set the minimum level to forward (from `logging/setLevel`)
+    pub fn set_level(&self, level: LoggingLevel) {
+        *self.level.write().unwrap() = level;
+    }
+
+    fn level(&self) -> LoggingLevel {
+        *self.level.read().unwrap()
+    }
+
+    /// Start forwarding to the given peer (only the first call has an effect)
+    pub fn start(&self, peer: Peer<RoleServer>, ipc_client: Arc<IpcClient>) {
+        if self.started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let stream = self.clone();
+        tokio::spawn(async move {
+            stream.run(peer, ipc_client).await;
+        });
+    }
+
+    async fn run(&self, peer: Peer<RoleServer>, ipc_client: Arc<IpcClient>) {
+        // None = skip to the end of the buffer, so old entries are not replayed
+        let mut cursor: Option<u64> = None;
+        let mut interval = tokio::time::interval(std::time::Duration::from_millis(
+            LOG_STREAM_POLL_INTERVAL_MS,
+        ));
+
+        loop {
+            interval.tick().await;
+            if !ipc_client.is_socket_available() {
+                cursor = None;
+                continue;
+            }
+
+            let level = self.level();
+            let filter = match cursor {
+                Some(since) => LogFilter {
+                    level: Some(app_min_level(level).to_string()),
+                    since: Some(since),
+                    ..Default::default()
+                },
+                None => LogFilter {
+                    limit: Some(0),
+                    ..Default::default()
+                },
+            };
+            let result = ipc_client.get_logs(filter).await;
+            let (entries, next_cursor) = match result {
+                Ok(page) => page,
+                Err(e) => {
+                    tracing::debug!("Log stream: failed to get logs: {}", e);
+                    cursor = None;
+                    continue;
+                }
+            };
+            // Sequence numbers start over when the app restarts between two
+            // polls; read the new run from its first entry on the next tick
+            if cursor.is_some_and(|since| next_cursor < since) {
+                cursor = Some(0);
+                continue;
+            }
+            cursor = Some(next_cursor);
+
+            for entry in entries {
+                if let Err(e) = peer.notify_logging_message(notification(&entry)).await {
+                    tracing::info!("Log stream stopped: {}", e);
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Build the notification for an app log entry
+fn notification(entry: &LogEntry) -> LoggingMessageNotificationParam {
+    LoggingMessageNotificationParam {
+        level: mcp_level(&entry.level),
+        logger: Some(entry.target.clone()),
+        data: serde_json::to_value(entry).unwrap_or_default(),
+    }
+}
+
+/// Map a tracing level to the MCP logging level
+fn mcp_level(level: &str) -> LoggingLevel {
+    match level.to_uppercase().as_str() {
+        "ERROR" => LoggingLevel::Error,
+        "WARN" => LoggingLevel::Warning,
+        "INFO" => LoggingLevel::Info,
+        _ => LoggingLevel::Debug,
+    }
+}
+
+/// Map an MCP logging level to the minimum tracing level to forward
+fn app_min_level(level: LoggingLevel) -> &'static str {
+    match level {
+        LoggingLevel::Debug => "TRACE",
+        LoggingLevel::Info => "INFO",
+        LoggingLevel::Notice | LoggingLevel::Warning => "WARN",
+        LoggingLevel::Error
+        | LoggingLevel::Critical
+        | LoggingLevel::Alert
+        | LoggingLevel::Emergency => "ERROR",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mcp_level() {
+        assert_eq!(mcp_level("ERROR"), LoggingLevel::Error);
+        assert_eq!(mcp_level("WARN"), LoggingLevel::Warning);
+        assert_eq!(mcp_level("INFO"), LoggingLevel::Info);
+        assert_eq!(mcp_level("DEBUG"), LoggingLevel::Debug);
+        assert_eq!(mcp_level("TRACE"), LoggingLevel::Debug);
+    }
+
+    #[test]
+    fn test_app_min_level_round_trip() {
+        for level in ["TRACE", "INFO", "WARN", "ERROR"] {
+            let mcp = mcp_level(level);
+            assert_eq!(app_min_level(mcp), level);
+        }
+        assert_eq!(app_min_level(LoggingLevel::Notice), "WARN");
+        assert_eq!(app_min_level(LoggingLevel::Emergency), "ERROR");
+    }
+
+    #[test]
+    fn test_notification_keeps_entry() {
+        let entry = LogEntry {
+            level: "ERROR".to_string(),
+            target: "my_app::io".to_string(),
+            message: "save failed".to_string(),
+            timestamp_ms: 1,
+            seq: 3,
+            fields: Default::default(),
+            spans: Vec::new(),
+        };
+        let param = notification(&entry);
+        assert_eq!(param.level, LoggingLevel::Error);
+        assert_eq!(param.logger.as_deref(), Some("my_app::io"));
+        assert_eq!(param.data["message"], "save failed");
+        assert_eq!(param.data["seq"], 3);
+    }
+}
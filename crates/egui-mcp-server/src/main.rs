@@ -8,6 +8,7 @@
 mod constants;
 mod guide;
 mod ipc_client;
+mod log_stream;
 mod requests;
 mod tools;
 mod utils;
@@ -20,15 +21,21 @@ mod errors;
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 use ipc_client::IpcClient;
+use log_stream::LogStream;
 use requests::*;
 use rmcp::{
-    ServerHandler, ServiceExt,
+    RoleServer, ServerHandler, ServiceExt,
     handler::server::{router::tool::ToolRouter, wrapper::Parameters},
-    model::{Content, Implementation, ProtocolVersion, ServerCapabilities, ServerInfo},
+    model::{
+        CallToolResult, Content, Implementation, ProtocolVersion, ServerCapabilities, ServerInfo,
+        SetLevelRequestParam,
+    },
+    service::{NotificationContext, RequestContext},
     tool, tool_handler, tool_router,
     transport::stdio,
 };
 use std::sync::Arc;
+use tools::recording::RecordingStore;
 use tools::snapshot::SnapshotStore;
 use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
 
@@ -56,7 +63,9 @@ struct EguiMcpServer {
     tool_router: ToolRouter<Self>,
     ipc_client: Arc<IpcClient>,
     snapshots: SnapshotStore,
+    recordings: RecordingStore,
     app_name: String,
+    log_stream: LogStream,
 }
 
 impl EguiMcpServer {
@@ -68,7 +77,9 @@ impl EguiMcpServer {
             tool_router,
             ipc_client,
             snapshots,
+            recordings: Arc::new(std::sync::RwLock::new(None)),
             app_name,
+            log_stream: LogStream::new(),
         }
     }
 }
@@ -89,13 +100,20 @@ impl EguiMcpServer {
         tools::basic::check_connection(&self.ipc_client).await
     }
 
+    #[tool(
+        description = "Get the panic message, location and backtrace of the last crash of the egui application. Requires the app to call McpClient::install_panic_hook()."
+    )]
+    async fn get_last_crash(&self) -> String {
+        tools::basic::get_last_crash(&self.ipc_client)
+    }
+
     // ========================================================================
-    // UI Tree tools (AT-SPI)
+    // UI Tree tools (AT-SPI, or IPC for headless apps)
     // ========================================================================
 
     #[tool(description = "Get the full UI tree from the egui application as JSON")]
     async fn get_ui_tree(&self) -> String {
-        tools::tree::get_ui_tree(&self.app_name).await
+        tools::tree::get_ui_tree(&self.app_name, &self.ipc_client).await
     }
 
     #[tool(description = "Find UI elements by their label text (substring match)")]
@@ -103,7 +121,7 @@ impl EguiMcpServer {
         &self,
         Parameters(FindByLabelRequest { pattern }): Parameters<FindByLabelRequest>,
     ) -> String {
-        tools::tree::find_by_label(&self.app_name, &pattern, false).await
+        tools::tree::find_by_label(&self.app_name, &self.ipc_client, &pattern, false).await
     }
 
     #[tool(description = "Find UI elements by their label text (exact match)")]
@@ -111,7 +129,7 @@ impl EguiMcpServer {
         &self,
         Parameters(FindByLabelExactRequest { pattern }): Parameters<FindByLabelExactRequest>,
     ) -> String {
-        tools::tree::find_by_label(&self.app_name, &pattern, true).await
+        tools::tree::find_by_label(&self.app_name, &self.ipc_client, &pattern, true).await
     }
 
     #[tool(
@@ -121,7 +139,7 @@ impl EguiMcpServer {
         &self,
         Parameters(FindByRoleRequest { role }): Parameters<FindByRoleRequest>,
     ) -> String {
-        tools::tree::find_by_role(&self.app_name, &role).await
+        tools::tree::find_by_role(&self.app_name, &self.ipc_client, &role).await
     }
 
     #[tool(
@@ -131,7 +149,7 @@ impl EguiMcpServer {
         &self,
         Parameters(GetElementRequest { id }): Parameters<GetElementRequest>,
     ) -> String {
-        tools::tree::get_element(&self.app_name, &id).await
+        tools::tree::get_element(&self.app_name, &self.ipc_client, &id).await
     }
 
     // ========================================================================
@@ -386,17 +404,29 @@ impl EguiMcpServer {
     #[tool(description = "Click at specific coordinates in the egui application window")]
     async fn click_at(
         &self,
-        Parameters(ClickAtRequest { x, y, button }): Parameters<ClickAtRequest>,
+        Parameters(ClickAtRequest {
+            x,
+            y,
+            button,
+            viewport_id,
+        }): Parameters<ClickAtRequest>,
     ) -> String {
-        tools::input::click_at(&self.ipc_client, x, y, button.as_deref()).await
+        tools::input::click_at(
+            &self.ipc_client,
+            x,
+            y,
+            button.as_deref(),
+            viewport_id.as_deref(),
+        )
+        .await
     }
 
     #[tool(description = "Send keyboard input to the egui application")]
     async fn keyboard_input(
         &self,
-        Parameters(KeyboardInputRequest { key }): Parameters<KeyboardInputRequest>,
+        Parameters(KeyboardInputRequest { key, viewport_id }): Parameters<KeyboardInputRequest>,
     ) -> String {
-        tools::input::keyboard_input(&self.ipc_client, &key).await
+        tools::input::keyboard_input(&self.ipc_client, &key, viewport_id.as_deref()).await
     }
 
     #[tool(description = "Scroll at specific coordinates in the egui application window")]
@@ -407,16 +437,28 @@ impl EguiMcpServer {
             y,
             delta_x,
             delta_y,
+            viewport_id,
         }): Parameters<ScrollRequest>,
     ) -> String {
-        tools::input::scroll(&self.ipc_client, x, y, delta_x, delta_y).await
+        tools::input::scroll(
+            &self.ipc_client,
+            x,
+            y,
+            delta_x,
+            delta_y,
+            viewport_id.as_deref(),
+        )
+        .await
     }
 
     #[tool(
         description = "Move mouse to specific coordinates in the egui application window (hover)"
     )]
-    async fn hover(&self, Parameters(HoverRequest { x, y }): Parameters<HoverRequest>) -> String {
-        tools::input::hover(&self.ipc_client, x, y).await
+    async fn hover(
+        &self,
+        Parameters(HoverRequest { x, y, viewport_id }): Parameters<HoverRequest>,
+    ) -> String {
+        tools::input::hover(&self.ipc_client, x, y, viewport_id.as_deref()).await
     }
 
     #[tool(description = "Drag from one point to another in the egui application window")]
@@ -428,6 +470,7 @@ impl EguiMcpServer {
             end_x,
             end_y,
             button,
+            viewport_id,
         }): Parameters<DragRequest>,
     ) -> String {
         tools::input::drag(
@@ -437,6 +480,7 @@ impl EguiMcpServer {
             end_x,
             end_y,
             button.as_deref(),
+            viewport_id.as_deref(),
         )
         .await
     }
@@ -444,9 +488,21 @@ impl EguiMcpServer {
     #[tool(description = "Double click at specific coordinates in the egui application window")]
     async fn double_click(
         &self,
-        Parameters(DoubleClickRequest { x, y, button }): Parameters<DoubleClickRequest>,
+        Parameters(DoubleClickRequest {
+            x,
+            y,
+            button,
+            viewport_id,
+        }): Parameters<DoubleClickRequest>,
     ) -> String {
-        tools::input::double_click(&self.ipc_client, x, y, button.as_deref()).await
+        tools::input::double_click(
+            &self.ipc_client,
+            x,
+            y,
+            button.as_deref(),
+            viewport_id.as_deref(),
+        )
+        .await
     }
 
     // ========================================================================
@@ -454,44 +510,115 @@ impl EguiMcpServer {
     // ========================================================================
 
     #[tool(
-        description = "Take a screenshot of the egui application. Returns base64-encoded PNG image data."
+        description = "Take a screenshot of the egui application. Returns the image size and format followed by the image. Use max_width/scale, format ('jpeg', 'webp') and quality to save context."
     )]
     async fn take_screenshot(
         &self,
-        Parameters(TakeScreenshotRequest { save_to_file }): Parameters<TakeScreenshotRequest>,
-    ) -> Content {
-        match tools::screenshot::take_screenshot(&self.ipc_client, save_to_file.unwrap_or(false))
+        Parameters(TakeScreenshotRequest {
+            save_to_file,
+            viewport_id,
+            output,
+        }): Parameters<TakeScreenshotRequest>,
+    ) -> Result<CallToolResult, rmcp::ErrorData> {
+        Ok(CallToolResult::success(
+            tools::screenshot::take_screenshot(
+                &self.ipc_client,
+                save_to_file.unwrap_or(false),
+                viewport_id.as_deref(),
+                &output,
+            )
             .await
-        {
-            Ok(content) => content,
-            Err(error_json) => Content::text(error_json),
-        }
+            .unwrap_or_else(|error_json| vec![Content::text(error_json)]),
+        ))
     }
 
     #[tool(
-        description = "Take a screenshot of a specific UI element by ID. Captures the full screen and crops to element bounds."
+        description = "Take a screenshot with a numbered box on every visible interactive element (buttons, inputs, checkboxes, sliders, links, ...). Returns a legend mapping each number to element ID, role and label, followed by the image, so what you see can be acted on by ID."
     )]
-    async fn screenshot_element(
+    async fn annotated_screenshot(
         &self,
-        Parameters(ScreenshotElementRequest { id, save_to_file }): Parameters<
-            ScreenshotElementRequest,
-        >,
-    ) -> Content {
-        match tools::screenshot::screenshot_element(
-            &self.app_name,
+        Parameters(AnnotatedScreenshotRequest {
+            save_to_file,
+            viewport_id,
+        }): Parameters<AnnotatedScreenshotRequest>,
+    ) -> Result<CallToolResult, rmcp::ErrorData> {
+        Ok(CallToolResult::success(
+            tools::annotate::annotated_screenshot(
+                &self.app_name,
+                &self.ipc_client,
+                save_to_file.unwrap_or(false),
+                viewport_id.as_deref(),
+            )
+            .await,
+        ))
+    }
+
+    #[tool(
+        description = "Take a vector screenshot of the next frame: the shapes egui painted (rects, circles, paths, positioned text runs) as SVG or a JSON shape list. Independent of fonts, GPU and scaling, so it suits exact diffs. Requires McpPlugin in the app."
+    )]
+    async fn take_vector_screenshot(
+        &self,
+        Parameters(TakeVectorScreenshotRequest {
+            format,
+            save_to_file,
+            viewport_id,
+        }): Parameters<TakeVectorScreenshotRequest>,
+    ) -> String {
+        tools::vector::take_vector_screenshot(
             &self.ipc_client,
-            &id,
+            format.as_deref(),
             save_to_file.unwrap_or(false),
+            viewport_id.as_deref(),
+        )
+        .await
+    }
+
+    #[tool(
+        description = "Get all text painted in the next frame with rect, center, color, font size and layer. Finds custom-painted text (canvas labels, plot annotations) that has no accessibility node; click it with click_at on the center. Requires McpPlugin in the app."
+    )]
+    async fn get_visible_text(
+        &self,
+        Parameters(GetVisibleTextRequest {
+            contains,
+            viewport_id,
+        }): Parameters<GetVisibleTextRequest>,
+    ) -> String {
+        tools::vector::get_visible_text(
+            &self.ipc_client,
+            contains.as_deref(),
+            viewport_id.as_deref(),
         )
         .await
-        {
-            Ok(content) => content,
-            Err(error_json) => Content::text(error_json),
-        }
     }
 
     #[tool(
-        description = "Take a screenshot of a specific region. Captures the full screen and crops to the specified coordinates."
+        description = "Take a screenshot of a specific UI element by ID. Captures the full screen and crops to element bounds. Supports max_width/scale, format and quality like take_screenshot."
+    )]
+    async fn screenshot_element(
+        &self,
+        Parameters(ScreenshotElementRequest {
+            id,
+            save_to_file,
+            viewport_id,
+            output,
+        }): Parameters<ScreenshotElementRequest>,
+    ) -> Result<CallToolResult, rmcp::ErrorData> {
+        Ok(CallToolResult::success(
+            tools::screenshot::screenshot_element(
+                &self.app_name,
+                &self.ipc_client,
+                &id,
+                save_to_file.unwrap_or(false),
+                viewport_id.as_deref(),
+                &output,
+            )
+            .await
+            .unwrap_or_else(|error_json| vec![Content::text(error_json)]),
+        ))
+    }
+
+    #[tool(
+        description = "Take a screenshot of a specific region. Captures the full screen and crops to the specified coordinates. Supports max_width/scale, format and quality like take_screenshot."
     )]
     async fn screenshot_region(
         &self,
@@ -501,25 +628,28 @@ impl EguiMcpServer {
             width,
             height,
             save_to_file,
+            viewport_id,
+            output,
         }): Parameters<ScreenshotRegionRequest>,
-    ) -> Content {
-        match tools::screenshot::screenshot_region(
-            &self.ipc_client,
-            x,
-            y,
-            width,
-            height,
-            save_to_file.unwrap_or(false),
-        )
-        .await
-        {
-            Ok(content) => content,
-            Err(error_json) => Content::text(error_json),
-        }
+    ) -> Result<CallToolResult, rmcp::ErrorData> {
+        Ok(CallToolResult::success(
+            tools::screenshot::screenshot_region(
+                &self.ipc_client,
+                x,
+                y,
+                width,
+                height,
+                save_to_file.unwrap_or(false),
+                viewport_id.as_deref(),
+                &output,
+            )
+            .await
+            .unwrap_or_else(|error_json| vec![Content::text(error_json)]),
+        ))
     }
 
     #[tool(
-        description = "Compare two screenshots and return similarity score. Returns a score between 0.0 (completely different) and 1.0 (identical)."
+        description = "Compare two screenshots and return similarity score. Returns a score between 0.0 (completely different) and 1.0 (identical). Optional masks exclude areas (rectangles or element bounds), and regions are scored against their own thresholds."
     )]
     async fn compare_screenshots(
         &self,
@@ -529,19 +659,26 @@ impl EguiMcpServer {
             path_a,
             path_b,
             algorithm,
+            masks,
+            regions,
         }): Parameters<CompareScreenshotsRequest>,
     ) -> String {
         tools::screenshot::compare_screenshots(
+            &self.app_name,
+            &self.ipc_client,
             base64_a.as_deref(),
             base64_b.as_deref(),
             path_a.as_deref(),
             path_b.as_deref(),
             algorithm.as_deref(),
+            &masks.unwrap_or_default(),
+            &regions.unwrap_or_default(),
         )
+        .await
     }
 
     #[tool(
-        description = "Generate a visual diff image highlighting differences between two screenshots. Returns the diff image as base64-encoded PNG."
+        description = "Generate a visual diff image highlighting differences between two screenshots. Returns the diff image as base64-encoded PNG. Masked areas are excluded and drawn in blue."
     )]
     async fn diff_screenshots(
         &self,
@@ -551,20 +688,110 @@ impl EguiMcpServer {
             path_a,
             path_b,
             save_to_file,
+            masks,
         }): Parameters<DiffScreenshotsRequest>,
     ) -> Content {
         match tools::screenshot::diff_screenshots(
+            &self.app_name,
+            &self.ipc_client,
             base64_a.as_deref(),
             base64_b.as_deref(),
             path_a.as_deref(),
             path_b.as_deref(),
             save_to_file.unwrap_or(false),
-        ) {
+            &masks.unwrap_or_default(),
+        )
+        .await
+        {
             Ok(content) => content,
             Err(error_json) => Content::text(error_json),
         }
     }
 
+    #[tool(
+        description = "Save a golden baseline screenshot under a name: the whole app, an element (element_id) or a region (x, y, width, height). Stored as PNG in EGUI_MCP_BASELINE_DIR (default: ./egui-mcp-baselines)."
+    )]
+    async fn save_baseline(
+        &self,
+        Parameters(SaveBaselineRequest {
+            name,
+            element_id,
+            x,
+            y,
+            width,
+            height,
+            viewport_id,
+        }): Parameters<SaveBaselineRequest>,
+    ) -> String {
+        let region = match tools::baseline::parse_region(x, y, width, height) {
+            Ok(region) => region,
+            Err(e) => return e,
+        };
+        tools::baseline::save_baseline(
+            &self.app_name,
+            &self.ipc_client,
+            &name,
+            element_id.as_deref(),
+            region,
+            viewport_id.as_deref(),
+        )
+        .await
+    }
+
+    #[tool(
+        description = "Recapture a saved baseline (same element or region) and compare it. Returns a pass/fail verdict with the similarity score; on failure also the diff image, with the actual capture and diff saved next to the baseline. Set update=true to accept the new capture as the baseline."
+    )]
+    async fn check_baseline(
+        &self,
+        Parameters(CheckBaselineRequest {
+            name,
+            threshold,
+            algorithm,
+            update,
+            masks,
+            regions,
+        }): Parameters<CheckBaselineRequest>,
+    ) -> Result<CallToolResult, rmcp::ErrorData> {
+        Ok(CallToolResult::success(
+            tools::baseline::check_baseline(
+                &self.app_name,
+                &self.ipc_client,
+                &name,
+                threshold,
+                algorithm.as_deref(),
+                update.unwrap_or(false),
+                &masks.unwrap_or_default(),
+                &regions.unwrap_or_default(),
+            )
+            .await,
+        ))
+    }
+
+    // ========================================================================
+    // Viewport tools (IPC)
+    // ========================================================================
+
+    #[tool(
+        description = "List the viewports (native windows) of the egui application with their IDs, titles and geometry. Pass a viewport ID to screenshot and input tools to target a secondary window."
+    )]
+    async fn list_viewports(&self) -> String {
+        tools::viewport::list_viewports(&self.ipc_client).await
+    }
+
+    // ========================================================================
+    // Inspection tools (IPC)
+    // ========================================================================
+
+    #[tool(
+        description = "Inspect egui Context internals: which egui Id has keyboard focus, what the pointer hovers, which widget is dragged, whether a popup/menu is open, pointer state and the layer stack (back-to-front). Use to debug why a click landed in the wrong place. Note: Requires the egui app to call handle_inspect_sync()."
+    )]
+    async fn inspect_context(
+        &self,
+        Parameters(InspectContextRequest { viewport_id }): Parameters<InspectContextRequest>,
+    ) -> String {
+        tools::inspect::inspect_context(&self.ipc_client, viewport_id.as_deref()).await
+    }
+
     // ========================================================================
     // Highlight tools (IPC + AT-SPI)
     // ========================================================================
@@ -624,10 +851,20 @@ impl EguiMcpServer {
     // ========================================================================
 
     #[tool(
-        description = "Get recent log entries from the egui application. Note: Requires the egui app to be configured with McpLogLayer."
+        description = "Get log entries from the egui application, with structured fields and span context. Filter by minimum level, target prefix, target regex and message regex. Pass the returned 'cursor' as 'since' to get only newer entries. Note: Requires the egui app to be configured with McpLogLayer."
     )]
     async fn get_logs(&self, Parameters(req): Parameters<GetLogsRequest>) -> String {
-        tools::logging::get_logs(&self.ipc_client, req.level.as_deref(), req.limit).await
+        tools::logging::get_logs(&self.ipc_client, req).await
+    }
+
+    #[tool(
+        description = "Change which log events the egui application captures, using EnvFilter directives (e.g. 'warn,my_app::net=debug' to get debug logs of one module). An empty string captures all levels again. Requires the app to pass McpLogLayer::filter() to McpClient::with_log_filter_sync()."
+    )]
+    async fn set_log_filter(
+        &self,
+        Parameters(SetLogFilterRequest { directives }): Parameters<SetLogFilterRequest>,
+    ) -> String {
+        tools::logging::set_log_filter(&self.ipc_client, directives).await
     }
 
     #[tool(description = "Clear the log buffer in the egui application")]
@@ -635,34 +872,183 @@ impl EguiMcpServer {
         tools::logging::clear_logs(&self.ipc_client).await
     }
 
+    // ========================================================================
+    // Input recording tools (IPC)
+    // ========================================================================
+
+    #[tool(
+        description = "Start recording real user input (clicks, drags, scrolls, keys) in the egui application. Requires the app to feed input through McpClient::take_viewport_inputs_sync(); with McpPlugin installed, each step also records the widget under the pointer."
+    )]
+    async fn start_input_recording(&self) -> String {
+        tools::recording::start_input_recording(&self.ipc_client).await
+    }
+
+    #[tool(
+        description = "Stop recording user input and return the recording as a script of timed input requests. Optionally save it to a JSON file for replay_recording."
+    )]
+    async fn stop_input_recording(
+        &self,
+        Parameters(StopInputRecordingRequest { save_path }): Parameters<StopInputRecordingRequest>,
+    ) -> String {
+        tools::recording::stop_input_recording(
+            &self.ipc_client,
+            &self.recordings,
+            save_path.as_deref(),
+        )
+        .await
+    }
+
+    #[tool(
+        description = "Replay a recording with its original timing by injecting each step as input. Uses 'script' (JSON), 'path' (JSON file) or, if neither is given, the last recording stopped in this session."
+    )]
+    async fn replay_recording(
+        &self,
+        Parameters(ReplayRecordingRequest {
+            path,
+            script,
+            speed,
+        }): Parameters<ReplayRecordingRequest>,
+    ) -> String {
+        tools::recording::replay_recording(
+            &self.ipc_client,
+            &self.recordings,
+            path.as_deref(),
+            script.as_deref(),
+            speed,
+        )
+        .await
+    }
+
+    #[tool(
+        description = "Start recording the screen: the app captures screenshots at the given fps (default 10) until stop_screen_recording or max_duration_ms (default 10000). Use it for bugs that only show up as motion, like flicker, layout jumps or animation glitches."
+    )]
+    async fn start_screen_recording(
+        &self,
+        Parameters(StartScreenRecordingRequest {
+            fps,
+            max_duration_ms,
+            viewport_id,
+        }): Parameters<StartScreenRecordingRequest>,
+    ) -> String {
+        tools::screen_recording::start_screen_recording(
+            &self.ipc_client,
+            fps,
+            max_duration_ms,
+            viewport_id.as_deref(),
+        )
+        .await
+    }
+
+    #[tool(
+        description = "Stop the screen recording and write it as an animated GIF (default) or APNG, or as a directory of numbered PNGs with their timestamps. Returns the path, frame count, duration and size."
+    )]
+    async fn stop_screen_recording(
+        &self,
+        Parameters(StopScreenRecordingRequest {
+            format,
+            output_path,
+            max_width,
+            scale,
+        }): Parameters<StopScreenRecordingRequest>,
+    ) -> String {
+        tools::screen_recording::stop_screen_recording(
+            &self.ipc_client,
+            format.as_deref(),
+            output_path.as_deref(),
+            max_width,
+            scale,
+        )
+        .await
+    }
+
     // ========================================================================
     // Performance tools (IPC)
     // ========================================================================
 
     #[tool(
-        description = "Get current frame statistics (FPS, frame time) from the egui application. Note: Requires the egui app to call record_frame()."
+        description = "Get current frame statistics (FPS, frame time) from the egui application, plus paint statistics of the last frame (shape counts, texture uploads, repaint causes) if the app installed McpPlugin. Note: Requires the egui app to call record_frame()."
     )]
     async fn get_frame_stats(&self) -> String {
         tools::perf::get_frame_stats(&self.ipc_client).await
     }
 
     #[tool(
-        description = "Start recording performance data for later analysis. Call get_perf_report to stop and get results."
+        description = "Get memory statistics: live heap bytes, allocation counts and the allocation churn of the last frame (requires the egui app to install McpAllocator from the alloc-tracking feature), plus the memory of egui's textures (requires install_plugin())."
+    )]
+    async fn get_memory_stats(&self) -> String {
+        tools::perf::get_memory_stats(&self.ipc_client).await
+    }
+
+    #[tool(
+        description = "Start recording performance data for later analysis. Call get_perf_report to stop and get results. With profile=true, also captures tracing span timings inside each frame for export_perf_trace."
     )]
     async fn start_perf_recording(
         &self,
         Parameters(req): Parameters<StartPerfRecordingRequest>,
     ) -> String {
-        tools::perf::start_perf_recording(&self.ipc_client, req.duration_ms).await
+        tools::perf::start_perf_recording(
+            &self.ipc_client,
+            req.duration_ms,
+            req.profile,
+            req.budgets_ms,
+        )
+        .await
     }
 
     #[tool(
-        description = "Stop performance recording and get the report with statistics including percentiles."
+        description = "Stop performance recording and get the report with statistics including percentiles, a frame time histogram, frames over each budget and the longest jank streaks (consecutive frames over the smallest budget, with timestamps). If the app installed McpPlugin, also includes aggregated paint statistics and the most frequent repaint causes (why frames were painted)."
     )]
     async fn get_perf_report(&self) -> String {
         tools::perf::get_perf_report(&self.ipc_client).await
     }
 
+    #[tool(
+        description = "Stop performance recording and check the report against limits (average/p95/p99/max frame time, minimum FPS, frames over a budget, longest jank streak). Returns every check with its actual value; fails with error 'perf_budget_exceeded' if any limit is exceeded. Use for CI performance gates."
+    )]
+    async fn assert_perf(&self, Parameters(req): Parameters<AssertPerfRequest>) -> String {
+        tools::perf::assert_perf(&self.ipc_client, &req).await
+    }
+
+    #[tool(
+        description = "Measure input-to-visible-change latency: inject an input (click, double_click, hover, key, scroll or drag), then report how many frames and milliseconds it took until the UI output changed. Run it on a settled UI (see wait_for_idle), since animations also count as changes. Note: Requires the egui app to call install_plugin()."
+    )]
+    async fn measure_latency(&self, Parameters(req): Parameters<MeasureLatencyRequest>) -> String {
+        tools::perf::measure_latency(&self.ipc_client, &req).await
+    }
+
+    #[tool(
+        description = "Export the span timings of the last profiling recording (start_perf_recording with profile=true) as a Chrome trace event JSON file, to open in Perfetto and see which UI functions are slow. Note: Requires the egui app to register McpProfileLayer."
+    )]
+    async fn export_perf_trace(
+        &self,
+        Parameters(ExportPerfTraceRequest { path }): Parameters<ExportPerfTraceRequest>,
+    ) -> String {
+        tools::perf::export_perf_trace(&self.ipc_client, path.as_deref()).await
+    }
+
+    // ========================================================================
+    // Time control tools (IPC)
+    // ========================================================================
+
+    #[tool(
+        description = "Freeze the time reported to egui so animations, blinking carets and tooltip delays stand still. Use before taking screenshots for comparison. Note: Requires the egui app to call apply_time_control_sync() in raw_input_hook."
+    )]
+    async fn freeze_time(&self) -> String {
+        tools::time::freeze_time(&self.ipc_client).await
+    }
+
+    #[tool(
+        description = "Run N frames with a fixed time step (also used as predicted_dt), then stay frozen. Freezes time first if needed. Returns after the frames have run."
+    )]
+    async fn step_frames(&self, Parameters(req): Parameters<StepFramesRequest>) -> String {
+        tools::time::step_frames(&self.ipc_client, req.frames, req.dt_ms).await
+    }
+
+    #[tool(description = "Resume real time after freeze_time or step_frames.")]
+    async fn resume_time(&self) -> String {
+        tools::time::resume_time(&self.ipc_client).await
+    }
+
     // ========================================================================
     // Wait tools (AT-SPI)
     // ========================================================================
@@ -708,6 +1094,28 @@ impl EguiMcpServer {
         )
         .await
     }
+
+    // ========================================================================
+    // Wait tools (IPC)
+    // ========================================================================
+
+    #[tool(
+        description = "Wait until the UI has settled: returns when no repaint (e.g. animation) has been requested for N consecutive frames. Use after clicks instead of sleeping. Note: Requires the egui app to call record_idle_state_sync()."
+    )]
+    async fn wait_for_idle(
+        &self,
+        Parameters(WaitForIdleRequest {
+            stable_frames,
+            timeout_ms,
+        }): Parameters<WaitForIdleRequest>,
+    ) -> String {
+        tools::wait::wait_for_idle(
+            &self.ipc_client,
+            stable_frames,
+            timeout_ms.unwrap_or(tools::wait::DEFAULT_TIMEOUT_MS),
+        )
+        .await
+    }
 }
 
 #[tool_handler]
@@ -715,15 +1123,20 @@ impl ServerHandler for EguiMcpServer {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             protocol_version: ProtocolVersion::V_2024_11_05,
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_logging()
+                .build(),
             server_info: Implementation::from_build_env(),
             instructions: Some(
                 "egui-mcp server provides tools for UI automation of egui applications. \
                  Use 'ping' to verify the server is running, 'check_connection' to verify \
-                 the egui app is connected, 'get_ui_tree' to inspect the full UI structure, \
+                 the egui app is connected (and whether it crashed), 'get_last_crash' for the \
+                 panic details of a crashed app, 'get_ui_tree' to inspect the full UI structure, \
                  'find_by_label' for substring search, 'find_by_label_exact' for exact match, \
                  'find_by_role' to search by role (e.g., Button, TextInput), \
-                 'get_element' to get details by ID (pass ID as string), \
+                 'get_element' to get details by ID (pass ID as string; without AT-SPI, \
+                 these tree tools use the app's AccessKit tree over IPC), \
                  'click_element' to click an element by ID (AT-SPI), \
                  'set_text' to input text into a text field by ID (AT-SPI), \
                  'click_at' to click at specific coordinates (IPC), \
@@ -733,6 +1146,8 @@ impl ServerHandler for EguiMcpServer {
                  'hover' to move mouse to specific coordinates (IPC), \
                  'drag' to drag from one point to another (IPC), \
                  'take_screenshot' to capture the current UI (IPC), \
+                 'take_vector_screenshot' to export the painted shapes as SVG or JSON (IPC), \
+                 'get_visible_text' to find painted text without accessibility nodes (IPC), \
                  'drag_element' to drag an element to target coordinates (AT-SPI + IPC), \
                  'get_bounds' to get element bounding box (AT-SPI Component), \
                  'focus_element' to focus an element (AT-SPI Component), \
@@ -753,18 +1168,47 @@ impl ServerHandler for EguiMcpServer {
                  'is_enabled' to check if element is enabled (AT-SPI State), \
                  'is_focused' to check if element is focused (AT-SPI State), \
                  'is_checked' to check if element is checked/pressed (AT-SPI State), \
+                 'annotated_screenshot' to capture the app with numbered boxes on interactive elements and a legend of their IDs, \
                  'screenshot_element' to capture a specific element (AT-SPI + IPC), \
                  'screenshot_region' to capture a specific region (IPC), \
                  'wait_for_element' to wait for element to appear/disappear (AT-SPI), \
                  'wait_for_state' to wait for element state change (AT-SPI), \
+                 'wait_for_idle' to wait until animations and repaints have settled (IPC), \
                  'compare_screenshots' to compare two screenshots and get similarity score, \
                  'diff_screenshots' to generate a visual diff image highlighting differences, \
-                 'highlight_element' to draw a colored highlight on an element (AT-SPI + IPC), and \
-                 'clear_highlights' to remove all highlights (IPC)."
+                 'save_baseline'/'check_baseline' to keep golden screenshots on disk and check against them, \
+                 'highlight_element' to draw a colored highlight on an element (AT-SPI + IPC), \
+                 'clear_highlights' to remove all highlights (IPC), \
+                 'inspect_context' to see egui focus, hover, open popups and the layer stack (IPC), \
+                 'export_perf_trace' to export profiled span timings as a Chrome trace (IPC), \
+                 'assert_perf' to check a performance recording against frame time budgets (IPC), \
+                 'measure_latency' to measure input-to-visible-change latency in frames and milliseconds (IPC), \
+                 'get_memory_stats' to get heap and texture memory statistics (IPC), \
+                 'set_log_filter' to change which log events the app captures (IPC), \
+                 'list_viewports' to list secondary windows for viewport_id targeting (IPC), \
+                 'start_input_recording'/'stop_input_recording'/'replay_recording' to capture \
+                 real user input as a script and replay it (IPC), \
+                 'start_screen_recording'/'stop_screen_recording' to record the screen as GIF, APNG or PNG frames (IPC), and \
+                 'freeze_time'/'step_frames'/'resume_time' to make animations deterministic (IPC). \
+                 App logs at warning level and above are sent as logging notifications; \
+                 use logging/setLevel to change the level."
                     .into(),
             ),
         }
     }
+
+    async fn set_level(
+        &self,
+        request: SetLevelRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<(), rmcp::ErrorData> {
+        self.log_stream.set_level(request.level);
+        Ok(())
+    }
+
+    async fn on_initialized(&self, context: NotificationContext<RoleServer>) {
+        self.log_stream.start(context.peer, self.ipc_client.clone());
+    }
 }
 
 async fn run_server() -> Result<()> {
@@ -60,6 +60,10 @@ pub struct ClickAtRequest {
     pub y: f32,
     #[schemars(description = "Mouse button: 'left', 'right', or 'middle' (default: 'left')")]
     pub button: Option<String>,
+    #[schemars(
+        description = "Target viewport ID (as string, from list_viewports). Default: root viewport"
+    )]
+    pub viewport_id: Option<String>,
 }
 
 /// Request for take_screenshot tool
@@ -69,6 +73,72 @@ pub struct TakeScreenshotRequest {
         description = "If true, save screenshot to a temp file and return the path. If false (default), return base64-encoded data."
     )]
     pub save_to_file: Option<bool>,
+    #[schemars(
+        description = "Target viewport ID (as string, from list_viewports). Default: root viewport"
+    )]
+    pub viewport_id: Option<String>,
+    #[serde(flatten)]
+    pub output: ImageOutputOptions,
+}
+
+/// Request for annotated_screenshot tool
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct AnnotatedScreenshotRequest {
+    #[schemars(
+        description = "If true, save the annotated screenshot to a temp file and return the path. If false (default), return base64-encoded data."
+    )]
+    pub save_to_file: Option<bool>,
+    #[schemars(
+        description = "Root viewport ID (as string, from list_viewports). Other viewports are rejected, as the UI tree only covers the root viewport. Default: root viewport"
+    )]
+    pub viewport_id: Option<String>,
+}
+
+/// Resizing and encoding of a returned screenshot
+#[derive(Debug, Default, Deserialize, schemars::JsonSchema)]
+pub struct ImageOutputOptions {
+    #[schemars(
+        description = "Downscale to at most this width in pixels, keeping the aspect ratio"
+    )]
+    pub max_width: Option<u32>,
+    #[schemars(
+        description = "Downscale factor, greater than 0 and at most 1, e.g. 0.5 for half size (applied before max_width)"
+    )]
+    pub scale: Option<f32>,
+    #[schemars(description = "Image format: 'png' (default), 'jpeg' or 'webp' (lossless)")]
+    pub format: Option<String>,
+    #[schemars(
+        description = "JPEG quality, 1-100 (default: 80). Only valid with format 'jpeg'; png and webp are lossless"
+    )]
+    pub quality: Option<u8>,
+}
+
+/// Request for take_vector_screenshot tool
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct TakeVectorScreenshotRequest {
+    #[schemars(
+        description = "Output format: 'svg' (default) or 'json' (shape list with layers, clip rects, colors and text runs)"
+    )]
+    pub format: Option<String>,
+    #[schemars(
+        description = "If true, save the output to a temp file and return the path. If false (default), return it inline."
+    )]
+    pub save_to_file: Option<bool>,
+    #[schemars(
+        description = "Target viewport ID (as string, from list_viewports). Default: root viewport"
+    )]
+    pub viewport_id: Option<String>,
+}
+
+/// Request for get_visible_text tool
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct GetVisibleTextRequest {
+    #[schemars(description = "Only return text runs containing this substring (case-insensitive)")]
+    pub contains: Option<String>,
+    #[schemars(
+        description = "Target viewport ID (as string, from list_viewports). Default: root viewport"
+    )]
+    pub viewport_id: Option<String>,
 }
 
 /// Request for keyboard_input tool
@@ -76,6 +146,10 @@ pub struct TakeScreenshotRequest {
 pub struct KeyboardInputRequest {
     #[schemars(description = "Key to send (e.g., 'a', 'Enter', 'Escape', 'Tab')")]
     pub key: String,
+    #[schemars(
+        description = "Target viewport ID (as string, from list_viewports). Default: root viewport"
+    )]
+    pub viewport_id: Option<String>,
 }
 
 /// Request for scroll tool
@@ -89,6 +163,10 @@ pub struct ScrollRequest {
     pub delta_x: Option<f32>,
     #[schemars(description = "Vertical scroll delta (positive = down)")]
     pub delta_y: Option<f32>,
+    #[schemars(
+        description = "Target viewport ID (as string, from list_viewports). Default: root viewport"
+    )]
+    pub viewport_id: Option<String>,
 }
 
 /// Request for hover tool
@@ -98,6 +176,10 @@ pub struct HoverRequest {
     pub x: f32,
     #[schemars(description = "Y coordinate to move mouse to")]
     pub y: f32,
+    #[schemars(
+        description = "Target viewport ID (as string, from list_viewports). Default: root viewport"
+    )]
+    pub viewport_id: Option<String>,
 }
 
 /// Request for drag tool
@@ -113,6 +195,10 @@ pub struct DragRequest {
     pub end_y: f32,
     #[schemars(description = "Mouse button: 'left', 'right', or 'middle' (default: 'left')")]
     pub button: Option<String>,
+    #[schemars(
+        description = "Target viewport ID (as string, from list_viewports). Default: root viewport"
+    )]
+    pub viewport_id: Option<String>,
 }
 
 /// Request for double_click tool
@@ -124,6 +210,10 @@ pub struct DoubleClickRequest {
     pub y: f32,
     #[schemars(description = "Mouse button: 'left', 'right', or 'middle' (default: 'left')")]
     pub button: Option<String>,
+    #[schemars(
+        description = "Target viewport ID (as string, from list_viewports). Default: root viewport"
+    )]
+    pub viewport_id: Option<String>,
 }
 
 /// Request for drag_element tool
@@ -265,6 +355,12 @@ pub struct ScreenshotElementRequest {
         description = "If true, save screenshot to a temp file and return the path. If false (default), return base64-encoded data."
     )]
     pub save_to_file: Option<bool>,
+    #[schemars(
+        description = "Target viewport ID (as string, from list_viewports). Default: root viewport"
+    )]
+    pub viewport_id: Option<String>,
+    #[serde(flatten)]
+    pub output: ImageOutputOptions,
 }
 
 /// Request for screenshot_region tool
@@ -282,6 +378,12 @@ pub struct ScreenshotRegionRequest {
         description = "If true, save screenshot to a temp file and return the path. If false (default), return base64-encoded data."
     )]
     pub save_to_file: Option<bool>,
+    #[schemars(
+        description = "Target viewport ID (as string, from list_viewports). Default: root viewport"
+    )]
+    pub viewport_id: Option<String>,
+    #[serde(flatten)]
+    pub output: ImageOutputOptions,
 }
 
 /// Request for wait_for_element tool
@@ -310,6 +412,51 @@ pub struct WaitForStateRequest {
     pub timeout_ms: Option<u64>,
 }
 
+/// Request for wait_for_idle tool
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct WaitForIdleRequest {
+    #[schemars(
+        description = "Number of consecutive frames without a requested repaint (default: 3)"
+    )]
+    pub stable_frames: Option<u32>,
+    #[schemars(description = "Timeout in milliseconds (default: 5000)")]
+    pub timeout_ms: Option<u64>,
+}
+
+/// Area of a screenshot: a rectangle in pixels, or the bounds of elements
+#[derive(Debug, Clone, Deserialize, schemars::JsonSchema)]
+pub struct ScreenshotArea {
+    #[schemars(description = "X coordinate of the rectangle")]
+    pub x: Option<f32>,
+    #[schemars(description = "Y coordinate of the rectangle")]
+    pub y: Option<f32>,
+    #[schemars(description = "Width of the rectangle")]
+    pub width: Option<f32>,
+    #[schemars(description = "Height of the rectangle")]
+    pub height: Option<f32>,
+    #[schemars(description = "Use the bounds of this element (node ID as string)")]
+    pub element_id: Option<String>,
+    #[schemars(
+        description = "Use the bounds of every element whose label contains this text (combine with role to narrow down)"
+    )]
+    pub label: Option<String>,
+    #[schemars(
+        description = "Use the bounds of every element with this role (e.g., 'ProgressIndicator')"
+    )]
+    pub role: Option<String>,
+}
+
+/// Screenshot area with its own similarity threshold
+#[derive(Debug, Clone, Deserialize, schemars::JsonSchema)]
+pub struct RegionThreshold {
+    #[schemars(description = "Name reported for the region (default: region_<index>)")]
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub area: ScreenshotArea,
+    #[schemars(description = "Minimum similarity score of the region, 0.0-1.0")]
+    pub threshold: f64,
+}
+
 /// Request for compare_screenshots tool
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct CompareScreenshotsRequest {
@@ -325,6 +472,62 @@ pub struct CompareScreenshotsRequest {
         description = "Comparison algorithm: 'hybrid' (default), 'mssim' (structural), 'rms' (pixel-wise)"
     )]
     pub algorithm: Option<String>,
+    #[schemars(
+        description = "Areas excluded from the comparison (clocks, spinners, live data). Element areas assume full-window screenshots of the running app"
+    )]
+    pub masks: Option<Vec<ScreenshotArea>>,
+    #[schemars(
+        description = "Areas scored separately, each with its own threshold; failing regions are reported"
+    )]
+    pub regions: Option<Vec<RegionThreshold>>,
+}
+
+/// Request for save_baseline tool
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct SaveBaselineRequest {
+    #[schemars(
+        description = "Baseline name (letters, digits, '-', '_', '.'; '/' creates subdirectories)"
+    )]
+    pub name: String,
+    #[schemars(description = "Capture only this element (node ID as string)")]
+    pub element_id: Option<String>,
+    #[schemars(description = "X coordinate of the region to capture")]
+    pub x: Option<f32>,
+    #[schemars(description = "Y coordinate of the region to capture")]
+    pub y: Option<f32>,
+    #[schemars(description = "Width of the region to capture")]
+    pub width: Option<f32>,
+    #[schemars(description = "Height of the region to capture")]
+    pub height: Option<f32>,
+    #[schemars(
+        description = "Target viewport ID (as string, from list_viewports). Default: root viewport"
+    )]
+    pub viewport_id: Option<String>,
+}
+
+/// Request for check_baseline tool
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct CheckBaselineRequest {
+    #[schemars(description = "Baseline name (as passed to save_baseline)")]
+    pub name: String,
+    #[schemars(description = "Minimum similarity score to pass, 0.0-1.0 (default: 0.99)")]
+    pub threshold: Option<f64>,
+    #[schemars(
+        description = "Comparison algorithm: 'hybrid' (default), 'mssim' (structural), 'rms' (pixel-wise)"
+    )]
+    pub algorithm: Option<String>,
+    #[schemars(
+        description = "If true, replace the baseline with the new capture when it does not match (default: false, or EGUI_MCP_UPDATE_BASELINES=1)"
+    )]
+    pub update: Option<bool>,
+    #[schemars(
+        description = "Areas excluded from the comparison, in screen coordinates (clocks, spinners, live data)"
+    )]
+    pub masks: Option<Vec<ScreenshotArea>>,
+    #[schemars(
+        description = "Areas scored separately, each with its own threshold; failing regions fail the check"
+    )]
+    pub regions: Option<Vec<RegionThreshold>>,
 }
 
 /// Request for diff_screenshots tool
@@ -342,6 +545,10 @@ pub struct DiffScreenshotsRequest {
         description = "If true, save diff image to a temp file and return the path. If false (default), return base64-encoded data."
     )]
     pub save_to_file: Option<bool>,
+    #[schemars(
+        description = "Areas excluded from the diff, drawn in blue. Element areas assume full-window screenshots of the running app"
+    )]
+    pub masks: Option<Vec<ScreenshotArea>>,
 }
 
 /// Request for highlight_element tool
@@ -396,8 +603,35 @@ pub struct GetLogsRequest {
         description = "Minimum log level to return: 'TRACE', 'DEBUG', 'INFO', 'WARN', 'ERROR'. If omitted, returns all levels."
     )]
     pub level: Option<String>,
-    #[schemars(description = "Maximum number of entries to return (default: all)")]
+    #[schemars(
+        description = "Maximum number of entries to return (default: all, or 1000 with 'history'). Without 'since' the most recent entries are returned, with 'since' the oldest ones after the cursor."
+    )]
     pub limit: Option<usize>,
+    #[schemars(
+        description = "Only return entries whose target starts with this prefix (e.g., 'my_app::net')"
+    )]
+    pub target: Option<String>,
+    #[schemars(description = "Only return entries whose target matches this regex")]
+    pub target_regex: Option<String>,
+    #[schemars(description = "Only return entries whose message matches this regex")]
+    pub message_regex: Option<String>,
+    #[schemars(
+        description = "Only return entries after this cursor (the 'cursor' of a previous get_logs result), to page through new logs"
+    )]
+    pub since: Option<u64>,
+    #[schemars(
+        description = "Read the persisted log files instead of the in-memory buffer, to include entries the buffer already dropped (default: false). Returns the newest entries that fit in one response and cannot be combined with 'since'. Requires the app to configure a LogFileSink."
+    )]
+    pub history: Option<bool>,
+}
+
+/// Request for set_log_filter tool
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct SetLogFilterRequest {
+    #[schemars(
+        description = "EnvFilter directives, e.g. 'warn,my_app::net=debug' or '[request]=trace'. An empty string captures all levels again."
+    )]
+    pub directives: String,
 }
 
 /// Request for start_perf_recording tool
@@ -407,4 +641,153 @@ pub struct StartPerfRecordingRequest {
         description = "Duration to record in milliseconds. 0 = until get_perf_report is called (default: 0)"
     )]
     pub duration_ms: Option<u64>,
+    #[schemars(
+        description = "Also capture tracing span timings for export_perf_trace (requires McpProfileLayer in the app, default: false)"
+    )]
+    pub profile: Option<bool>,
+    #[schemars(
+        description = "Frame time budgets in milliseconds to count frames over (default: [16.6, 33.3]). The smallest budget is the jank threshold."
+    )]
+    pub budgets_ms: Option<Vec<f32>>,
+}
+
+/// Request for measure_latency tool
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct MeasureLatencyRequest {
+    #[schemars(
+        description = "Input to inject: 'click', 'double_click', 'hover', 'key', 'scroll' or 'drag'"
+    )]
+    pub action: String,
+    #[schemars(description = "X coordinate (start X for 'drag'; required except for 'key')")]
+    pub x: Option<f32>,
+    #[schemars(description = "Y coordinate (start Y for 'drag'; required except for 'key')")]
+    pub y: Option<f32>,
+    #[schemars(description = "End X coordinate (required for 'drag')")]
+    pub end_x: Option<f32>,
+    #[schemars(description = "End Y coordinate (required for 'drag')")]
+    pub end_y: Option<f32>,
+    #[schemars(description = "Mouse button: 'left', 'right', or 'middle' (default: 'left')")]
+    pub button: Option<String>,
+    #[schemars(description = "Key to send for 'key' (e.g., 'a', 'Enter')")]
+    pub key: Option<String>,
+    #[schemars(description = "Horizontal scroll delta for 'scroll' (default: 0)")]
+    pub delta_x: Option<f32>,
+    #[schemars(description = "Vertical scroll delta for 'scroll' (default: 0)")]
+    pub delta_y: Option<f32>,
+    #[schemars(
+        description = "Target viewport ID (as string, from list_viewports). Default: root viewport"
+    )]
+    pub viewport_id: Option<String>,
+    #[schemars(
+        description = "Maximum time to wait for a visible change in milliseconds (default: 2000)"
+    )]
+    pub timeout_ms: Option<u64>,
+}
+
+/// Request for assert_perf tool
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct AssertPerfRequest {
+    #[schemars(description = "Maximum allowed average frame time in milliseconds")]
+    pub max_avg_frame_time_ms: Option<f32>,
+    #[schemars(description = "Maximum allowed 95th percentile frame time in milliseconds")]
+    pub max_p95_frame_time_ms: Option<f32>,
+    #[schemars(description = "Maximum allowed 99th percentile frame time in milliseconds")]
+    pub max_p99_frame_time_ms: Option<f32>,
+    #[schemars(description = "Maximum allowed frame time in milliseconds")]
+    pub max_frame_time_ms: Option<f32>,
+    #[schemars(description = "Minimum required average FPS")]
+    pub min_avg_fps: Option<f32>,
+    #[schemars(
+        description = "Budget in milliseconds for max_frames_over_budget. Must be one of the budgets passed to start_perf_recording (default: the smallest budget)"
+    )]
+    pub budget_ms: Option<f32>,
+    #[schemars(description = "Maximum allowed number of frames over budget_ms")]
+    pub max_frames_over_budget: Option<usize>,
+    #[schemars(description = "Maximum allowed number of consecutive janky frames")]
+    pub max_jank_streak_frames: Option<usize>,
+}
+
+/// Request for export_perf_trace tool
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ExportPerfTraceRequest {
+    #[schemars(
+        description = "File path to write the Chrome trace JSON to (default: /tmp/egui-mcp-trace-<timestamp>.json)"
+    )]
+    pub path: Option<String>,
+}
+
+/// Request for step_frames tool
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct StepFramesRequest {
+    #[schemars(description = "Number of frames to run (default: 1)")]
+    pub frames: Option<u32>,
+    #[schemars(
+        description = "Time advanced per frame in milliseconds, also used as predicted_dt (default: 16.67)"
+    )]
+    pub dt_ms: Option<f32>,
+}
+
+/// Request for inspect_context tool
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct InspectContextRequest {
+    #[schemars(
+        description = "Target viewport ID (as string, from list_viewports). Default: root viewport"
+    )]
+    pub viewport_id: Option<String>,
+}
+
+/// Request for stop_input_recording tool
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct StopInputRecordingRequest {
+    #[schemars(description = "Optional file path to save the recording to as JSON")]
+    pub save_path: Option<String>,
+}
+
+/// Request for replay_recording tool
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ReplayRecordingRequest {
+    #[schemars(description = "Path of a recording saved by stop_input_recording")]
+    pub path: Option<String>,
+    #[schemars(
+        description = "Recording as a JSON string ({\"duration_ms\", \"steps\": [{\"at_ms\", \"request\"}]}). Takes precedence over 'path'"
+    )]
+    pub script: Option<String>,
+    #[schemars(
+        description = "Playback speed multiplier, e.g. 2.0 replays twice as fast (default: 1.0)"
+    )]
+    pub speed: Option<f32>,
+}
+
+/// Request for start_screen_recording tool
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct StartScreenRecordingRequest {
+    #[schemars(description = "Frames per second, up to 30 (default: 10)")]
+    pub fps: Option<f32>,
+    #[schemars(
+        description = "Stop capturing after this many milliseconds, up to 60000 (default: 10000)"
+    )]
+    pub max_duration_ms: Option<u64>,
+    #[schemars(
+        description = "Target viewport ID (as string, from list_viewports). Default: root viewport"
+    )]
+    pub viewport_id: Option<String>,
+}
+
+/// Request for stop_screen_recording tool
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct StopScreenRecordingRequest {
+    #[schemars(
+        description = "Output format: 'gif' (default), 'apng', or 'frames' (a directory of numbered PNGs with frames.json listing their timestamps)"
+    )]
+    pub format: Option<String>,
+    #[schemars(
+        description = "Output file (or directory for 'frames'). Default: /tmp/egui-mcp-recording-<timestamp>"
+    )]
+    pub output_path: Option<String>,
+    #[schemars(description = "Downscale frames to at most this width in pixels")]
+    pub max_width: Option<u32>,
+    #[schemars(
+        description = "Downscale factor for the frames, greater than 0 and at most 1, e.g. 0.5 for half size"
+    )]
+    pub scale: Option<f32>,
 }
@@ -108,7 +108,7 @@ pub async fn drag_element(
                 };
 
                 match ipc_client
-                    .drag(center_x, center_y, end_x, end_y, mouse_button)
+                    .drag(center_x, center_y, end_x, end_y, mouse_button, None)
                     .await
                 {
                     Ok(()) => {
@@ -0,0 +1,352 @@
+//! Annotated screenshot tool implementation (set-of-marks)
+//!
+//! Draws a numbered box on every visible interactive element of the UI tree
+//! so vision models can refer to elements by number, and returns a legend
+//! mapping each number to the element ID, role and label.
+
+use super::screenshot::{encode_png, save_screenshot_to_file};
+use super::tree::{fill_bounds, load_tree};
+use super::{ToolResult, error_response, not_connected_error, parse_viewport_id};
+use crate::ipc_client::IpcClient;
+use egui_mcp_protocol::{NodeInfo, Rect};
+use image::{Rgba, RgbaImage};
+use rmcp::model::Content;
+use serde_json::json;
+
+/// Roles of interactive elements, as named by AccessKit (IPC tree) and AT-SPI
+const INTERACTIVE_ROLES: &[&str] = &[
+    // AccessKit
+    "Button",
+    "DefaultButton",
+    "CheckBox",
+    "RadioButton",
+    "Switch",
+    "TextInput",
+    "MultilineTextInput",
+    "SearchInput",
+    "PasswordInput",
+    "NumberInput",
+    "ComboBox",
+    "EditableComboBox",
+    "Slider",
+    "SpinButton",
+    "Link",
+    "MenuItem",
+    "MenuItemCheckBox",
+    "MenuItemRadio",
+    "Tab",
+    "ListBoxOption",
+    "TreeItem",
+    "ColorWell",
+    // AT-SPI
+    "PushButton",
+    "ToggleButton",
+    "Entry",
+    "PasswordText",
+    "CheckMenuItem",
+    "RadioMenuItem",
+    "PageTab",
+    "ListItem",
+];
+
+/// Box colors, cycled by mark number
+const PALETTE: &[[u8; 3]] = &[
+    [230, 25, 75],
+    [0, 130, 200],
+    [60, 150, 40],
+    [145, 30, 180],
+    [245, 130, 48],
+    [0, 128, 128],
+];
+
+/// 3x5 bitmap digits, one row per byte (bit 2 = left column)
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+/// Pixel size of a digit dot
+const DIGIT_SCALE: i64 = 2;
+/// Box outline width in pixels
+const OUTLINE_WIDTH: i64 = 2;
+
+/// Take a screenshot with numbered boxes on the interactive elements
+///
+/// Returns the legend as JSON followed by the image (or the saved file path).
+pub async fn annotated_screenshot(
+    app_name: &str,
+    ipc_client: &IpcClient,
+    save_to_file: bool,
+    viewport_id: Option<&str>,
+) -> Vec<Content> {
+    use base64::Engine;
+
+    let error = |e: ToolResult| vec![Content::text(e)];
+    let viewport_id = match parse_viewport_id(viewport_id) {
+        Ok(id) => id,
+        Err(e) => return error(e),
+    };
+    if !ipc_client.is_socket_available() {
+        return error(not_connected_error());
+    }
+    // The UI tree only holds the elements of the root viewport
+    if let Some(id) = viewport_id {
+        match ipc_client.list_viewports().await {
+            Ok(viewports) if viewports.iter().any(|v| v.id == id && v.is_root) => {}
+            Ok(_) => {
+                return error(error_response(
+                    "invalid_viewport",
+                    format!(
+                        "Viewport {} is not the root viewport. The UI tree has no viewport information, so only the root viewport can be annotated",
+                        id
+                    ),
+                ));
+            }
+            Err(e) => {
+                return error(error_response(
+                    "ipc_error",
+                    format!("Failed to list viewports: {}", e),
+                ));
+            }
+        }
+    }
+
+    let tree = match load_tree(app_name, ipc_client).await {
+        Ok(Some(tree)) => tree,
+        Ok(None) => {
+            return error(error_response(
+                "not_available",
+                "UI tree access requires AT-SPI on Linux. Make sure the egui app is running.",
+            ));
+        }
+        Err(e) => return error(e),
+    };
+    let mut nodes: Vec<NodeInfo> = tree
+        .nodes
+        .into_iter()
+        .filter(|node| INTERACTIVE_ROLES.contains(&node.role.as_str()))
+        .collect();
+    fill_bounds(app_name, &mut nodes).await;
+
+    let data = match ipc_client.take_screenshot(viewport_id).await {
+        Ok((data, _format)) => data,
+        Err(e) => {
+            return error(error_response(
+                "screenshot_error",
+                format!("Failed to take screenshot: {}", e),
+            ));
+        }
+    };
+    let mut img = match base64::engine::general_purpose::STANDARD
+        .decode(&data)
+        .map_err(|e| e.to_string())
+        .and_then(|bytes| image::load_from_memory(&bytes).map_err(|e| e.to_string()))
+    {
+        Ok(img) => img.to_rgba8(),
+        Err(e) => {
+            return error(error_response(
+                "image_error",
+                format!("Failed to load screenshot: {}", e),
+            ));
+        }
+    };
+
+    let marks = visible_marks(nodes, img.dimensions());
+    let rects: Vec<Rect> = marks.iter().filter_map(|node| node.bounds).collect();
+    draw_marks(&mut img, &rects);
+
+    let legend: Vec<_> = marks
+        .iter()
+        .enumerate()
+        .map(|(index, node)| {
+            json!({
+                "mark": index + 1,
+                "id": node.id,
+                "role": node.role,
+                "label": node.label,
+                "bounds": node.bounds,
+                "disabled": node.disabled
+            })
+        })
+        .collect();
+    let legend = json!({
+        "count": legend.len(),
+        "marks": legend
+    })
+    .to_string();
+
+    let png = match encode_png(&img) {
+        Ok(png) => png,
+        Err(e) => return error(e),
+    };
+    let data = base64::engine::general_purpose::STANDARD.encode(png);
+    if save_to_file {
+        vec![Content::text(legend), save_screenshot_to_file(&data)]
+    } else {
+        vec![Content::text(legend), Content::image(data, "image/png")]
+    }
+}
+
+/// Nodes with bounds inside the image, top to bottom and left to right
+fn visible_marks(nodes: Vec<NodeInfo>, (width, height): (u32, u32)) -> Vec<NodeInfo> {
+    let mut marks: Vec<NodeInfo> = nodes
+        .into_iter()
+        .filter(|node| {
+            node.bounds.is_some_and(|b| {
+                b.width > 0.0
+                    && b.height > 0.0
+                    && b.x < width as f32
+                    && b.y < height as f32
+                    && b.x + b.width > 0.0
+                    && b.y + b.height > 0.0
+            })
+        })
+        .collect();
+    marks.sort_by(|a, b| {
+        let (a, b) = (a.bounds.unwrap_or(ZERO), b.bounds.unwrap_or(ZERO));
+        (a.y, a.x)
+            .partial_cmp(&(b.y, b.x))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    marks
+}
+
+const ZERO: Rect = Rect {
+    x: 0.0,
+    y: 0.0,
+    width: 0.0,
+    height: 0.0,
+};
+
+/// Draw a box with a number tag (1-based) for each rectangle
+fn draw_marks(img: &mut RgbaImage, rects: &[Rect]) {
+    for (index, rect) in rects.iter().enumerate() {
+        let [r, g, b] = PALETTE[index % PALETTE.len()];
+        let color = Rgba([r, g, b, 255]);
+        let (x0, y0) = (rect.x.round() as i64, rect.y.round() as i64);
+        let (x1, y1) = (
+            (rect.x + rect.width).round() as i64,
+            (rect.y + rect.height).round() as i64,
+        );
+
+        // Outline
+        fill_rect(img, x0, y0, x1, y0 + OUTLINE_WIDTH, color);
+        fill_rect(img, x0, y1 - OUTLINE_WIDTH, x1, y1, color);
+        fill_rect(img, x0, y0, x0 + OUTLINE_WIDTH, y1, color);
+        fill_rect(img, x1 - OUTLINE_WIDTH, y0, x1, y1, color);
+
+        // Number tag above the top-left corner, or inside at the top edge
+        let number = (index + 1).to_string();
+        let digit_width = 3 * DIGIT_SCALE;
+        let tag_width = number.len() as i64 * (digit_width + DIGIT_SCALE) + DIGIT_SCALE;
+        let tag_height = 5 * DIGIT_SCALE + 2 * DIGIT_SCALE;
+        let tag_y = if y0 >= tag_height {
+            y0 - tag_height
+        } else {
+            y0
+        };
+        fill_rect(img, x0, tag_y, x0 + tag_width, tag_y + tag_height, color);
+        for (i, digit) in number.bytes().enumerate() {
+            let x = x0 + DIGIT_SCALE + i as i64 * (digit_width + DIGIT_SCALE);
+            draw_digit(img, x, tag_y + DIGIT_SCALE, (digit - b'0') as usize);
+        }
+    }
+}
+
+fn draw_digit(img: &mut RgbaImage, x: i64, y: i64, digit: usize) {
+    let white = Rgba([255, 255, 255, 255]);
+    for (row, bits) in DIGITS[digit].iter().enumerate() {
+        for col in 0..3 {
+            if bits & (0b100 >> col) != 0 {
+                let px = x + col * DIGIT_SCALE;
+                let py = y + row as i64 * DIGIT_SCALE;
+                fill_rect(img, px, py, px + DIGIT_SCALE, py + DIGIT_SCALE, white);
+            }
+        }
+    }
+}
+
+/// Fill the pixels in `[x0, x1) x [y0, y1)`, clipped to the image
+fn fill_rect(img: &mut RgbaImage, x0: i64, y0: i64, x1: i64, y1: i64, color: Rgba<u8>) {
+    let (width, height) = (img.width() as i64, img.height() as i64);
+    for y in y0.max(0)..y1.min(height) {
+        for x in x0.max(0)..x1.min(width) {
+            img.put_pixel(x as u32, y as u32, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: u64, bounds: Option<Rect>) -> NodeInfo {
+        NodeInfo {
+            id,
+            role: "Button".to_string(),
+            label: Some(format!("Button {}", id)),
+            value: None,
+            bounds,
+            children: vec![],
+            toggled: None,
+            disabled: false,
+            focused: false,
+        }
+    }
+
+    fn rect(x: f32, y: f32, width: f32, height: f32) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn test_visible_marks_sorted_and_filtered() {
+        let nodes = vec![
+            node(1, Some(rect(50.0, 40.0, 20.0, 10.0))),
+            node(2, Some(rect(10.0, 40.0, 20.0, 10.0))),
+            node(3, Some(rect(10.0, 5.0, 20.0, 10.0))),
+            node(4, None),
+            node(5, Some(rect(200.0, 5.0, 20.0, 10.0))),
+            node(6, Some(rect(10.0, 20.0, 0.0, 10.0))),
+        ];
+        let ids: Vec<u64> = visible_marks(nodes, (100, 100))
+            .iter()
+            .map(|n| n.id)
+            .collect();
+        assert_eq!(ids, vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn test_draw_marks() {
+        let background = Rgba([0, 0, 0, 255]);
+        let mut img = RgbaImage::from_pixel(100, 60, background);
+        draw_marks(&mut img, &[rect(20.0, 30.0, 40.0, 20.0)]);
+
+        let [r, g, b] = PALETTE[0];
+        let color = Rgba([r, g, b, 255]);
+        // Outline corners and the tag above the box
+        assert_eq!(*img.get_pixel(20, 30), color);
+        assert_eq!(*img.get_pixel(59, 49), color);
+        assert_eq!(*img.get_pixel(20, 17), color);
+        // The "1" is drawn in white in the tag
+        let tag_pixels = (16..30)
+            .flat_map(|y| (20..30).map(move |x| (x, y)))
+            .filter(|&(x, y)| *img.get_pixel(x, y) == Rgba([255, 255, 255, 255]))
+            .count();
+        assert!(tag_pixels > 0);
+        // The inside of the box is untouched
+        assert_eq!(*img.get_pixel(40, 40), background);
+    }
+}
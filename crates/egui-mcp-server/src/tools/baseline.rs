@@ -0,0 +1,444 @@
+//! Golden screenshot baseline tool implementations (save_baseline, check_baseline)
+//!
+//! Baselines are PNG files under a directory (`EGUI_MCP_BASELINE_DIR`, default
+//! `egui-mcp-baselines` in the working directory) with a JSON file next to each
+//! one recording what was captured, so `check_baseline` recaptures the same
+//! element or region. Failed checks write `<name>.actual.png` and
+//! `<name>.diff.png` next to the baseline.
+
+use super::masks::{AreaResolver, mask_gray, score_regions};
+use super::screenshot::{capture_image, encode_png, masked_diff_image, similarity};
+use super::{ToolResult, error_response, parse_element_id, parse_viewport_id};
+use crate::constants::{DEFAULT_BASELINE_DIR, DEFAULT_BASELINE_THRESHOLD};
+use crate::ipc_client::IpcClient;
+use crate::requests::{RegionThreshold, ScreenshotArea};
+use egui_mcp_protocol::Rect;
+use rmcp::model::Content;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+
+/// What a baseline captured, stored as `<name>.json`
+#[derive(Debug, Serialize, Deserialize)]
+struct BaselineTarget {
+    element_id: Option<u64>,
+    region: Option<Rect>,
+    viewport_id: Option<u64>,
+}
+
+impl BaselineTarget {
+    /// Parse the target of save_baseline: the app, an element or a region
+    fn new(
+        element_id: Option<&str>,
+        region: Option<Rect>,
+        viewport_id: Option<&str>,
+    ) -> Result<Self, ToolResult> {
+        if element_id.is_some() && region.is_some() {
+            return Err(error_response(
+                "invalid_target",
+                "Pass either element_id or a region (x, y, width, height), not both",
+            ));
+        }
+        Ok(Self {
+            element_id: element_id.map(parse_element_id).transpose()?,
+            region,
+            viewport_id: parse_viewport_id(viewport_id)?,
+        })
+    }
+}
+
+/// Region from the x, y, width and height arguments (None if none is given)
+pub fn parse_region(
+    x: Option<f32>,
+    y: Option<f32>,
+    width: Option<f32>,
+    height: Option<f32>,
+) -> Result<Option<Rect>, ToolResult> {
+    match (x, y, width, height) {
+        (Some(x), Some(y), Some(width), Some(height)) => Ok(Some(Rect {
+            x,
+            y,
+            width,
+            height,
+        })),
+        (None, None, None, None) => Ok(None),
+        _ => Err(error_response(
+            "invalid_region",
+            "A region needs all of x, y, width and height",
+        )),
+    }
+}
+
+/// Capture the app, an element or a region and store it as baseline `name`
+pub async fn save_baseline(
+    app_name: &str,
+    ipc_client: &IpcClient,
+    name: &str,
+    element_id: Option<&str>,
+    region: Option<Rect>,
+    viewport_id: Option<&str>,
+) -> ToolResult {
+    let target = match BaselineTarget::new(element_id, region, viewport_id) {
+        Ok(target) => target,
+        Err(e) => return e,
+    };
+    let paths = match BaselinePaths::new(name) {
+        Ok(paths) => paths,
+        Err(e) => return e,
+    };
+
+    let image = match capture_image(
+        app_name,
+        ipc_client,
+        target.element_id,
+        target.region,
+        target.viewport_id,
+    )
+    .await
+    {
+        Ok((image, _bounds)) => image.to_rgba8(),
+        Err(e) => return e,
+    };
+    if let Err(e) = paths.write_baseline(&image, &target) {
+        return e;
+    }
+
+    json!({
+        "success": true,
+        "name": name,
+        "path": paths.png,
+        "width": image.width(),
+        "height": image.height()
+    })
+    .to_string()
+}
+
+/// Recapture baseline `name` and compare it to the stored image
+///
+/// Returns the verdict as JSON, followed by the diff image when the check
+/// fails. In update mode (`update` or `EGUI_MCP_UPDATE_BASELINES=1`) a
+/// failing capture replaces the baseline instead. Masks and regions are in
+/// screen coordinates and shifted to the captured element or region.
+#[allow(clippy::too_many_arguments)]
+pub async fn check_baseline(
+    app_name: &str,
+    ipc_client: &IpcClient,
+    name: &str,
+    threshold: Option<f64>,
+    algorithm: Option<&str>,
+    update: bool,
+    masks: &[ScreenshotArea],
+    regions: &[RegionThreshold],
+) -> Vec<Content> {
+    let error = |e: ToolResult| vec![Content::text(e)];
+    let paths = match BaselinePaths::new(name) {
+        Ok(paths) => paths,
+        Err(e) => return error(e),
+    };
+    let (baseline, target) = match paths.read_baseline() {
+        Ok(baseline) => baseline,
+        Err(e) => return error(e),
+    };
+
+    let (actual, bounds) = match capture_image(
+        app_name,
+        ipc_client,
+        target.element_id,
+        target.region,
+        target.viewport_id,
+    )
+    .await
+    {
+        Ok((image, bounds)) => (image.to_rgba8(), bounds),
+        Err(e) => return error(e),
+    };
+
+    let mut resolver = AreaResolver::new(app_name, ipc_client);
+    let mask_rects = match resolver.masks(masks).await {
+        Ok(rects) => rects,
+        Err(e) => return error(e),
+    };
+    let regions = match resolver.regions(regions).await {
+        Ok(regions) => regions,
+        Err(e) => return error(e),
+    };
+    // Masks and regions are given in screen coordinates
+    let to_capture = |rect: Rect| match bounds {
+        Some(origin) => Rect {
+            x: rect.x - origin.x,
+            y: rect.y - origin.y,
+            ..rect
+        },
+        None => rect,
+    };
+    let mask_rects: Vec<Rect> = mask_rects.into_iter().map(to_capture).collect();
+    let regions: Vec<_> = regions
+        .into_iter()
+        .map(|mut region| {
+            region.rects = region.rects.into_iter().map(to_capture).collect();
+            region
+        })
+        .collect();
+
+    let threshold = threshold.unwrap_or(DEFAULT_BASELINE_THRESHOLD);
+    let algorithm = algorithm.unwrap_or("hybrid");
+    let same_size = baseline.dimensions() == actual.dimensions();
+    let mut region_report = None;
+    let score = if same_size {
+        let mut gray_baseline = image::DynamicImage::ImageRgba8(baseline.clone()).to_luma8();
+        let mut gray_actual = image::DynamicImage::ImageRgba8(actual.clone()).to_luma8();
+        mask_gray(&mut gray_baseline, &mask_rects);
+        mask_gray(&mut gray_actual, &mask_rects);
+        if !regions.is_empty() {
+            match score_regions(&gray_baseline, &gray_actual, &regions, algorithm) {
+                Ok(report) => region_report = Some(report),
+                Err(e) => return error(e),
+            }
+        }
+        match similarity(&gray_baseline, &gray_actual, algorithm) {
+            Ok(score) => score,
+            Err(e) => return error(e),
+        }
+    } else {
+        0.0
+    };
+    let regions_passed = region_report
+        .as_ref()
+        .is_none_or(|(_, failed)| failed.is_empty());
+    let passed = same_size && score >= threshold && regions_passed;
+
+    let mut verdict = json!({
+        "name": name,
+        "passed": passed,
+        "score": score,
+        "threshold": threshold,
+        "algorithm": algorithm,
+        "baseline_path": paths.png
+    });
+    if let Some((report, failed)) = region_report {
+        verdict["regions"] = json!(report);
+        verdict["failed_regions"] = json!(failed);
+    }
+    if !same_size {
+        verdict["reason"] = json!(format!(
+            "dimension_mismatch: baseline {:?} vs actual {:?}",
+            baseline.dimensions(),
+            actual.dimensions()
+        ));
+    }
+    if passed {
+        // Remove leftovers of an earlier failure
+        let _ = std::fs::remove_file(&paths.actual);
+        let _ = std::fs::remove_file(&paths.diff);
+        return vec![Content::text(verdict.to_string())];
+    }
+
+    if update || update_mode_from_env() {
+        if let Err(e) = paths.write_baseline(&actual, &target) {
+            return error(e);
+        }
+        let _ = std::fs::remove_file(&paths.actual);
+        let _ = std::fs::remove_file(&paths.diff);
+        verdict["updated"] = json!(true);
+        return vec![Content::text(verdict.to_string())];
+    }
+
+    let diff = match encode_png(&masked_diff_image(baseline, actual.clone(), &mask_rects)) {
+        Ok(diff) => diff,
+        Err(e) => return error(e),
+    };
+    if let Err(e) = actual
+        .save(&paths.actual)
+        .map_err(|e| e.to_string())
+        .and_then(|()| std::fs::write(&paths.diff, &diff).map_err(|e| e.to_string()))
+    {
+        return error(error_response(
+            "file_write_error",
+            format!("Failed to write diff files: {}", e),
+        ));
+    }
+    verdict["actual_path"] = json!(paths.actual);
+    verdict["diff_path"] = json!(paths.diff);
+
+    use base64::Engine;
+    vec![
+        Content::text(verdict.to_string()),
+        Content::image(
+            base64::engine::general_purpose::STANDARD.encode(&diff),
+            "image/png",
+        ),
+    ]
+}
+
+/// Directory holding the baselines
+fn baseline_dir() -> PathBuf {
+    std::env::var_os("EGUI_MCP_BASELINE_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_BASELINE_DIR))
+}
+
+/// Whether `EGUI_MCP_UPDATE_BASELINES` asks to accept new captures
+fn update_mode_from_env() -> bool {
+    std::env::var("EGUI_MCP_UPDATE_BASELINES")
+        .map(|v| matches!(v.as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+/// Files of one baseline
+struct BaselinePaths {
+    png: PathBuf,
+    meta: PathBuf,
+    actual: PathBuf,
+    diff: PathBuf,
+}
+
+impl BaselinePaths {
+    fn new(name: &str) -> Result<Self, ToolResult> {
+        Self::in_dir(&baseline_dir(), name)
+    }
+
+    /// Paths of baseline `name`; `/` in the name creates subdirectories
+    fn in_dir(dir: &Path, name: &str) -> Result<Self, ToolResult> {
+        let valid = !name.is_empty()
+            && name.split('/').all(|segment| {
+                !segment.is_empty()
+                    && segment != "."
+                    && segment != ".."
+                    && segment
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            });
+        if !valid {
+            return Err(error_response(
+                "invalid_name",
+                format!(
+                    "Invalid baseline name '{}'. Use letters, digits, '-', '_', '.' and '/' for subdirectories",
+                    name
+                ),
+            ));
+        }
+        let base = dir.join(name);
+        let with_suffix = |suffix: &str| {
+            let mut path = base.clone().into_os_string();
+            path.push(suffix);
+            PathBuf::from(path)
+        };
+        Ok(Self {
+            png: with_suffix(".png"),
+            meta: with_suffix(".json"),
+            actual: with_suffix(".actual.png"),
+            diff: with_suffix(".diff.png"),
+        })
+    }
+
+    fn write_baseline(
+        &self,
+        image: &image::RgbaImage,
+        target: &BaselineTarget,
+    ) -> Result<(), ToolResult> {
+        let write_error = |e: &dyn std::fmt::Display| {
+            error_response(
+                "file_write_error",
+                format!("Failed to write baseline {}: {}", self.png.display(), e),
+            )
+        };
+        if let Some(parent) = self.png.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| write_error(&e))?;
+        }
+        image.save(&self.png).map_err(|e| write_error(&e))?;
+        let meta = serde_json::to_string_pretty(target).map_err(|e| write_error(&e))?;
+        std::fs::write(&self.meta, meta).map_err(|e| write_error(&e))
+    }
+
+    fn read_baseline(&self) -> Result<(image::RgbaImage, BaselineTarget), ToolResult> {
+        if !self.png.exists() {
+            return Err(error_response(
+                "baseline_not_found",
+                format!(
+                    "No baseline at {}. Create it with save_baseline first",
+                    self.png.display()
+                ),
+            ));
+        }
+        let image = image::open(&self.png)
+            .map_err(|e| {
+                error_response(
+                    "file_error",
+                    format!("Failed to open baseline {}: {}", self.png.display(), e),
+                )
+            })?
+            .to_rgba8();
+        // Baselines added by hand without metadata capture the whole root viewport
+        let target = match std::fs::read_to_string(&self.meta) {
+            Ok(meta) => serde_json::from_str(&meta).map_err(|e| {
+                error_response(
+                    "file_error",
+                    format!("Invalid baseline metadata {}: {}", self.meta.display(), e),
+                )
+            })?,
+            Err(_) => BaselineTarget {
+                element_id: None,
+                region: None,
+                viewport_id: None,
+            },
+        };
+        Ok((image, target))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_baseline_paths() {
+        let dir = Path::new("baselines");
+        let paths = BaselinePaths::in_dir(dir, "settings/dark-mode").unwrap();
+        assert_eq!(paths.png, dir.join("settings/dark-mode.png"));
+        assert_eq!(paths.meta, dir.join("settings/dark-mode.json"));
+        assert_eq!(paths.diff, dir.join("settings/dark-mode.diff.png"));
+
+        for name in ["", "../escape", "/absolute", "a//b", "spaces here"] {
+            assert!(BaselinePaths::in_dir(dir, name).is_err(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_parse_region() {
+        let region = parse_region(Some(1.0), Some(2.0), Some(30.0), Some(40.0)).unwrap();
+        assert_eq!(region.map(|r| (r.x, r.width)), Some((1.0, 30.0)));
+        assert!(parse_region(None, None, None, None).unwrap().is_none());
+        assert!(parse_region(Some(1.0), None, None, None).is_err());
+        assert!(parse_region(Some(1.0), Some(2.0), Some(30.0), None).is_err());
+    }
+
+    #[test]
+    fn test_baseline_target_is_element_or_region() {
+        let region = parse_region(Some(1.0), Some(2.0), Some(30.0), Some(40.0)).unwrap();
+        assert!(BaselineTarget::new(Some("7"), region, None).is_err());
+        let target = BaselineTarget::new(Some("7"), None, None).unwrap();
+        assert_eq!(target.element_id, Some(7));
+        let target = BaselineTarget::new(None, region, Some("3")).unwrap();
+        assert!(target.region.is_some());
+        assert_eq!(target.viewport_id, Some(3));
+    }
+
+    #[test]
+    fn test_baseline_roundtrip() {
+        let dir =
+            std::env::temp_dir().join(format!("egui-mcp-baseline-test-{}", std::process::id()));
+        let paths = BaselinePaths::in_dir(&dir, "nested/button").unwrap();
+        let image = image::RgbaImage::from_pixel(4, 3, image::Rgba([10, 20, 30, 255]));
+        let target = BaselineTarget {
+            element_id: Some(7),
+            region: None,
+            viewport_id: None,
+        };
+        paths.write_baseline(&image, &target).unwrap();
+
+        let (loaded, loaded_target) = paths.read_baseline().unwrap();
+        assert_eq!(loaded, image);
+        assert_eq!(loaded_target.element_id, Some(7));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
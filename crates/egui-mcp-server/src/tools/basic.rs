@@ -1,6 +1,7 @@
-//! Basic tool implementations (ping, check_connection)
+//! Basic tool implementations (ping, check_connection, get_last_crash)
 
-use crate::ipc_client::IpcClient;
+use super::{ToolResult, error_response};
+use crate::ipc_client::{IpcClient, crash_summary};
 use serde_json::json;
 
 /// Ping the server to check if it's running
@@ -11,11 +12,10 @@ pub fn ping() -> String {
 /// Check connection to the egui application
 pub async fn check_connection(ipc_client: &IpcClient) -> String {
     if !ipc_client.is_socket_available() {
-        return json!({
-            "connected": false,
-            "message": "No egui application socket found. Make sure the egui app is running with egui-mcp-client."
-        })
-        .to_string();
+        return disconnected(
+            ipc_client,
+            "No egui application socket found. Make sure the egui app is running with egui-mcp-client.",
+        );
     }
 
     match ipc_client.ping().await {
@@ -24,15 +24,46 @@ pub async fn check_connection(ipc_client: &IpcClient) -> String {
             "message": "egui application is connected and responding"
         })
         .to_string(),
-        Ok(false) => json!({
+        Ok(false) => disconnected(ipc_client, "egui application did not respond correctly"),
+        Err(e) => disconnected(ipc_client, &format!("Failed to connect: {}", e)),
+    }
+}
+
+/// Build the "not connected" result, including the crash report if the app panicked
+fn disconnected(ipc_client: &IpcClient, message: &str) -> String {
+    if let Some(crash) = ipc_client.recent_crash() {
+        return json!({
             "connected": false,
-            "message": "egui application did not respond correctly"
+            "crashed": true,
+            "message": crash_summary(&crash),
+            "crash": crash
         })
-        .to_string(),
-        Err(e) => json!({
-            "connected": false,
-            "message": format!("Failed to connect: {}", e)
+        .to_string();
+    }
+    let mut result = json!({
+        "connected": false,
+        "message": message
+    });
+    // An older crash (before this server connected) may still explain the failure
+    if let Some(crash) = ipc_client.last_crash() {
+        result["last_crash"] = json!(crash);
+    }
+    result.to_string()
+}
+
+/// Get the crash report written by the app's panic hook
+pub fn get_last_crash(ipc_client: &IpcClient) -> ToolResult {
+    match ipc_client.last_crash() {
+        Some(crash) => json!({
+            "crashed": true,
+            "recent": ipc_client.recent_crash().is_some(),
+            "message": crash_summary(&crash),
+            "crash": crash
         })
         .to_string(),
+        None => error_response(
+            "no_crash",
+            "No crash recorded. The app must call McpClient::install_panic_hook() to record panics.",
+        ),
     }
 }
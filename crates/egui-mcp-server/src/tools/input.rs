@@ -1,11 +1,22 @@
 //! IPC input tool implementations (click_at, keyboard_input, scroll, hover, drag, double_click)
 
-use super::{ToolResult, error_response, not_connected_error, success_response};
+use super::{ToolResult, error_response, not_connected_error, parse_viewport_id, success_response};
 use crate::ipc_client::IpcClient;
 use egui_mcp_protocol::MouseButton;
 
 /// Click at specific coordinates
-pub async fn click_at(ipc_client: &IpcClient, x: f32, y: f32, button: Option<&str>) -> ToolResult {
+pub async fn click_at(
+    ipc_client: &IpcClient,
+    x: f32,
+    y: f32,
+    button: Option<&str>,
+    viewport_id: Option<&str>,
+) -> ToolResult {
+    let viewport_id = match parse_viewport_id(viewport_id) {
+        Ok(id) => id,
+        Err(e) => return e,
+    };
+
     if !ipc_client.is_socket_available() {
         return not_connected_error();
     }
@@ -16,19 +27,28 @@ pub async fn click_at(ipc_client: &IpcClient, x: f32, y: f32, button: Option<&st
         _ => MouseButton::Left,
     };
 
-    match ipc_client.click_at(x, y, mouse_button).await {
+    match ipc_client.click_at(x, y, mouse_button, viewport_id).await {
         Ok(()) => success_response(format!("Clicked at ({}, {})", x, y)),
         Err(e) => error_response("click_error", format!("Failed to click: {}", e)),
     }
 }
 
 /// Send keyboard input
-pub async fn keyboard_input(ipc_client: &IpcClient, key: &str) -> ToolResult {
+pub async fn keyboard_input(
+    ipc_client: &IpcClient,
+    key: &str,
+    viewport_id: Option<&str>,
+) -> ToolResult {
+    let viewport_id = match parse_viewport_id(viewport_id) {
+        Ok(id) => id,
+        Err(e) => return e,
+    };
+
     if !ipc_client.is_socket_available() {
         return not_connected_error();
     }
 
-    match ipc_client.keyboard_input(key).await {
+    match ipc_client.keyboard_input(key, viewport_id).await {
         Ok(()) => success_response(format!("Sent key: {}", key)),
         Err(e) => error_response(
             "keyboard_error",
@@ -44,7 +64,13 @@ pub async fn scroll(
     y: f32,
     delta_x: Option<f32>,
     delta_y: Option<f32>,
+    viewport_id: Option<&str>,
 ) -> ToolResult {
+    let viewport_id = match parse_viewport_id(viewport_id) {
+        Ok(id) => id,
+        Err(e) => return e,
+    };
+
     if !ipc_client.is_socket_available() {
         return not_connected_error();
     }
@@ -52,7 +78,7 @@ pub async fn scroll(
     let dx = delta_x.unwrap_or(0.0);
     let dy = delta_y.unwrap_or(0.0);
 
-    match ipc_client.scroll(x, y, dx, dy).await {
+    match ipc_client.scroll(x, y, dx, dy, viewport_id).await {
         Ok(()) => success_response(format!(
             "Scrolled at ({}, {}) with delta ({}, {})",
             x, y, dx, dy
@@ -62,12 +88,22 @@ pub async fn scroll(
 }
 
 /// Move mouse to specific coordinates (hover)
-pub async fn hover(ipc_client: &IpcClient, x: f32, y: f32) -> ToolResult {
+pub async fn hover(
+    ipc_client: &IpcClient,
+    x: f32,
+    y: f32,
+    viewport_id: Option<&str>,
+) -> ToolResult {
+    let viewport_id = match parse_viewport_id(viewport_id) {
+        Ok(id) => id,
+        Err(e) => return e,
+    };
+
     if !ipc_client.is_socket_available() {
         return not_connected_error();
     }
 
-    match ipc_client.move_mouse(x, y).await {
+    match ipc_client.move_mouse(x, y, viewport_id).await {
         Ok(()) => success_response(format!("Moved mouse to ({}, {})", x, y)),
         Err(e) => error_response("hover_error", format!("Failed to move mouse: {}", e)),
     }
@@ -81,7 +117,13 @@ pub async fn drag(
     end_x: f32,
     end_y: f32,
     button: Option<&str>,
+    viewport_id: Option<&str>,
 ) -> ToolResult {
+    let viewport_id = match parse_viewport_id(viewport_id) {
+        Ok(id) => id,
+        Err(e) => return e,
+    };
+
     if !ipc_client.is_socket_available() {
         return not_connected_error();
     }
@@ -93,7 +135,7 @@ pub async fn drag(
     };
 
     match ipc_client
-        .drag(start_x, start_y, end_x, end_y, mouse_button)
+        .drag(start_x, start_y, end_x, end_y, mouse_button, viewport_id)
         .await
     {
         Ok(()) => success_response(format!(
@@ -110,7 +152,13 @@ pub async fn double_click(
     x: f32,
     y: f32,
     button: Option<&str>,
+    viewport_id: Option<&str>,
 ) -> ToolResult {
+    let viewport_id = match parse_viewport_id(viewport_id) {
+        Ok(id) => id,
+        Err(e) => return e,
+    };
+
     if !ipc_client.is_socket_available() {
         return not_connected_error();
     }
@@ -121,7 +169,10 @@ pub async fn double_click(
         _ => MouseButton::Left,
     };
 
-    match ipc_client.double_click(x, y, mouse_button).await {
+    match ipc_client
+        .double_click(x, y, mouse_button, viewport_id)
+        .await
+    {
         Ok(()) => success_response(format!("Double clicked at ({}, {})", x, y)),
         Err(e) => error_response(
             "double_click_error",
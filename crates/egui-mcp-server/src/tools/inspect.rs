@@ -0,0 +1,69 @@
+//! Context inspection tool implementations
+
+use super::{ToolResult, error_response, not_connected_error, parse_viewport_id};
+use crate::ipc_client::IpcClient;
+use egui_mcp_protocol::WidgetRef;
+use serde_json::{Value, json};
+
+/// Inspect egui `Context` internals: focus, hover, open popups and layer order
+pub async fn inspect_context(ipc_client: &IpcClient, viewport_id: Option<&str>) -> ToolResult {
+    let viewport_id = match parse_viewport_id(viewport_id) {
+        Ok(id) => id,
+        Err(e) => return e,
+    };
+
+    if !ipc_client.is_socket_available() {
+        return not_connected_error();
+    }
+
+    match ipc_client.inspect_context(viewport_id).await {
+        Ok(info) => {
+            let layers: Vec<_> = info
+                .layers
+                .iter()
+                .map(|l| {
+                    json!({
+                        "id": l.id.to_string(),
+                        "order": l.order,
+                        "visible": l.visible,
+                        "rect": l.rect,
+                        "parent": l.parent.map(|p| p.to_string())
+                    })
+                })
+                .collect();
+            json!({
+                "viewport_id": info.viewport_id.to_string(),
+                "focused": info.focused.as_ref().map(widget_json),
+                "hovered": info.hovered.iter().map(widget_json).collect::<Vec<_>>(),
+                "dragged": info.dragged.as_ref().map(widget_json),
+                "clicked": info.clicked.as_ref().map(widget_json),
+                "pointer": {
+                    "latest_pos": info.pointer.latest_pos,
+                    "hover_pos": info.pointer.hover_pos,
+                    "press_origin": info.pointer.press_origin,
+                    "primary_down": info.pointer.primary_down,
+                    "secondary_down": info.pointer.secondary_down,
+                    "middle_down": info.pointer.middle_down,
+                    "layer_under_pointer": info.pointer.layer_under_pointer.map(|l| l.to_string())
+                },
+                "any_popup_open": info.any_popup_open,
+                "wants_pointer_input": info.wants_pointer_input,
+                "wants_keyboard_input": info.wants_keyboard_input,
+                "layers": layers
+            })
+            .to_string()
+        }
+        Err(e) => error_response("ipc_error", format!("Failed to inspect context: {}", e)),
+    }
+}
+
+/// Convert a widget reference to JSON (IDs as strings to avoid precision loss)
+fn widget_json(widget: &WidgetRef) -> Value {
+    json!({
+        "id": widget.id.to_string(),
+        "rect": widget.rect,
+        "layer_id": widget.layer_id.map(|l| l.to_string()),
+        "layer_order": widget.layer_order,
+        "enabled": widget.enabled
+    })
+}
@@ -1,21 +1,25 @@
 //! Logging tool implementations
 
-use super::{ToolResult, error_response, success_response};
-use crate::ipc_client::IpcClient;
+use super::{ToolResult, error_response, not_connected_error, success_response};
+use crate::ipc_client::{IpcClient, LogFilter};
+use crate::requests::GetLogsRequest;
 use serde_json::json;
 
 /// Get recent log entries from the egui application
-pub async fn get_logs(
-    ipc_client: &IpcClient,
-    level: Option<&str>,
-    limit: Option<usize>,
-) -> ToolResult {
-    match ipc_client
-        .get_logs(level.map(|s| s.to_string()), limit)
-        .await
-    {
-        Ok(logs) => json!({
+pub async fn get_logs(ipc_client: &IpcClient, req: GetLogsRequest) -> ToolResult {
+    let filter = LogFilter {
+        level: req.level,
+        limit: req.limit,
+        target: req.target,
+        target_regex: req.target_regex,
+        message_regex: req.message_regex,
+        since: req.since,
+        history: req.history.unwrap_or(false),
+    };
+    match ipc_client.get_logs(filter).await {
+        Ok((logs, cursor)) => json!({
             "count": logs.len(),
+            "cursor": cursor,
             "logs": logs
         })
         .to_string(),
@@ -23,6 +27,23 @@ pub async fn get_logs(
     }
 }
 
+/// Change which events the egui application captures in its log buffer
+pub async fn set_log_filter(ipc_client: &IpcClient, directives: String) -> ToolResult {
+    if !ipc_client.is_socket_available() {
+        return not_connected_error();
+    }
+
+    let message = if directives.trim().is_empty() {
+        "Log filter removed; all levels are captured".to_string()
+    } else {
+        format!("Log filter set to '{}'", directives)
+    };
+    match ipc_client.set_log_filter(directives).await {
+        Ok(()) => success_response(message),
+        Err(e) => error_response("ipc_error", format!("Failed to set log filter: {}", e)),
+    }
+}
+
 /// Clear the log buffer in the egui application
 pub async fn clear_logs(ipc_client: &IpcClient) -> ToolResult {
     match ipc_client.clear_logs().await {
@@ -0,0 +1,274 @@
+//! Ignore masks and per-region thresholds for screenshot comparison
+//!
+//! Masks and regions are rectangles in screenshot pixels or the bounds of
+//! elements (by ID, or every element matching a label substring and/or role)
+//! from the UI tree. Masked pixels are made equal in both images before
+//! comparing, so clocks, spinners and live data do not count as differences.
+
+use super::tree::{fill_bounds, load_tree};
+use super::{ToolResult, error_response, parse_element_id};
+use crate::ipc_client::IpcClient;
+use crate::requests::{RegionThreshold, ScreenshotArea};
+use egui_mcp_protocol::{Rect, UiTree};
+use serde_json::json;
+
+/// Color of masked pixels in diff images
+const MASK_COLOR: image::Rgba<u8> = image::Rgba([0, 128, 255, 255]);
+
+/// A region with its resolved rectangles and similarity threshold
+pub struct ResolvedRegion {
+    pub name: String,
+    pub rects: Vec<Rect>,
+    pub threshold: f64,
+}
+
+/// Resolves areas to rectangles, loading the UI tree only when an area selects elements
+pub struct AreaResolver<'a> {
+    app_name: &'a str,
+    ipc_client: &'a IpcClient,
+    tree: Option<UiTree>,
+}
+
+impl<'a> AreaResolver<'a> {
+    pub fn new(app_name: &'a str, ipc_client: &'a IpcClient) -> Self {
+        Self {
+            app_name,
+            ipc_client,
+            tree: None,
+        }
+    }
+
+    /// Rectangles of all masks
+    pub async fn masks(&mut self, masks: &[ScreenshotArea]) -> Result<Vec<Rect>, ToolResult> {
+        let mut rects = Vec::new();
+        for mask in masks {
+            rects.extend(self.resolve(mask).await?);
+        }
+        Ok(rects)
+    }
+
+    /// Regions with their rectangles; unnamed regions are numbered
+    pub async fn regions(
+        &mut self,
+        regions: &[RegionThreshold],
+    ) -> Result<Vec<ResolvedRegion>, ToolResult> {
+        let mut resolved = Vec::new();
+        for (index, region) in regions.iter().enumerate() {
+            resolved.push(ResolvedRegion {
+                name: region
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| format!("region_{}", index)),
+                rects: self.resolve(&region.area).await?,
+                threshold: region.threshold,
+            });
+        }
+        Ok(resolved)
+    }
+
+    async fn resolve(&mut self, area: &ScreenshotArea) -> Result<Vec<Rect>, ToolResult> {
+        if let (Some(x), Some(y), Some(width), Some(height)) =
+            (area.x, area.y, area.width, area.height)
+        {
+            return Ok(vec![Rect {
+                x,
+                y,
+                width,
+                height,
+            }]);
+        }
+        if area.element_id.is_none() && area.label.is_none() && area.role.is_none() {
+            return Err(error_response(
+                "invalid_area",
+                "An area needs x, y, width and height, or element_id, label or role",
+            ));
+        }
+
+        let tree = self.tree().await?;
+        let mut nodes = match &area.element_id {
+            Some(id) => {
+                let id = parse_element_id(id)?;
+                tree.get(id).cloned().into_iter().collect()
+            }
+            None => tree
+                .nodes
+                .iter()
+                .filter(|node| {
+                    area.label.as_ref().is_none_or(|label| {
+                        node.label
+                            .as_ref()
+                            .is_some_and(|l| l.contains(label.as_str()))
+                    }) && area.role.as_ref().is_none_or(|role| node.role == *role)
+                })
+                .cloned()
+                .collect::<Vec<_>>(),
+        };
+        fill_bounds(self.app_name, &mut nodes).await;
+        let rects: Vec<Rect> = nodes.iter().filter_map(|node| node.bounds).collect();
+        if rects.is_empty() {
+            return Err(error_response(
+                "area_not_found",
+                format!("No element with bounds matches the area {:?}", area),
+            ));
+        }
+        Ok(rects)
+    }
+
+    async fn tree(&mut self) -> Result<&UiTree, ToolResult> {
+        if self.tree.is_none() {
+            let tree = load_tree(self.app_name, self.ipc_client)
+                .await?
+                .ok_or_else(|| {
+                    error_response(
+                        "not_available",
+                        "Element areas need the UI tree, but the app was not found",
+                    )
+                })?;
+            self.tree = Some(tree);
+        }
+        Ok(self.tree.as_ref().expect("tree was just loaded"))
+    }
+}
+
+/// Pixel bounds `(x, y, width, height)` of a rectangle clamped to the image
+fn pixel_bounds(rect: &Rect, (width, height): (u32, u32)) -> Option<(u32, u32, u32, u32)> {
+    let min_x = (rect.x.floor().max(0.0) as u32).min(width);
+    let min_y = (rect.y.floor().max(0.0) as u32).min(height);
+    let max_x = ((rect.x + rect.width).ceil().max(0.0) as u32).min(width);
+    let max_y = ((rect.y + rect.height).ceil().max(0.0) as u32).min(height);
+    (max_x > min_x && max_y > min_y).then(|| (min_x, min_y, max_x - min_x, max_y - min_y))
+}
+
+/// Fill the masked pixels of an image with a color
+pub fn fill_masks<P: image::Pixel>(
+    img: &mut image::ImageBuffer<P, Vec<P::Subpixel>>,
+    masks: &[Rect],
+    color: P,
+) {
+    let dimensions = img.dimensions();
+    for mask in masks {
+        if let Some((x, y, width, height)) = pixel_bounds(mask, dimensions) {
+            for py in y..y + height {
+                for px in x..x + width {
+                    img.put_pixel(px, py, color);
+                }
+            }
+        }
+    }
+}
+
+/// Blank the masked pixels of a grayscale image for comparison
+pub fn mask_gray(img: &mut image::GrayImage, masks: &[Rect]) {
+    fill_masks(img, masks, image::Luma([0]));
+}
+
+/// Mark the masked pixels of a diff image
+pub fn mark_masks(diff: &mut image::RgbaImage, masks: &[Rect]) {
+    fill_masks(diff, masks, MASK_COLOR);
+}
+
+/// Score each region of two (masked) grayscale images of the same size
+///
+/// Returns the per-region report and the names of the regions below their
+/// threshold.
+pub fn score_regions(
+    gray_a: &image::GrayImage,
+    gray_b: &image::GrayImage,
+    regions: &[ResolvedRegion],
+    algorithm: &str,
+) -> Result<(Vec<serde_json::Value>, Vec<String>), ToolResult> {
+    let mut report = Vec::new();
+    let mut failed = Vec::new();
+    for region in regions {
+        // A region made of several elements passes only if each of them does
+        let mut score: Option<f64> = None;
+        for rect in &region.rects {
+            let Some((x, y, width, height)) = pixel_bounds(rect, gray_a.dimensions()) else {
+                continue;
+            };
+            let crop_a = image::imageops::crop_imm(gray_a, x, y, width, height).to_image();
+            let crop_b = image::imageops::crop_imm(gray_b, x, y, width, height).to_image();
+            let rect_score = super::screenshot::similarity(&crop_a, &crop_b, algorithm)?;
+            score = Some(score.map_or(rect_score, |s| s.min(rect_score)));
+        }
+
+        let passed = score.is_some_and(|s| s >= region.threshold);
+        if !passed {
+            failed.push(region.name.clone());
+        }
+        let mut entry = json!({
+            "name": region.name,
+            "rects": region.rects,
+            "score": score,
+            "threshold": region.threshold,
+            "passed": passed
+        });
+        if score.is_none() {
+            entry["error"] = json!("Region is outside the image");
+        }
+        report.push(entry);
+    }
+    Ok((report, failed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: f32, y: f32, width: f32, height: f32) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn test_pixel_bounds_clamps_to_image() {
+        assert_eq!(
+            pixel_bounds(&rect(1.5, 2.0, 3.0, 4.0), (10, 10)),
+            Some((1, 2, 4, 4))
+        );
+        assert_eq!(
+            pixel_bounds(&rect(-5.0, 8.0, 10.0, 10.0), (10, 10)),
+            Some((0, 8, 5, 2))
+        );
+        assert_eq!(pixel_bounds(&rect(20.0, 0.0, 5.0, 5.0), (10, 10)), None);
+    }
+
+    #[test]
+    fn test_masked_regions_pass_and_unmasked_fail() {
+        let a = image::GrayImage::from_pixel(32, 16, image::Luma([200]));
+        let mut b = a.clone();
+        // A "clock" in the left half changes
+        for y in 0..8 {
+            for x in 0..8 {
+                b.put_pixel(x, y, image::Luma([0]));
+            }
+        }
+        let regions = vec![
+            ResolvedRegion {
+                name: "left".to_string(),
+                rects: vec![rect(0.0, 0.0, 16.0, 16.0)],
+                threshold: 0.99,
+            },
+            ResolvedRegion {
+                name: "right".to_string(),
+                rects: vec![rect(16.0, 0.0, 16.0, 16.0)],
+                threshold: 0.99,
+            },
+        ];
+
+        let (report, failed) = score_regions(&a, &b, &regions, "rms").unwrap();
+        assert_eq!(failed, vec!["left".to_string()]);
+        assert_eq!(report[1]["passed"], true);
+
+        let clock = [rect(0.0, 0.0, 8.0, 8.0)];
+        let (mut a, mut b) = (a, b);
+        mask_gray(&mut a, &clock);
+        mask_gray(&mut b, &clock);
+        let (_, failed) = score_regions(&a, &b, &regions, "rms").unwrap();
+        assert!(failed.is_empty());
+    }
+}
@@ -4,19 +4,28 @@
 //! The main.rs file contains thin wrappers that delegate to these implementations.
 
 pub mod action;
+pub mod annotate;
+pub mod baseline;
 pub mod basic;
 pub mod component;
 pub mod highlight;
 pub mod input;
+pub mod inspect;
 pub mod logging;
+pub mod masks;
 pub mod perf;
+pub mod recording;
+pub mod screen_recording;
 pub mod screenshot;
 pub mod selection;
 pub mod snapshot;
 pub mod state;
 pub mod text;
+pub mod time;
 pub mod tree;
 pub mod value;
+pub mod vector;
+pub mod viewport;
 pub mod wait;
 
 use serde_json::json;
@@ -73,3 +82,16 @@ pub fn parse_element_id(id: &str) -> Result<u64, ToolResult> {
     id.parse::<u64>()
         .map_err(|_| error_response("invalid_id", format!("Invalid element ID: {}", id)))
 }
+
+/// Helper to parse an optional viewport ID from string
+pub fn parse_viewport_id(id: Option<&str>) -> Result<Option<u64>, ToolResult> {
+    id.map(|id| {
+        id.parse::<u64>().map_err(|_| {
+            error_response(
+                "invalid_viewport_id",
+                format!("Invalid viewport ID: {}", id),
+            )
+        })
+    })
+    .transpose()
+}
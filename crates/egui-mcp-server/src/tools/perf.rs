@@ -1,8 +1,11 @@
 //! Performance tool implementations
 
-use super::{ToolResult, error_response, success_response};
+use super::{ToolResult, error_response, not_connected_error, parse_viewport_id, success_response};
+use crate::constants::DEFAULT_LATENCY_TIMEOUT_MS;
 use crate::ipc_client::IpcClient;
-use serde_json::json;
+use crate::requests::{AssertPerfRequest, MeasureLatencyRequest};
+use egui_mcp_protocol::{MouseButton, PerfReport, Request};
+use serde_json::{Value, json};
 
 /// Get current frame statistics
 pub async fn get_frame_stats(ipc_client: &IpcClient) -> ToolResult {
@@ -10,22 +13,59 @@ pub async fn get_frame_stats(ipc_client: &IpcClient) -> ToolResult {
         Ok(stats) => json!({
             "fps": stats.fps,
             "frame_time_ms": stats.frame_time_ms,
-            "sample_count": stats.sample_count
+            "sample_count": stats.sample_count,
+            "paint": stats.paint
         })
         .to_string(),
         Err(e) => error_response("ipc_error", format!("Failed to get frame stats: {}", e)),
     }
 }
 
+/// Get heap and texture memory statistics
+pub async fn get_memory_stats(ipc_client: &IpcClient) -> ToolResult {
+    match ipc_client.get_memory_stats().await {
+        Ok(stats) => {
+            let mut result = json!({
+                "heap": stats.heap,
+                "textures": stats.textures
+            });
+            if stats.heap.is_none() {
+                result["hint"] = json!(
+                    "Heap statistics require the alloc-tracking feature of egui-mcp-client with McpAllocator as global allocator"
+                );
+            }
+            result.to_string()
+        }
+        Err(e) => error_response("ipc_error", format!("Failed to get memory stats: {}", e)),
+    }
+}
+
 /// Start recording performance data
-pub async fn start_perf_recording(ipc_client: &IpcClient, duration_ms: Option<u64>) -> ToolResult {
+pub async fn start_perf_recording(
+    ipc_client: &IpcClient,
+    duration_ms: Option<u64>,
+    profile: Option<bool>,
+    budgets_ms: Option<Vec<f32>>,
+) -> ToolResult {
     let duration = duration_ms.unwrap_or(0);
-    match ipc_client.start_perf_recording(duration).await {
+    let profile = profile.unwrap_or(false);
+    let budgets_ms = budgets_ms.unwrap_or_default();
+    if let Some(budget) = budgets_ms.iter().find(|b| !b.is_finite() || **b <= 0.0) {
+        return error_response(
+            "invalid_budget",
+            format!("Frame time budgets must be positive, got {}", budget),
+        );
+    }
+    match ipc_client
+        .start_perf_recording(duration, profile, budgets_ms)
+        .await
+    {
         Ok(()) => {
+            let mode = if profile { "Profiling" } else { "Recording" };
             if duration > 0 {
-                success_response(format!("Recording started for {}ms", duration))
+                success_response(format!("{} started for {}ms", mode, duration))
             } else {
-                success_response("Recording started (call get_perf_report to stop)")
+                success_response(format!("{} started (call get_perf_report to stop)", mode))
             }
         }
         Err(e) => error_response("ipc_error", format!("Failed to start recording: {}", e)),
@@ -35,17 +75,7 @@ pub async fn start_perf_recording(ipc_client: &IpcClient, duration_ms: Option<u6
 /// Get performance report (stops recording)
 pub async fn get_perf_report(ipc_client: &IpcClient) -> ToolResult {
     match ipc_client.get_perf_report().await {
-        Ok(Some(report)) => json!({
-            "duration_ms": report.duration_ms,
-            "total_frames": report.total_frames,
-            "avg_fps": report.avg_fps,
-            "avg_frame_time_ms": report.avg_frame_time_ms,
-            "min_frame_time_ms": report.min_frame_time_ms,
-            "max_frame_time_ms": report.max_frame_time_ms,
-            "p95_frame_time_ms": report.p95_frame_time_ms,
-            "p99_frame_time_ms": report.p99_frame_time_ms
-        })
-        .to_string(),
+        Ok(Some(report)) => report_json(&report).to_string(),
         Ok(None) => error_response(
             "no_data",
             "No performance recording active or no frames recorded",
@@ -56,3 +86,341 @@ pub async fn get_perf_report(ipc_client: &IpcClient) -> ToolResult {
         ),
     }
 }
+
+/// Stop recording and check the performance report against the given limits
+pub async fn assert_perf(ipc_client: &IpcClient, limits: &AssertPerfRequest) -> ToolResult {
+    let report = match ipc_client.get_perf_report().await {
+        Ok(Some(report)) => report,
+        Ok(None) => {
+            return error_response(
+                "no_data",
+                "No performance recording active or no frames recorded",
+            );
+        }
+        Err(e) => {
+            return error_response(
+                "ipc_error",
+                format!("Failed to get performance report: {}", e),
+            );
+        }
+    };
+
+    let checks = match check_perf_limits(&report, limits) {
+        Ok(checks) => checks,
+        Err(e) => return e,
+    };
+    if checks.is_empty() {
+        return error_response(
+            "no_checks",
+            "No limits given. Set at least one of the max_*/min_* parameters",
+        );
+    }
+
+    let failed = checks.iter().filter(|c| c["passed"] == false).count();
+    if failed == 0 {
+        json!({
+            "success": true,
+            "message": format!("All {} performance checks passed", checks.len()),
+            "checks": checks,
+            "report": report_json(&report)
+        })
+        .to_string()
+    } else {
+        json!({
+            "error": "perf_budget_exceeded",
+            "message": format!("{} of {} performance checks failed", failed, checks.len()),
+            "checks": checks,
+            "report": report_json(&report)
+        })
+        .to_string()
+    }
+}
+
+/// Evaluate each given limit against the report
+///
+/// Returns one `{name, limit, actual, passed}` object per limit, or an error
+/// response if `budget_ms` was not recorded.
+fn check_perf_limits(
+    report: &PerfReport,
+    limits: &AssertPerfRequest,
+) -> Result<Vec<Value>, ToolResult> {
+    let mut checks = Vec::new();
+    let mut check_max = |name: &str, limit: Option<f32>, actual: f32| {
+        if let Some(limit) = limit {
+            checks.push(json!({
+                "name": name,
+                "limit": limit,
+                "actual": actual,
+                "passed": actual <= limit
+            }));
+        }
+    };
+    check_max(
+        "max_avg_frame_time_ms",
+        limits.max_avg_frame_time_ms,
+        report.avg_frame_time_ms,
+    );
+    check_max(
+        "max_p95_frame_time_ms",
+        limits.max_p95_frame_time_ms,
+        report.p95_frame_time_ms,
+    );
+    check_max(
+        "max_p99_frame_time_ms",
+        limits.max_p99_frame_time_ms,
+        report.p99_frame_time_ms,
+    );
+    check_max(
+        "max_frame_time_ms",
+        limits.max_frame_time_ms,
+        report.max_frame_time_ms,
+    );
+
+    if let Some(limit) = limits.min_avg_fps {
+        checks.push(json!({
+            "name": "min_avg_fps",
+            "limit": limit,
+            "actual": report.avg_fps,
+            "passed": report.avg_fps >= limit
+        }));
+    }
+
+    if let Some(limit) = limits.max_frames_over_budget {
+        let budget_ms = limits.budget_ms.unwrap_or(report.jank_budget_ms);
+        let Some(budget) = report
+            .budgets
+            .iter()
+            .find(|b| (b.budget_ms - budget_ms).abs() < 0.01)
+        else {
+            return Err(error_response(
+                "unknown_budget",
+                format!(
+                    "Budget {}ms was not recorded. Pass it in budgets_ms to start_perf_recording",
+                    budget_ms
+                ),
+            ));
+        };
+        checks.push(json!({
+            "name": "max_frames_over_budget",
+            "budget_ms": budget.budget_ms,
+            "limit": limit,
+            "actual": budget.frames_over,
+            "passed": budget.frames_over <= limit
+        }));
+    }
+
+    if let Some(limit) = limits.max_jank_streak_frames {
+        let longest = report.jank_streaks.first().map_or(0, |s| s.frames);
+        checks.push(json!({
+            "name": "max_jank_streak_frames",
+            "budget_ms": report.jank_budget_ms,
+            "limit": limit,
+            "actual": longest,
+            "passed": longest <= limit
+        }));
+    }
+
+    Ok(checks)
+}
+
+/// Convert a performance report into the JSON returned by the perf tools
+fn report_json(report: &PerfReport) -> Value {
+    json!({
+        "duration_ms": report.duration_ms,
+        "total_frames": report.total_frames,
+        "avg_fps": report.avg_fps,
+        "avg_frame_time_ms": report.avg_frame_time_ms,
+        "min_frame_time_ms": report.min_frame_time_ms,
+        "max_frame_time_ms": report.max_frame_time_ms,
+        "p95_frame_time_ms": report.p95_frame_time_ms,
+        "p99_frame_time_ms": report.p99_frame_time_ms,
+        "histogram": report.histogram,
+        "budgets": report.budgets,
+        "jank_budget_ms": report.jank_budget_ms,
+        "jank_streaks": report.jank_streaks,
+        "paint": report.paint,
+        "memory": report.memory,
+        "textures": report.textures
+    })
+}
+
+/// Inject an input and measure how long it takes to change the UI output
+pub async fn measure_latency(ipc_client: &IpcClient, req: &MeasureLatencyRequest) -> ToolResult {
+    let viewport_id = match parse_viewport_id(req.viewport_id.as_deref()) {
+        Ok(id) => id,
+        Err(e) => return e,
+    };
+    let input = match latency_input(req, viewport_id) {
+        Ok(input) => input,
+        Err(e) => return e,
+    };
+
+    if !ipc_client.is_socket_available() {
+        return not_connected_error();
+    }
+
+    let timeout_ms = req.timeout_ms.unwrap_or(DEFAULT_LATENCY_TIMEOUT_MS);
+    match ipc_client.measure_latency(input, timeout_ms).await {
+        Ok(report) if report.changed => json!({
+            "success": true,
+            "changed": true,
+            "frames": report.frames,
+            "latency_ms": report.latency_ms,
+            "consume_ms": report.consume_ms,
+            "consumed_frame": report.consumed_frame,
+            "changed_frame": report.changed_frame
+        })
+        .to_string(),
+        Ok(report) => {
+            let message = if report.consumed_frame.is_some() {
+                format!(
+                    "The input was consumed but the output did not change within {}ms",
+                    timeout_ms
+                )
+            } else {
+                format!(
+                    "The input was not consumed within {}ms: the egui app must call take_viewport_inputs_sync() in raw_input_hook",
+                    timeout_ms
+                )
+            };
+            json!({
+                "error": "no_change",
+                "message": message,
+                "consume_ms": report.consume_ms,
+                "consumed_frame": report.consumed_frame
+            })
+            .to_string()
+        }
+        Err(e) => error_response("ipc_error", format!("Failed to measure latency: {}", e)),
+    }
+}
+
+/// Build the input request for measure_latency
+fn latency_input(
+    req: &MeasureLatencyRequest,
+    viewport_id: Option<u64>,
+) -> Result<Request, ToolResult> {
+    let missing = |field: &str| {
+        error_response(
+            "invalid_params",
+            format!("'{}' is required for action '{}'", field, req.action),
+        )
+    };
+    let button = match req.button.as_deref() {
+        Some("right") => MouseButton::Right,
+        Some("middle") => MouseButton::Middle,
+        _ => MouseButton::Left,
+    };
+    let position = || {
+        Ok::<_, ToolResult>((
+            req.x.ok_or_else(|| missing("x"))?,
+            req.y.ok_or_else(|| missing("y"))?,
+        ))
+    };
+
+    let input = match req.action.as_str() {
+        "click" => {
+            let (x, y) = position()?;
+            Request::ClickAt {
+                x,
+                y,
+                button,
+                viewport_id,
+            }
+        }
+        "double_click" => {
+            let (x, y) = position()?;
+            Request::DoubleClick {
+                x,
+                y,
+                button,
+                viewport_id,
+            }
+        }
+        "hover" => {
+            let (x, y) = position()?;
+            Request::MoveMouse { x, y, viewport_id }
+        }
+        "key" => Request::KeyboardInput {
+            key: req.key.clone().ok_or_else(|| missing("key"))?,
+            viewport_id,
+        },
+        "scroll" => {
+            let (x, y) = position()?;
+            Request::Scroll {
+                x,
+                y,
+                delta_x: req.delta_x.unwrap_or(0.0),
+                delta_y: req.delta_y.unwrap_or(0.0),
+                viewport_id,
+            }
+        }
+        "drag" => {
+            let (start_x, start_y) = position()?;
+            Request::Drag {
+                start_x,
+                start_y,
+                end_x: req.end_x.ok_or_else(|| missing("end_x"))?,
+                end_y: req.end_y.ok_or_else(|| missing("end_y"))?,
+                button,
+                viewport_id,
+            }
+        }
+        other => {
+            return Err(error_response(
+                "invalid_action",
+                format!(
+                    "Unknown action '{}'. Use 'click', 'double_click', 'hover', 'key', 'scroll' or 'drag'",
+                    other
+                ),
+            ));
+        }
+    };
+    Ok(input)
+}
+
+/// Export the span timings of the last profiling recording as a Chrome trace file
+pub async fn export_perf_trace(ipc_client: &IpcClient, path: Option<&str>) -> ToolResult {
+    match ipc_client.export_perf_trace().await {
+        Ok((events, dropped_events)) => {
+            let span_count = events.iter().filter(|e| e.ph == "X").count();
+            if span_count == 0 {
+                return error_response(
+                    "no_data",
+                    "No trace data. Call start_perf_recording with profile=true first",
+                );
+            }
+
+            let file_path = match path {
+                Some(p) => p.to_string(),
+                None => {
+                    let timestamp = std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .map(|d| d.as_millis())
+                        .unwrap_or(0);
+                    format!("/tmp/egui-mcp-trace-{}.json", timestamp)
+                }
+            };
+
+            let trace = json!({
+                "traceEvents": events,
+                "displayTimeUnit": "ms"
+            });
+            match std::fs::write(&file_path, trace.to_string()) {
+                Ok(()) => json!({
+                    "success": true,
+                    "file_path": file_path,
+                    "event_count": span_count,
+                    "dropped_events": dropped_events,
+                    "hint": "Open the file in https://ui.perfetto.dev or chrome://tracing"
+                })
+                .to_string(),
+                Err(e) => error_response(
+                    "file_write_error",
+                    format!("Failed to write trace file: {}", e),
+                ),
+            }
+        }
+        Err(e) => error_response("ipc_error", format!("Failed to export trace: {}", e)),
+    }
+}
@@ -0,0 +1,154 @@
+//! Input recording tool implementations (start/stop_input_recording, replay_recording)
+
+use super::{ToolResult, error_response, not_connected_error};
+use crate::ipc_client::IpcClient;
+use egui_mcp_protocol::{InputRecording, Request};
+use serde_json::json;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+/// Last recording stopped in this session
+pub type RecordingStore = std::sync::Arc<RwLock<Option<InputRecording>>>;
+
+/// Start recording real user input in the egui application
+pub async fn start_input_recording(ipc_client: &IpcClient) -> ToolResult {
+    if !ipc_client.is_socket_available() {
+        return not_connected_error();
+    }
+
+    match ipc_client.start_input_recording().await {
+        Ok(()) => json!({
+            "success": true,
+            "message": "Recording user input. Reproduce the issue in the app, then call stop_input_recording."
+        })
+        .to_string(),
+        Err(e) => error_response("ipc_error", format!("Failed to start recording: {}", e)),
+    }
+}
+
+/// Stop recording, keep the recording for replay and optionally save it as JSON
+pub async fn stop_input_recording(
+    ipc_client: &IpcClient,
+    recordings: &RecordingStore,
+    save_path: Option<&str>,
+) -> ToolResult {
+    if !ipc_client.is_socket_available() {
+        return not_connected_error();
+    }
+
+    let recording = match ipc_client.stop_input_recording().await {
+        Ok(recording) => recording,
+        Err(e) => return error_response("ipc_error", format!("Failed to stop recording: {}", e)),
+    };
+
+    if let Some(path) = save_path {
+        let data = match serde_json::to_string_pretty(&recording) {
+            Ok(data) => data,
+            Err(e) => return error_response("serialize_error", e.to_string()),
+        };
+        if let Err(e) = std::fs::write(path, data) {
+            return error_response("write_error", format!("Failed to write {}: {}", path, e));
+        }
+    }
+
+    let result = json!({
+        "success": true,
+        "steps": recording.steps.len(),
+        "duration_ms": recording.duration_ms,
+        "saved_to": save_path,
+        "recording": recording
+    });
+    *recordings.write().unwrap() = Some(recording);
+    result.to_string()
+}
+
+/// Replay a recording by injecting its steps with their original timing
+///
+/// The recording is taken from `script` (JSON), `path` (JSON file) or the
+/// last recording stopped in this session, in that order.
+pub async fn replay_recording(
+    ipc_client: &IpcClient,
+    recordings: &RecordingStore,
+    path: Option<&str>,
+    script: Option<&str>,
+    speed: Option<f32>,
+) -> ToolResult {
+    let speed = speed.unwrap_or(1.0);
+    if !speed.is_finite() || speed <= 0.0 {
+        return error_response("invalid_speed", "speed must be a positive number");
+    }
+
+    let recording = match load_recording(recordings, path, script) {
+        Ok(recording) => recording,
+        Err(e) => return e,
+    };
+    if let Some(index) = recording
+        .steps
+        .iter()
+        .position(|step| !is_input_request(&step.request))
+    {
+        return error_response(
+            "invalid_script",
+            format!("Step {} is not an input request", index),
+        );
+    }
+
+    if !ipc_client.is_socket_available() {
+        return not_connected_error();
+    }
+
+    let started = Instant::now();
+    for (index, step) in recording.steps.iter().enumerate() {
+        let due = Duration::from_secs_f64(step.at_ms as f64 / 1000.0 / speed as f64);
+        tokio::time::sleep(due.saturating_sub(started.elapsed())).await;
+
+        if let Err(e) = ipc_client.send_input(&step.request).await {
+            return error_response(
+                "replay_error",
+                format!("Failed to replay step {}: {}", index, e),
+            );
+        }
+    }
+
+    json!({
+        "success": true,
+        "steps": recording.steps.len(),
+        "duration_ms": started.elapsed().as_millis() as u64
+    })
+    .to_string()
+}
+
+fn load_recording(
+    recordings: &RecordingStore,
+    path: Option<&str>,
+    script: Option<&str>,
+) -> Result<InputRecording, ToolResult> {
+    let data = match (script, path) {
+        (Some(script), _) => script.to_string(),
+        (None, Some(path)) => std::fs::read_to_string(path)
+            .map_err(|e| error_response("read_error", format!("Failed to read {}: {}", path, e)))?,
+        (None, None) => {
+            return recordings.read().unwrap().clone().ok_or_else(|| {
+                error_response(
+                    "no_recording",
+                    "No recording to replay. Pass 'script' or 'path', or record one with start_input_recording/stop_input_recording.",
+                )
+            });
+        }
+    };
+    serde_json::from_str(&data)
+        .map_err(|e| error_response("invalid_script", format!("Invalid recording: {}", e)))
+}
+
+/// Whether the request is one of the inputs a recording may contain
+fn is_input_request(request: &Request) -> bool {
+    matches!(
+        request,
+        Request::ClickAt { .. }
+            | Request::DoubleClick { .. }
+            | Request::Drag { .. }
+            | Request::MoveMouse { .. }
+            | Request::Scroll { .. }
+            | Request::KeyboardInput { .. }
+    )
+}
@@ -0,0 +1,355 @@
+//! Screen recording tool implementations (start/stop_screen_recording)
+//!
+//! The egui app captures screenshots at a fixed rate through its screenshot
+//! path; on stop the server fetches the frames one at a time and encodes
+//! them as an animated GIF or APNG, or writes them as numbered PNGs with
+//! their timestamps.
+
+use super::screenshot::resize_image;
+use super::{ToolResult, error_response, not_connected_error, parse_viewport_id};
+use crate::constants::{
+    DEFAULT_RECORDING_DURATION_MS, DEFAULT_RECORDING_FPS, MAX_RECORDING_DURATION_MS,
+    MAX_RECORDING_FPS,
+};
+use crate::ipc_client::IpcClient;
+use image::RgbaImage;
+use serde_json::json;
+
+/// Delay of the last frame when there is no next frame to measure against
+const LAST_FRAME_DELAY_MS: u64 = 100;
+
+/// A decoded frame with its time since the recording started
+struct Frame {
+    timestamp_ms: u64,
+    image: RgbaImage,
+}
+
+/// Start capturing screenshots of the egui application
+pub async fn start_screen_recording(
+    ipc_client: &IpcClient,
+    fps: Option<f32>,
+    max_duration_ms: Option<u64>,
+    viewport_id: Option<&str>,
+) -> ToolResult {
+    let viewport_id = match parse_viewport_id(viewport_id) {
+        Ok(id) => id,
+        Err(e) => return e,
+    };
+    let fps = fps.unwrap_or(DEFAULT_RECORDING_FPS);
+    if !(fps > 0.0 && fps <= MAX_RECORDING_FPS) {
+        return error_response(
+            "invalid_fps",
+            format!(
+                "fps must be greater than 0 and at most {}",
+                MAX_RECORDING_FPS
+            ),
+        );
+    }
+    let max_duration_ms = max_duration_ms
+        .unwrap_or(DEFAULT_RECORDING_DURATION_MS)
+        .min(MAX_RECORDING_DURATION_MS);
+
+    if !ipc_client.is_socket_available() {
+        return not_connected_error();
+    }
+
+    match ipc_client
+        .start_screen_recording(fps, max_duration_ms, viewport_id)
+        .await
+    {
+        Ok(()) => json!({
+            "success": true,
+            "fps": fps,
+            "max_duration_ms": max_duration_ms,
+            "message": "Recording the screen. Reproduce the issue in the app, then call stop_screen_recording."
+        })
+        .to_string(),
+        Err(e) => error_response(
+            "ipc_error",
+            format!("Failed to start screen recording: {}", e),
+        ),
+    }
+}
+
+/// Stop the screen recording and encode the frames
+///
+/// `format` is "gif" (default), "apng" or "frames" (a directory of numbered
+/// PNGs with a `frames.json` listing their timestamps).
+pub async fn stop_screen_recording(
+    ipc_client: &IpcClient,
+    format: Option<&str>,
+    output_path: Option<&str>,
+    max_width: Option<u32>,
+    scale: Option<f32>,
+) -> ToolResult {
+    let format = format.unwrap_or("gif").to_ascii_lowercase();
+    if !matches!(format.as_str(), "gif" | "apng" | "frames") {
+        return error_response(
+            "invalid_format",
+            format!(
+                "Unknown recording format '{}'. Use 'gif', 'apng' or 'frames'",
+                format
+            ),
+        );
+    }
+    if !ipc_client.is_socket_available() {
+        return not_connected_error();
+    }
+
+    let (count, duration_ms) = match ipc_client.stop_screen_recording().await {
+        Ok(stopped) => stopped,
+        Err(e) => {
+            return error_response(
+                "ipc_error",
+                format!("Failed to stop screen recording: {}", e),
+            );
+        }
+    };
+    let frames = match fetch_frames(ipc_client, max_width, scale).await {
+        Ok(frames) => frames,
+        Err(e) => return e,
+    };
+    if frames.is_empty() {
+        return error_response(
+            "no_frames",
+            "No frames were captured. The app must call handle_screenshot_sync every frame \
+             (or install McpPlugin with software screenshots) and keep running while recording.",
+        );
+    }
+
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let extension = match format.as_str() {
+        "gif" => ".gif",
+        "apng" => ".png",
+        _ => "",
+    };
+    let path = output_path
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("/tmp/egui-mcp-recording-{}{}", timestamp, extension));
+
+    let written = match format.as_str() {
+        "gif" => encode_gif(&frames).and_then(|data| write_file(&path, &data)),
+        "apng" => encode_apng(&frames).and_then(|data| write_file(&path, &data)),
+        _ => write_frames(&path, &frames),
+    };
+    let size_bytes = match written {
+        Ok(size) => size,
+        Err(e) => return e,
+    };
+
+    let (width, height) = frames[0].image.dimensions();
+    json!({
+        "success": true,
+        "format": format,
+        "path": path,
+        "frames": frames.len(),
+        "captured_frames": count,
+        "duration_ms": duration_ms,
+        "width": width,
+        "height": height,
+        "size_bytes": size_bytes
+    })
+    .to_string()
+}
+
+/// Take all frames of the stopped recording from the app, resized as requested
+async fn fetch_frames(
+    ipc_client: &IpcClient,
+    max_width: Option<u32>,
+    scale: Option<f32>,
+) -> Result<Vec<Frame>, ToolResult> {
+    use base64::Engine;
+
+    let mut frames = Vec::new();
+    loop {
+        let frame = ipc_client.take_recorded_frame().await.map_err(|e| {
+            error_response("ipc_error", format!("Failed to get recorded frame: {}", e))
+        })?;
+        let Some(frame) = frame else {
+            return Ok(frames);
+        };
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(&frame.data)
+            .map_err(|e| {
+                error_response("decode_error", format!("Failed to decode base64: {}", e))
+            })?;
+        let image = image::load_from_memory(&bytes)
+            .map_err(|e| error_response("image_error", format!("Failed to load frame: {}", e)))?;
+        frames.push(Frame {
+            timestamp_ms: frame.timestamp_ms,
+            image: resize_image(image, max_width, scale)?.to_rgba8(),
+        });
+    }
+}
+
+/// Display time of each frame in milliseconds (until the next frame)
+fn frame_delays(frames: &[Frame]) -> Vec<u64> {
+    frames
+        .iter()
+        .enumerate()
+        .map(|(i, frame)| match frames.get(i + 1) {
+            Some(next) => next.timestamp_ms.saturating_sub(frame.timestamp_ms).max(1),
+            None => LAST_FRAME_DELAY_MS,
+        })
+        .collect()
+}
+
+/// Frames on a canvas of the first frame's size (the window may have been resized)
+fn canvas_frames(frames: &[Frame]) -> impl Iterator<Item = std::borrow::Cow<'_, RgbaImage>> {
+    let (width, height) = frames[0].image.dimensions();
+    frames.iter().map(move |frame| {
+        if frame.image.dimensions() == (width, height) {
+            std::borrow::Cow::Borrowed(&frame.image)
+        } else {
+            let mut canvas = RgbaImage::new(width, height);
+            image::imageops::replace(&mut canvas, &frame.image, 0, 0);
+            std::borrow::Cow::Owned(canvas)
+        }
+    })
+}
+
+/// Encode the frames as an endlessly looping GIF
+fn encode_gif(frames: &[Frame]) -> Result<Vec<u8>, ToolResult> {
+    use image::codecs::gif::{GifEncoder, Repeat};
+
+    let encode_error = |e: image::ImageError| {
+        error_response("encode_error", format!("Failed to encode GIF: {}", e))
+    };
+    let mut data = Vec::new();
+    {
+        let mut encoder = GifEncoder::new_with_speed(&mut data, 10);
+        encoder.set_repeat(Repeat::Infinite).map_err(encode_error)?;
+        for (image, delay_ms) in canvas_frames(frames).zip(frame_delays(frames)) {
+            let delay = image::Delay::from_numer_denom_ms(delay_ms as u32, 1);
+            encoder
+                .encode_frame(image::Frame::from_parts(image.into_owned(), 0, 0, delay))
+                .map_err(encode_error)?;
+        }
+    }
+    Ok(data)
+}
+
+/// Encode the frames as an endlessly looping APNG
+fn encode_apng(frames: &[Frame]) -> Result<Vec<u8>, ToolResult> {
+    let encode_error = |e: png::EncodingError| {
+        error_response("encode_error", format!("Failed to encode APNG: {}", e))
+    };
+    let (width, height) = frames[0].image.dimensions();
+    let mut data = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut data, width, height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .set_animated(frames.len() as u32, 0)
+            .map_err(encode_error)?;
+        let mut writer = encoder.write_header().map_err(encode_error)?;
+        for (image, delay_ms) in canvas_frames(frames).zip(frame_delays(frames)) {
+            writer
+                .set_frame_delay(delay_ms.min(u16::MAX as u64) as u16, 1000)
+                .map_err(encode_error)?;
+            writer
+                .write_image_data(image.as_raw())
+                .map_err(encode_error)?;
+        }
+        writer.finish().map_err(encode_error)?;
+    }
+    Ok(data)
+}
+
+/// Write `frame-0001.png`, ... and `frames.json` to a directory; returns the total size
+fn write_frames(dir: &str, frames: &[Frame]) -> Result<usize, ToolResult> {
+    let write_error = |e: std::io::Error| {
+        error_response("write_error", format!("Failed to write {}: {}", dir, e))
+    };
+    std::fs::create_dir_all(dir).map_err(write_error)?;
+
+    let mut size = 0;
+    let mut index = Vec::new();
+    for (i, frame) in frames.iter().enumerate() {
+        let file = format!("frame-{:04}.png", i + 1);
+        let data = super::screenshot::encode_png(&frame.image)?;
+        size += write_file(&format!("{}/{}", dir, file), &data)?;
+        index.push(json!({
+            "file": file,
+            "timestamp_ms": frame.timestamp_ms
+        }));
+    }
+    let index = serde_json::to_string_pretty(&index)
+        .map_err(|e| error_response("serialize_error", e.to_string()))?;
+    size += write_file(&format!("{}/frames.json", dir), index.as_bytes())?;
+    Ok(size)
+}
+
+fn write_file(path: &str, data: &[u8]) -> Result<usize, ToolResult> {
+    std::fs::write(path, data)
+        .map_err(|e| error_response("write_error", format!("Failed to write {}: {}", path, e)))?;
+    Ok(data.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames() -> Vec<Frame> {
+        [
+            (0, [255, 0, 0, 255]),
+            (100, [0, 255, 0, 255]),
+            (250, [0, 0, 255, 255]),
+        ]
+        .into_iter()
+        .map(|(timestamp_ms, color)| Frame {
+            timestamp_ms,
+            image: RgbaImage::from_pixel(8, 6, image::Rgba(color)),
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_frame_delays() {
+        assert_eq!(frame_delays(&frames()), vec![100, 150, LAST_FRAME_DELAY_MS]);
+    }
+
+    #[test]
+    fn test_encode_animations() {
+        use image::AnimationDecoder;
+
+        let gif = encode_gif(&frames()).unwrap();
+        let decoded = image::codecs::gif::GifDecoder::new(std::io::Cursor::new(gif))
+            .unwrap()
+            .into_frames()
+            .collect_frames()
+            .unwrap();
+        assert_eq!(decoded.len(), 3);
+        assert_eq!(decoded[1].delay().numer_denom_ms(), (150, 1));
+
+        let apng = encode_apng(&frames()).unwrap();
+        let decoder = image::codecs::png::PngDecoder::new(std::io::Cursor::new(apng)).unwrap();
+        assert!(decoder.is_apng().unwrap());
+        let decoded = decoder
+            .apng()
+            .unwrap()
+            .into_frames()
+            .collect_frames()
+            .unwrap();
+        assert_eq!(decoded.len(), 3);
+    }
+
+    #[test]
+    fn test_write_frames() {
+        let dir =
+            std::env::temp_dir().join(format!("egui-mcp-recording-test-{}", std::process::id()));
+        let dir_str = dir.to_str().unwrap();
+        write_frames(dir_str, &frames()).unwrap();
+
+        assert!(dir.join("frame-0003.png").exists());
+        let index: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.join("frames.json")).unwrap())
+                .unwrap();
+        assert_eq!(index[2]["timestamp_ms"], 250);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
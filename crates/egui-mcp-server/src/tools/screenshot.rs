@@ -1,7 +1,11 @@
 //! Screenshot tool implementations
 
-use super::{ToolResult, error_response, not_connected_error, parse_element_id};
+use super::masks::{AreaResolver, fill_masks, mark_masks, mask_gray, score_regions};
+use super::{ToolResult, error_response, not_connected_error, parse_element_id, parse_viewport_id};
+use crate::constants::DEFAULT_JPEG_QUALITY;
 use crate::ipc_client::IpcClient;
+use crate::requests::{ImageOutputOptions, RegionThreshold, ScreenshotArea};
+use egui_mcp_protocol::Rect;
 use rmcp::model::Content;
 use serde_json::json;
 
@@ -12,24 +16,12 @@ use crate::atspi_client::AtspiClient;
 pub async fn take_screenshot(
     ipc_client: &IpcClient,
     save_to_file: bool,
-) -> Result<Content, String> {
-    if !ipc_client.is_socket_available() {
-        return Err(not_connected_error());
-    }
-
-    match ipc_client.take_screenshot().await {
-        Ok((data, _format)) => {
-            if save_to_file {
-                Ok(save_screenshot_to_file(&data))
-            } else {
-                Ok(Content::image(&data, "image/png"))
-            }
-        }
-        Err(e) => Err(error_response(
-            "screenshot_error",
-            format!("Failed to take screenshot: {}", e),
-        )),
-    }
+    viewport_id: Option<&str>,
+    output: &ImageOutputOptions,
+) -> Result<Vec<Content>, ToolResult> {
+    let viewport_id = parse_viewport_id(viewport_id)?;
+    let image = capture(ipc_client, None, viewport_id).await?;
+    output_screenshot(image, output, save_to_file)
 }
 
 /// Take a screenshot of a specific element
@@ -38,69 +30,96 @@ pub async fn screenshot_element(
     ipc_client: &IpcClient,
     id_str: &str,
     save_to_file: bool,
-) -> Result<Content, String> {
+    viewport_id: Option<&str>,
+    output: &ImageOutputOptions,
+) -> Result<Vec<Content>, ToolResult> {
     let id = parse_element_id(id_str)?;
+    let viewport_id = parse_viewport_id(viewport_id)?;
+    let (image, _bounds) = capture_image(app_name, ipc_client, Some(id), None, viewport_id).await?;
+    output_screenshot(image, output, save_to_file)
+}
 
-    if !ipc_client.is_socket_available() {
-        return Err(not_connected_error());
-    }
-
+/// Get the bounds of an element (AT-SPI)
+pub async fn element_bounds(app_name: &str, id: u64) -> Result<Rect, ToolResult> {
     #[cfg(target_os = "linux")]
     {
-        let client = match AtspiClient::new().await {
-            Ok(c) => c,
-            Err(e) => return Err(super::atspi_connection_error(e)),
-        };
-
-        // Get element bounds
-        let bounds = match client.get_bounds(app_name, id).await {
-            Ok(Some(b)) => b,
-            Ok(None) => {
-                return Err(error_response(
-                    "no_bounds",
-                    format!("Element {} has no bounds", id),
-                ));
-            }
-            Err(e) => {
-                return Err(error_response(
-                    "atspi_error",
-                    format!("Failed to get element bounds: {}", e),
-                ));
-            }
-        };
-
-        // Take full screenshot and crop
-        match ipc_client.take_screenshot().await {
-            Ok((data, _format)) => {
-                match crop_screenshot(&data, bounds.x, bounds.y, bounds.width, bounds.height) {
-                    Ok(cropped) => {
-                        if save_to_file {
-                            Ok(save_screenshot_to_file(&cropped))
-                        } else {
-                            Ok(Content::image(&cropped, "image/png"))
-                        }
-                    }
-                    Err(e) => Err(error_response("crop_error", e)),
-                }
-            }
+        let client = AtspiClient::new()
+            .await
+            .map_err(super::atspi_connection_error)?;
+        match client.get_bounds(app_name, id).await {
+            Ok(Some(bounds)) => Ok(bounds),
+            Ok(None) => Err(error_response(
+                "no_bounds",
+                format!("Element {} has no bounds", id),
+            )),
             Err(e) => Err(error_response(
-                "screenshot_error",
-                format!("Failed to take screenshot: {}", e),
+                "atspi_error",
+                format!("Failed to get element bounds: {}", e),
             )),
         }
     }
 
     #[cfg(not(target_os = "linux"))]
     {
-        let _ = (app_name, id, save_to_file);
-        Err(error_response(
-            "not_available",
-            "screenshot_element requires AT-SPI on Linux.",
-        ))
+        let _ = (app_name, id);
+        Err(super::not_available_error("Element bounds"))
+    }
+}
+
+/// Capture the application, an element or a region as an image
+///
+/// `element_id` takes precedence over `region`; without either the whole
+/// viewport is captured. Also returns the captured bounds (None = viewport).
+pub async fn capture_image(
+    app_name: &str,
+    ipc_client: &IpcClient,
+    element_id: Option<u64>,
+    region: Option<Rect>,
+    viewport_id: Option<u64>,
+) -> Result<(image::DynamicImage, Option<Rect>), ToolResult> {
+    if !ipc_client.is_socket_available() {
+        return Err(not_connected_error());
     }
+
+    let bounds = match element_id {
+        Some(id) => Some(element_bounds(app_name, id).await?),
+        None => region,
+    };
+    let image = capture(ipc_client, bounds, viewport_id).await?;
+    Ok((image, bounds))
+}
+
+/// Take a screenshot of a viewport, cropped to `bounds` if given
+async fn capture(
+    ipc_client: &IpcClient,
+    bounds: Option<Rect>,
+    viewport_id: Option<u64>,
+) -> Result<image::DynamicImage, ToolResult> {
+    use base64::Engine;
+
+    if !ipc_client.is_socket_available() {
+        return Err(not_connected_error());
+    }
+
+    let (data, _format) = ipc_client.take_screenshot(viewport_id).await.map_err(|e| {
+        error_response(
+            "screenshot_error",
+            format!("Failed to take screenshot: {}", e),
+        )
+    })?;
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(&data)
+        .map_err(|e| error_response("decode_error", format!("Failed to decode base64: {}", e)))?;
+    let image = image::load_from_memory(&bytes)
+        .map_err(|e| error_response("image_error", format!("Failed to load image: {}", e)))?;
+    Ok(match bounds {
+        Some(b) => image.crop_imm(b.x as u32, b.y as u32, b.width as u32, b.height as u32),
+        None => image,
+    })
 }
 
 /// Take a screenshot of a specific region
+#[allow(clippy::too_many_arguments)]
 pub async fn screenshot_region(
     ipc_client: &IpcClient,
     x: f32,
@@ -108,39 +127,219 @@ pub async fn screenshot_region(
     width: f32,
     height: f32,
     save_to_file: bool,
-) -> Result<Content, String> {
-    if !ipc_client.is_socket_available() {
-        return Err(not_connected_error());
+    viewport_id: Option<&str>,
+    output: &ImageOutputOptions,
+) -> Result<Vec<Content>, ToolResult> {
+    let viewport_id = parse_viewport_id(viewport_id)?;
+    let region = Rect {
+        x,
+        y,
+        width,
+        height,
+    };
+    let image = capture(ipc_client, Some(region), viewport_id).await?;
+    output_screenshot(image, output, save_to_file)
+}
+
+/// Resize and encode a screenshot, reporting the resulting size
+///
+/// Returns the size info followed by the image, or only the info (with the
+/// file path) when saving to a file.
+fn output_screenshot(
+    image: image::DynamicImage,
+    options: &ImageOutputOptions,
+    save_to_file: bool,
+) -> Result<Vec<Content>, ToolResult> {
+    use base64::Engine;
+
+    let (original_width, original_height) = (image.width(), image.height());
+    let image = resize_image(image, options.max_width, options.scale)?;
+    let format = ImageFormat::parse(options.format.as_deref())?;
+    let bytes = encode_image(&image, format, options.quality)?;
+
+    let mut info = json!({
+        "width": image.width(),
+        "height": image.height(),
+        "original_width": original_width,
+        "original_height": original_height,
+        "format": format.extension(),
+        "size_bytes": bytes.len()
+    });
+    if save_to_file {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0);
+        let file_path = format!(
+            "/tmp/egui-mcp-screenshot-{}.{}",
+            timestamp,
+            format.extension()
+        );
+        std::fs::write(&file_path, &bytes).map_err(|e| {
+            error_response(
+                "file_write_error",
+                format!("Failed to write screenshot file: {}", e),
+            )
+        })?;
+        info["file_path"] = json!(file_path);
+        Ok(vec![Content::text(info.to_string())])
+    } else {
+        let data = base64::engine::general_purpose::STANDARD.encode(&bytes);
+        Ok(vec![
+            Content::text(info.to_string()),
+            Content::image(data, format.mime_type()),
+        ])
     }
+}
 
-    match ipc_client.take_screenshot().await {
-        Ok((data, _format)) => match crop_screenshot(&data, x, y, width, height) {
-            Ok(cropped) => {
-                if save_to_file {
-                    Ok(save_screenshot_to_file(&cropped))
-                } else {
-                    Ok(Content::image(&cropped, "image/png"))
-                }
-            }
-            Err(e) => Err(error_response("crop_error", e)),
-        },
-        Err(e) => Err(error_response(
-            "screenshot_error",
-            format!("Failed to take screenshot: {}", e),
-        )),
+/// Output format of a screenshot
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    WebP,
+}
+
+impl ImageFormat {
+    /// Parse a format name (default: PNG)
+    pub fn parse(format: Option<&str>) -> Result<Self, ToolResult> {
+        match format.map(str::to_ascii_lowercase).as_deref() {
+            None | Some("png") => Ok(Self::Png),
+            Some("jpeg") | Some("jpg") => Ok(Self::Jpeg),
+            Some("webp") => Ok(Self::WebP),
+            Some(other) => Err(error_response(
+                "invalid_format",
+                format!(
+                    "Unknown image format '{}'. Use 'png', 'jpeg' or 'webp'",
+                    other
+                ),
+            )),
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Jpeg => "jpeg",
+            Self::WebP => "webp",
+        }
+    }
+
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            Self::Png => "image/png",
+            Self::Jpeg => "image/jpeg",
+            Self::WebP => "image/webp",
+        }
+    }
+}
+
+/// Downscale an image by `scale`, then to at most `max_width` pixels wide
+pub fn resize_image(
+    image: image::DynamicImage,
+    max_width: Option<u32>,
+    scale: Option<f32>,
+) -> Result<image::DynamicImage, ToolResult> {
+    let (width, height) = (image.width() as f32, image.height() as f32);
+    let mut factor = match scale {
+        // Only downscaling: enlarging would cost context for no detail
+        Some(scale) if !(scale > 0.0 && scale <= 1.0) => {
+            return Err(error_response(
+                "invalid_scale",
+                format!("Scale must be greater than 0 and at most 1, got {}", scale),
+            ));
+        }
+        Some(scale) => scale,
+        None => 1.0,
+    };
+    if let Some(max_width) = max_width {
+        if max_width == 0 {
+            return Err(error_response(
+                "invalid_max_width",
+                "max_width must be at least 1",
+            ));
+        }
+        factor = factor.min(max_width as f32 / width);
+    }
+    if factor == 1.0 {
+        return Ok(image);
+    }
+    let new_width = ((width * factor).round() as u32).max(1);
+    let new_height = ((height * factor).round() as u32).max(1);
+    Ok(image.resize_exact(new_width, new_height, image::imageops::FilterType::Triangle))
+}
+
+/// Encode an image; `quality` (1-100) is only accepted for JPEG
+pub fn encode_image(
+    image: &image::DynamicImage,
+    format: ImageFormat,
+    quality: Option<u8>,
+) -> Result<Vec<u8>, ToolResult> {
+    if let Some(quality) = quality {
+        if !(1..=100).contains(&quality) {
+            return Err(error_response(
+                "invalid_quality",
+                format!("Quality must be between 1 and 100, got {}", quality),
+            ));
+        }
+        if format != ImageFormat::Jpeg {
+            return Err(error_response(
+                "invalid_quality",
+                format!(
+                    "Quality only applies to JPEG; {} is encoded lossless",
+                    format.extension()
+                ),
+            ));
+        }
+    }
+    let encode_error = |e: image::ImageError| {
+        error_response("encode_error", format!("Failed to encode image: {}", e))
+    };
+    match format {
+        ImageFormat::Png => encode_png(&image.to_rgba8()),
+        ImageFormat::Jpeg => {
+            let mut buffer = Vec::new();
+            // JPEG has no alpha channel
+            image::codecs::jpeg::JpegEncoder::new_with_quality(
+                &mut buffer,
+                quality.unwrap_or(DEFAULT_JPEG_QUALITY),
+            )
+            .encode_image(&image.to_rgb8())
+            .map_err(encode_error)?;
+            Ok(buffer)
+        }
+        ImageFormat::WebP => {
+            let mut buffer = Vec::new();
+            image::codecs::webp::WebPEncoder::new_lossless(&mut buffer)
+                .encode(
+                    image.to_rgba8().as_raw(),
+                    image.width(),
+                    image.height(),
+                    image::ExtendedColorType::Rgba8,
+                )
+                .map_err(encode_error)?;
+            Ok(buffer)
+        }
     }
 }
 
 /// Compare two screenshots and return similarity score
-pub fn compare_screenshots(
+///
+/// Masked areas are ignored; each region is scored separately against its
+/// own threshold.
+#[allow(clippy::too_many_arguments)]
+pub async fn compare_screenshots(
+    app_name: &str,
+    ipc_client: &IpcClient,
     base64_a: Option<&str>,
     base64_b: Option<&str>,
     path_a: Option<&str>,
     path_b: Option<&str>,
     algorithm: Option<&str>,
+    masks: &[ScreenshotArea],
+    regions: &[RegionThreshold],
 ) -> ToolResult {
     use image::DynamicImage;
-    use image_compare::Algorithm;
 
     // Load first image
     let img_a: DynamicImage = match load_image(base64_a, path_a) {
@@ -155,8 +354,8 @@ pub fn compare_screenshots(
     };
 
     // Convert to grayscale for comparison
-    let gray_a = img_a.to_luma8();
-    let gray_b = img_b.to_luma8();
+    let mut gray_a = img_a.to_luma8();
+    let mut gray_b = img_b.to_luma8();
 
     // Check dimensions match
     if gray_a.dimensions() != gray_b.dimensions() {
@@ -173,69 +372,84 @@ pub fn compare_screenshots(
         .to_string();
     }
 
-    // Compare based on algorithm
-    let score = match algorithm.unwrap_or("hybrid") {
-        "mssim" => {
-            match image_compare::gray_similarity_structure(
-                &Algorithm::MSSIMSimple,
-                &gray_a,
-                &gray_b,
-            ) {
-                Ok(result) => result.score,
-                Err(e) => {
-                    return error_response("compare_error", format!("Comparison failed: {}", e));
-                }
-            }
-        }
-        "rms" => {
-            match image_compare::gray_similarity_structure(
-                &Algorithm::RootMeanSquared,
-                &gray_a,
-                &gray_b,
-            ) {
-                Ok(result) => result.score,
-                Err(e) => {
-                    return error_response("compare_error", format!("Comparison failed: {}", e));
-                }
-            }
-        }
-        _ => {
-            // Hybrid: combine MSSIM and RMS
-            let mssim = match image_compare::gray_similarity_structure(
-                &Algorithm::MSSIMSimple,
-                &gray_a,
-                &gray_b,
-            ) {
-                Ok(result) => result.score,
-                Err(e) => return error_response("compare_error", format!("MSSIM failed: {}", e)),
-            };
-            let rms = match image_compare::gray_similarity_structure(
-                &Algorithm::RootMeanSquared,
-                &gray_a,
-                &gray_b,
-            ) {
-                Ok(result) => result.score,
-                Err(e) => return error_response("compare_error", format!("RMS failed: {}", e)),
-            };
-            (mssim + rms) / 2.0
-        }
+    let mut resolver = AreaResolver::new(app_name, ipc_client);
+    let mask_rects = match resolver.masks(masks).await {
+        Ok(rects) => rects,
+        Err(e) => return e,
+    };
+    let regions = match resolver.regions(regions).await {
+        Ok(regions) => regions,
+        Err(e) => return e,
+    };
+    mask_gray(&mut gray_a, &mask_rects);
+    mask_gray(&mut gray_b, &mask_rects);
+
+    let algorithm = algorithm.unwrap_or("hybrid");
+    let score = match similarity(&gray_a, &gray_b, algorithm) {
+        Ok(score) => score,
+        Err(e) => return e,
     };
 
-    json!({
+    let mut result = json!({
         "score": score,
         "identical": score > 0.9999,
-        "algorithm": algorithm.unwrap_or("hybrid")
-    })
-    .to_string()
+        "algorithm": algorithm
+    });
+    if !mask_rects.is_empty() {
+        result["masked_rects"] = json!(mask_rects);
+    }
+    if !regions.is_empty() {
+        let (report, failed) = match score_regions(&gray_a, &gray_b, &regions, algorithm) {
+            Ok(scores) => scores,
+            Err(e) => return e,
+        };
+        result["passed"] = json!(failed.is_empty());
+        result["regions"] = json!(report);
+        result["failed_regions"] = json!(failed);
+    }
+    result.to_string()
+}
+
+/// Similarity score of two grayscale images of the same size (1.0 = identical)
+///
+/// `algorithm` is "mssim", "rms" or "hybrid" (anything else: the average of both).
+pub fn similarity(
+    gray_a: &image::GrayImage,
+    gray_b: &image::GrayImage,
+    algorithm: &str,
+) -> Result<f64, ToolResult> {
+    use image_compare::Algorithm;
+
+    let score = |algorithm: &Algorithm, name: &str| {
+        image_compare::gray_similarity_structure(algorithm, gray_a, gray_b)
+            .map(|result| result.score)
+            .map_err(|e| error_response("compare_error", format!("{} failed: {}", name, e)))
+    };
+    match algorithm {
+        "mssim" => score(&Algorithm::MSSIMSimple, "Comparison"),
+        "rms" => score(&Algorithm::RootMeanSquared, "Comparison"),
+        _ => {
+            // Hybrid: combine MSSIM and RMS
+            let mssim = score(&Algorithm::MSSIMSimple, "MSSIM")?;
+            let rms = score(&Algorithm::RootMeanSquared, "RMS")?;
+            Ok((mssim + rms) / 2.0)
+        }
+    }
 }
 
 /// Generate a visual diff image
-pub fn diff_screenshots(
+///
+/// Masked areas are not compared and drawn in blue.
+#[allow(clippy::too_many_arguments)]
+pub async fn diff_screenshots(
+    app_name: &str,
+    ipc_client: &IpcClient,
     base64_a: Option<&str>,
     base64_b: Option<&str>,
     path_a: Option<&str>,
     path_b: Option<&str>,
     save_to_file: bool,
+    masks: &[ScreenshotArea],
 ) -> Result<Content, String> {
     use base64::Engine;
 
@@ -245,10 +459,22 @@ pub fn diff_screenshots(
     // Load second image
     let img_b = load_image(base64_b, path_b)?;
 
-    // Generate diff image
-    let rgba_a = img_a.to_rgba8();
-    let rgba_b = img_b.to_rgba8();
+    let mask_rects = AreaResolver::new(app_name, ipc_client).masks(masks).await?;
+    let diff_img = masked_diff_image(img_a.to_rgba8(), img_b.to_rgba8(), &mask_rects);
+    let png_data = encode_png(&diff_img)?;
+    let base64_data = base64::engine::general_purpose::STANDARD.encode(&png_data);
 
+    if save_to_file {
+        Ok(save_screenshot_to_file(&base64_data))
+    } else {
+        Ok(Content::image(&base64_data, "image/png"))
+    }
+}
+
+/// Diff two images: identical pixels in translucent gray, different ones in red
+///
+/// Images of different sizes are padded with black to the larger size.
+pub fn diff_image(rgba_a: &image::RgbaImage, rgba_b: &image::RgbaImage) -> image::RgbaImage {
     let (width_a, height_a) = rgba_a.dimensions();
     let (width_b, height_b) = rgba_b.dimensions();
     let max_width = width_a.max(width_b);
@@ -280,26 +506,32 @@ pub fn diff_screenshots(
             diff_img.put_pixel(x, y, diff);
         }
     }
-
-    // Encode to PNG
-    let mut buffer = std::io::Cursor::new(Vec::new());
     diff_img
-        .write_to(&mut buffer, image::ImageFormat::Png)
-        .map_err(|e| {
-            error_response(
-                "encode_error",
-                format!("Failed to encode diff image: {}", e),
-            )
-        })?;
-
-    let png_data = buffer.into_inner();
-    let base64_data = base64::engine::general_purpose::STANDARD.encode(&png_data);
+}
 
-    if save_to_file {
-        Ok(save_screenshot_to_file(&base64_data))
-    } else {
-        Ok(Content::image(&base64_data, "image/png"))
+/// [`diff_image`] with the masked areas excluded and marked
+pub fn masked_diff_image(
+    mut rgba_a: image::RgbaImage,
+    mut rgba_b: image::RgbaImage,
+    masks: &[Rect],
+) -> image::RgbaImage {
+    if masks.is_empty() {
+        return diff_image(&rgba_a, &rgba_b);
     }
+    let transparent = image::Rgba([0, 0, 0, 0]);
+    fill_masks(&mut rgba_a, masks, transparent);
+    fill_masks(&mut rgba_b, masks, transparent);
+    let mut diff = diff_image(&rgba_a, &rgba_b);
+    mark_masks(&mut diff, masks);
+    diff
+}
+
+/// Encode an image as PNG
+pub fn encode_png(img: &image::RgbaImage) -> Result<Vec<u8>, ToolResult> {
+    let mut buffer = std::io::Cursor::new(Vec::new());
+    img.write_to(&mut buffer, image::ImageFormat::Png)
+        .map_err(|e| error_response("encode_error", format!("Failed to encode image: {}", e)))?;
+    Ok(buffer.into_inner())
 }
 
 /// Load an image from base64 or file path
@@ -328,33 +560,6 @@ fn load_image(
     }
 }
 
-/// Crop a screenshot to specific bounds
-fn crop_screenshot(
-    base64_data: &str,
-    x: f32,
-    y: f32,
-    width: f32,
-    height: f32,
-) -> Result<String, String> {
-    use base64::Engine;
-
-    let bytes = base64::engine::general_purpose::STANDARD
-        .decode(base64_data)
-        .map_err(|e| format!("Failed to decode base64: {}", e))?;
-
-    let img =
-        image::load_from_memory(&bytes).map_err(|e| format!("Failed to load image: {}", e))?;
-
-    let cropped = img.crop_imm(x as u32, y as u32, width as u32, height as u32);
-
-    let mut buffer = std::io::Cursor::new(Vec::new());
-    cropped
-        .write_to(&mut buffer, image::ImageFormat::Png)
-        .map_err(|e| format!("Failed to encode cropped image: {}", e))?;
-
-    Ok(base64::engine::general_purpose::STANDARD.encode(buffer.into_inner()))
-}
-
 /// Save base64-encoded PNG data to a temp file
 pub fn save_screenshot_to_file(data: &str) -> Content {
     use base64::Engine;
@@ -393,3 +598,52 @@ pub fn save_screenshot_to_file(data: &str) -> Content {
         ),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::GenericImageView;
+
+    fn image(width: u32, height: u32) -> image::DynamicImage {
+        image::DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(
+            width,
+            height,
+            image::Rgba([10, 20, 30, 255]),
+        ))
+    }
+
+    #[test]
+    fn test_resize_image() {
+        let resized = resize_image(image(400, 200), Some(100), None).unwrap();
+        assert_eq!(resized.dimensions(), (100, 50));
+
+        // max_width only limits; smaller images are not enlarged
+        let resized = resize_image(image(400, 200), Some(1000), Some(0.5)).unwrap();
+        assert_eq!(resized.dimensions(), (200, 100));
+        let resized = resize_image(image(400, 200), Some(100), Some(0.5)).unwrap();
+        assert_eq!(resized.dimensions(), (100, 50));
+
+        assert!(resize_image(image(4, 4), None, Some(0.0)).is_err());
+        assert!(resize_image(image(4, 4), None, Some(4.0)).is_err());
+        assert!(resize_image(image(4, 4), None, Some(f32::NAN)).is_err());
+        let resized = resize_image(image(4, 4), None, Some(1.0)).unwrap();
+        assert_eq!(resized.dimensions(), (4, 4));
+    }
+
+    #[test]
+    fn test_encode_image_formats() {
+        let img = image(64, 32);
+        for format in [ImageFormat::Png, ImageFormat::Jpeg, ImageFormat::WebP] {
+            let bytes = encode_image(&img, format, None).unwrap();
+            let decoded = image::load_from_memory(&bytes).unwrap();
+            assert_eq!(decoded.dimensions(), (64, 32));
+        }
+        assert_eq!(ImageFormat::parse(Some("JPG")).unwrap(), ImageFormat::Jpeg);
+        assert!(ImageFormat::parse(Some("bmp")).is_err());
+        assert!(encode_image(&img, ImageFormat::Jpeg, Some(50)).is_ok());
+        assert!(encode_image(&img, ImageFormat::Jpeg, Some(0)).is_err());
+        assert!(encode_image(&img, ImageFormat::Jpeg, Some(101)).is_err());
+        assert!(encode_image(&img, ImageFormat::Png, Some(50)).is_err());
+        assert!(encode_image(&img, ImageFormat::WebP, Some(50)).is_err());
+    }
+}
@@ -0,0 +1,70 @@
+//! Time control tool implementations
+
+use super::{ToolResult, error_response, not_connected_error};
+use crate::ipc_client::IpcClient;
+use serde_json::json;
+
+/// Default time step per frame (60 fps)
+const DEFAULT_DT_MS: f32 = 1000.0 / 60.0;
+
+/// Freeze the time reported to egui
+pub async fn freeze_time(ipc_client: &IpcClient) -> ToolResult {
+    if !ipc_client.is_socket_available() {
+        return not_connected_error();
+    }
+
+    match ipc_client.freeze_time().await {
+        Ok(time) => json!({
+            "success": true,
+            "frozen": true,
+            "time": time
+        })
+        .to_string(),
+        Err(e) => error_response("ipc_error", format!("Failed to freeze time: {}", e)),
+    }
+}
+
+/// Run frames with a fixed time step, then stay frozen
+pub async fn step_frames(
+    ipc_client: &IpcClient,
+    frames: Option<u32>,
+    dt_ms: Option<f32>,
+) -> ToolResult {
+    if !ipc_client.is_socket_available() {
+        return not_connected_error();
+    }
+
+    let frames = frames.unwrap_or(1);
+    let dt_ms = dt_ms.unwrap_or(DEFAULT_DT_MS);
+    if !dt_ms.is_finite() || dt_ms < 0.0 {
+        return error_response("invalid_dt", format!("Invalid dt_ms: {}", dt_ms));
+    }
+
+    match ipc_client.step_frames(frames, dt_ms).await {
+        Ok(time) => json!({
+            "success": true,
+            "frozen": true,
+            "frames": frames,
+            "dt_ms": dt_ms,
+            "time": time
+        })
+        .to_string(),
+        Err(e) => error_response("ipc_error", format!("Failed to step frames: {}", e)),
+    }
+}
+
+/// Resume real time
+pub async fn resume_time(ipc_client: &IpcClient) -> ToolResult {
+    if !ipc_client.is_socket_available() {
+        return not_connected_error();
+    }
+
+    match ipc_client.resume_time().await {
+        Ok(()) => json!({
+            "success": true,
+            "frozen": false
+        })
+        .to_string(),
+        Err(e) => error_response("ipc_error", format!("Failed to resume time: {}", e)),
+    }
+}
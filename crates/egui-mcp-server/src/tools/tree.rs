@@ -1,152 +1,164 @@
 //! UI tree tool implementations (get_ui_tree, find_by_label, find_by_role, get_element)
+//!
+//! The tree comes from AT-SPI on Linux. If AT-SPI is unavailable or does not
+//! know the app (e.g. a `HeadlessHarness` without a D-Bus session), the app's
+//! AccessKit tree is requested over IPC instead.
 
 use super::{ToolResult, error_response, parse_element_id};
+use crate::ipc_client::IpcClient;
+use egui_mcp_protocol::{NodeInfo, UiTree};
 use serde_json::json;
 
 #[cfg(target_os = "linux")]
 use crate::atspi_client::AtspiClient;
 
-/// Get the UI tree from the connected egui application via AT-SPI
-pub async fn get_ui_tree(app_name: &str) -> ToolResult {
+/// Get the UI tree via AT-SPI, falling back to the app's AccessKit tree over IPC
+///
+/// Returns `Ok(None)` if AT-SPI works but does not know the app and the app
+/// does not serve its tree over IPC.
+pub async fn load_tree(
+    app_name: &str,
+    ipc_client: &IpcClient,
+) -> Result<Option<UiTree>, ToolResult> {
     #[cfg(target_os = "linux")]
-    {
-        let client = match AtspiClient::new().await {
-            Ok(c) => c,
-            Err(e) => return super::atspi_connection_error(e),
-        };
-
-        match client.get_ui_tree_by_app_name(app_name).await {
-            Ok(Some(tree)) => {
-                return serde_json::to_string_pretty(&tree).unwrap_or_else(|e| {
-                    error_response(
-                        "serialization_error",
-                        format!("Failed to serialize UI tree: {}", e),
-                    )
-                });
-            }
+    let atspi_error = match AtspiClient::new().await {
+        Ok(client) => match client.get_ui_tree_by_app_name(app_name).await {
+            Ok(Some(tree)) => return Ok(Some(tree)),
             Ok(None) => {
                 tracing::info!("AT-SPI did not find any matching application");
+                None
             }
             Err(e) => {
                 tracing::warn!("AT-SPI failed: {}", e);
+                Some(error_response(
+                    "not_available",
+                    "UI tree access requires AT-SPI on Linux. Make sure the egui app is running.",
+                ))
             }
-        }
-    }
+        },
+        Err(e) => Some(super::atspi_connection_error(e)),
+    };
 
     #[cfg(not(target_os = "linux"))]
-    let _ = app_name;
+    let atspi_error = {
+        let _ = app_name;
+        Some(error_response(
+            "not_available",
+            "UI tree access requires AT-SPI on Linux, or an app that serves its AccessKit tree over IPC.",
+        ))
+    };
+
+    if ipc_client.is_socket_available() {
+        match ipc_client.get_ui_tree().await {
+            Ok(tree) => return Ok(Some(tree)),
+            Err(e) => tracing::debug!("IPC UI tree not available: {}", e),
+        }
+    }
 
-    error_response(
-        "not_available",
-        "UI tree access requires AT-SPI on Linux. Make sure the egui app is running.",
-    )
+    match atspi_error {
+        Some(error) => Err(error),
+        None => Ok(None),
+    }
 }
 
-/// Find UI elements by their label text (substring match)
-pub async fn find_by_label(app_name: &str, pattern: &str, exact: bool) -> ToolResult {
+/// Fill in missing bounds of nodes from AT-SPI
+///
+/// Trees from AT-SPI carry no bounds; they are requested per element here.
+/// Nodes whose bounds cannot be read keep `None`.
+pub async fn fill_bounds(app_name: &str, nodes: &mut [NodeInfo]) {
+    if nodes.iter().all(|node| node.bounds.is_some()) {
+        return;
+    }
+
     #[cfg(target_os = "linux")]
     {
-        let client = match AtspiClient::new().await {
-            Ok(c) => c,
-            Err(e) => return super::atspi_connection_error(e),
+        let Ok(client) = AtspiClient::new().await else {
+            return;
         };
-
-        match client.find_by_label(app_name, pattern, exact).await {
-            Ok(elements) => {
-                return serde_json::to_string_pretty(&json!({
-                    "count": elements.len(),
-                    "elements": elements
-                }))
-                .unwrap_or_else(|e| {
-                    error_response(
-                        "serialization_error",
-                        format!("Failed to serialize elements: {}", e),
-                    )
-                });
-            }
-            Err(e) => {
-                tracing::warn!("AT-SPI find_by_label failed: {}", e);
+        for node in nodes.iter_mut().filter(|node| node.bounds.is_none()) {
+            match client.get_bounds(app_name, node.id).await {
+                Ok(bounds) => node.bounds = bounds,
+                Err(e) => tracing::debug!("No bounds for element {}: {}", node.id, e),
             }
         }
     }
 
     #[cfg(not(target_os = "linux"))]
-    let _ = (app_name, pattern, exact);
-
-    error_response("not_available", "Element search requires AT-SPI on Linux.")
+    let _ = app_name;
 }
 
-/// Find UI elements by their role
-pub async fn find_by_role(app_name: &str, role: &str) -> ToolResult {
-    #[cfg(target_os = "linux")]
-    {
-        let client = match AtspiClient::new().await {
-            Ok(c) => c,
-            Err(e) => return super::atspi_connection_error(e),
-        };
-
-        match client.find_by_role(app_name, role).await {
-            Ok(elements) => {
-                return serde_json::to_string_pretty(&json!({
-                    "count": elements.len(),
-                    "elements": elements
-                }))
-                .unwrap_or_else(|e| {
-                    error_response(
-                        "serialization_error",
-                        format!("Failed to serialize elements: {}", e),
-                    )
-                });
-            }
-            Err(e) => {
-                tracing::warn!("AT-SPI find_by_role failed: {}", e);
-            }
-        }
+/// Get the UI tree from the connected egui application
+pub async fn get_ui_tree(app_name: &str, ipc_client: &IpcClient) -> ToolResult {
+    match load_tree(app_name, ipc_client).await {
+        Ok(Some(tree)) => serde_json::to_string_pretty(&tree).unwrap_or_else(|e| {
+            error_response(
+                "serialization_error",
+                format!("Failed to serialize UI tree: {}", e),
+            )
+        }),
+        Ok(None) => error_response(
+            "not_available",
+            "UI tree access requires AT-SPI on Linux. Make sure the egui app is running.",
+        ),
+        Err(e) => e,
     }
+}
 
-    #[cfg(not(target_os = "linux"))]
-    let _ = (app_name, role);
+/// Find UI elements by their label text (substring match)
+pub async fn find_by_label(
+    app_name: &str,
+    ipc_client: &IpcClient,
+    pattern: &str,
+    exact: bool,
+) -> ToolResult {
+    match load_tree(app_name, ipc_client).await {
+        Ok(tree) => elements_response(
+            tree.map(|tree| tree.find_by_label(pattern, exact))
+                .unwrap_or_default(),
+        ),
+        Err(e) => e,
+    }
+}
 
-    error_response("not_available", "Element search requires AT-SPI on Linux.")
+/// Find UI elements by their role
+pub async fn find_by_role(app_name: &str, ipc_client: &IpcClient, role: &str) -> ToolResult {
+    match load_tree(app_name, ipc_client).await {
+        Ok(tree) => elements_response(tree.map(|tree| tree.find_by_role(role)).unwrap_or_default()),
+        Err(e) => e,
+    }
 }
 
 /// Get detailed information about a specific UI element by its ID
-pub async fn get_element(app_name: &str, id_str: &str) -> ToolResult {
+pub async fn get_element(app_name: &str, ipc_client: &IpcClient, id_str: &str) -> ToolResult {
     let id = match parse_element_id(id_str) {
         Ok(id) => id,
         Err(e) => return e,
     };
 
-    #[cfg(target_os = "linux")]
-    {
-        let client = match AtspiClient::new().await {
-            Ok(c) => c,
-            Err(e) => return super::atspi_connection_error(e),
-        };
-
-        match client.get_element(app_name, id).await {
-            Ok(Some(element)) => {
-                return serde_json::to_string_pretty(&element).unwrap_or_else(|e| {
-                    error_response(
-                        "serialization_error",
-                        format!("Failed to serialize element: {}", e),
-                    )
-                });
-            }
-            Ok(None) => {
-                return error_response("not_found", format!("Element with ID {} not found", id));
-            }
-            Err(e) => {
-                tracing::warn!("AT-SPI get_element failed: {}", e);
-            }
-        }
+    let tree = match load_tree(app_name, ipc_client).await {
+        Ok(tree) => tree,
+        Err(e) => return e,
+    };
+    match tree.as_ref().and_then(|tree| tree.get(id)) {
+        Some(element) => serde_json::to_string_pretty(element).unwrap_or_else(|e| {
+            error_response(
+                "serialization_error",
+                format!("Failed to serialize element: {}", e),
+            )
+        }),
+        None => error_response("not_found", format!("Element with ID {} not found", id)),
     }
+}
 
-    #[cfg(not(target_os = "linux"))]
-    let _ = (app_name, id);
-
-    error_response(
-        "not_available",
-        "Element retrieval requires AT-SPI on Linux.",
-    )
+fn elements_response(elements: Vec<egui_mcp_protocol::NodeInfo>) -> ToolResult {
+    serde_json::to_string_pretty(&json!({
+        "count": elements.len(),
+        "elements": elements
+    }))
+    .unwrap_or_else(|e| {
+        error_response(
+            "serialization_error",
+            format!("Failed to serialize elements: {}", e),
+        )
+    })
 }
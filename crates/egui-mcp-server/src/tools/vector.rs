@@ -0,0 +1,447 @@
+//! Vector screenshot and visible text tool implementations
+
+use super::{ToolResult, error_response, not_connected_error, parse_viewport_id};
+use crate::ipc_client::IpcClient;
+use egui_mcp_protocol::{FrameShapes, Rect, VectorShape, VectorStroke};
+use serde_json::json;
+use std::fmt::Write;
+
+/// Take a vector screenshot of the next frame as SVG or as a JSON shape list
+pub async fn take_vector_screenshot(
+    ipc_client: &IpcClient,
+    format: Option<&str>,
+    save_to_file: bool,
+    viewport_id: Option<&str>,
+) -> ToolResult {
+    let format = format.unwrap_or("svg");
+    if !matches!(format, "svg" | "json") {
+        return error_response(
+            "invalid_format",
+            format!("Invalid format '{}'. Use 'svg' or 'json'", format),
+        );
+    }
+    let viewport_id = match parse_viewport_id(viewport_id) {
+        Ok(id) => id,
+        Err(e) => return e,
+    };
+
+    if !ipc_client.is_socket_available() {
+        return not_connected_error();
+    }
+
+    let shapes = match ipc_client.get_frame_shapes(viewport_id, false).await {
+        Ok(shapes) => shapes,
+        Err(e) => {
+            return error_response("ipc_error", format!("Failed to get frame shapes: {}", e));
+        }
+    };
+
+    let data = if format == "svg" {
+        frame_shapes_to_svg(&shapes)
+    } else {
+        json!(shapes).to_string()
+    };
+    if !save_to_file {
+        return data;
+    }
+
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let file_path = format!("/tmp/egui-mcp-vector-{}.{}", timestamp, format);
+    match std::fs::write(&file_path, &data) {
+        Ok(()) => json!({
+            "file_path": file_path,
+            "size_bytes": data.len(),
+            "shape_count": shapes.shapes.len()
+        })
+        .to_string(),
+        Err(e) => error_response(
+            "file_write_error",
+            format!("Failed to write vector screenshot file: {}", e),
+        ),
+    }
+}
+
+/// Get the text painted in the next frame with positions, colors and layers
+///
+/// Covers custom-painted text that has no accessibility node.
+pub async fn get_visible_text(
+    ipc_client: &IpcClient,
+    contains: Option<&str>,
+    viewport_id: Option<&str>,
+) -> ToolResult {
+    let viewport_id = match parse_viewport_id(viewport_id) {
+        Ok(id) => id,
+        Err(e) => return e,
+    };
+
+    if !ipc_client.is_socket_available() {
+        return not_connected_error();
+    }
+
+    // Only the text shapes, so heavy vector content is not transferred
+    match ipc_client.get_frame_shapes(viewport_id, true).await {
+        Ok(shapes) => {
+            let texts = visible_text(&shapes, contains);
+            json!({
+                "count": texts.len(),
+                "texts": texts
+            })
+            .to_string()
+        }
+        Err(e) => error_response("ipc_error", format!("Failed to get frame shapes: {}", e)),
+    }
+}
+
+/// Text runs that are inside their clip rectangle, back to front
+///
+/// `contains` filters runs by a case-insensitive substring.
+fn visible_text(frame: &FrameShapes, contains: Option<&str>) -> Vec<serde_json::Value> {
+    let contains = contains.map(str::to_lowercase);
+    let mut texts = Vec::new();
+    for painted in &frame.shapes {
+        let VectorShape::Text { runs, .. } = &painted.shape else {
+            continue;
+        };
+        for run in runs {
+            if !intersects(&run.rect, &painted.clip_rect) {
+                continue;
+            }
+            if let Some(needle) = &contains
+                && !run.text.to_lowercase().contains(needle)
+            {
+                continue;
+            }
+            texts.push(json!({
+                "text": run.text,
+                "rect": run.rect,
+                "center": [run.rect.x + run.rect.width / 2.0, run.rect.y + run.rect.height / 2.0],
+                "color": run.color,
+                "font_size": run.font_size,
+                "font_family": run.font_family,
+                "layer_id": painted.layer_id,
+                "layer_order": painted.layer_order
+            }));
+        }
+    }
+    texts
+}
+
+/// Render frame shapes as an SVG document in screen points
+///
+/// Each distinct clip rectangle becomes a `clipPath`. Text is written as
+/// `<text>` elements per run so it stays searchable; meshes (images) and
+/// paint callbacks are drawn as dashed placeholder rectangles.
+pub fn frame_shapes_to_svg(frame: &FrameShapes) -> String {
+    let screen = &frame.screen_rect;
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="{x} {y} {w} {h}">"#,
+        x = screen.x,
+        y = screen.y,
+        w = screen.width,
+        h = screen.height,
+    );
+
+    let mut clips: Vec<&Rect> = Vec::new();
+    let mut body = String::new();
+    for painted in &frame.shapes {
+        let clip = &painted.clip_rect;
+        let index = match clips.iter().position(|r| same_rect(r, clip)) {
+            Some(index) => index,
+            None => {
+                clips.push(clip);
+                clips.len() - 1
+            }
+        };
+        let _ = write!(body, r#"<g clip-path="url(#clip{})">"#, index);
+        write_shape(&mut body, &painted.shape);
+        body.push_str("</g>\n");
+    }
+
+    svg.push_str("<defs>\n");
+    for (index, rect) in clips.iter().enumerate() {
+        let _ = writeln!(
+            svg,
+            r#"<clipPath id="clip{}"><rect x="{}" y="{}" width="{}" height="{}"/></clipPath>"#,
+            index, rect.x, rect.y, rect.width, rect.height
+        );
+    }
+    svg.push_str("</defs>\n");
+    svg.push_str(&body);
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn write_shape(out: &mut String, shape: &VectorShape) {
+    match shape {
+        VectorShape::Rect {
+            rect,
+            corner_radius,
+            fill,
+            stroke,
+        } => {
+            let _ = write!(
+                out,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}"{}/>"#,
+                rect.x,
+                rect.y,
+                rect.width,
+                rect.height,
+                corner_radius,
+                paint_attrs(fill.as_deref(), stroke.as_ref())
+            );
+        }
+        VectorShape::Circle {
+            center,
+            radius,
+            fill,
+            stroke,
+        } => {
+            let _ = write!(
+                out,
+                r#"<circle cx="{}" cy="{}" r="{}"{}/>"#,
+                center[0],
+                center[1],
+                radius,
+                paint_attrs(fill.as_deref(), stroke.as_ref())
+            );
+        }
+        VectorShape::Ellipse {
+            center,
+            radius,
+            fill,
+            stroke,
+        } => {
+            let _ = write!(
+                out,
+                r#"<ellipse cx="{}" cy="{}" rx="{}" ry="{}"{}/>"#,
+                center[0],
+                center[1],
+                radius[0],
+                radius[1],
+                paint_attrs(fill.as_deref(), stroke.as_ref())
+            );
+        }
+        VectorShape::Path {
+            points,
+            closed,
+            fill,
+            stroke,
+        } => {
+            let points: Vec<String> = points
+                .iter()
+                .map(|p| format!("{},{}", p[0], p[1]))
+                .collect();
+            let element = if *closed { "polygon" } else { "polyline" };
+            let _ = write!(
+                out,
+                r#"<{} points="{}"{}/>"#,
+                element,
+                points.join(" "),
+                paint_attrs(fill.as_deref(), stroke.as_ref())
+            );
+        }
+        VectorShape::Text {
+            rect, runs, angle, ..
+        } => {
+            if *angle != 0.0 {
+                let _ = write!(
+                    out,
+                    r#"<g transform="rotate({} {} {})">"#,
+                    angle.to_degrees(),
+                    rect.x,
+                    rect.y
+                );
+            }
+            for run in runs {
+                let (color, opacity) = svg_color(&run.color);
+                let family = if run.font_family == "Monospace" {
+                    "monospace"
+                } else {
+                    "sans-serif"
+                };
+                let _ = write!(
+                    out,
+                    r#"<text x="{}" y="{}" font-size="{}" font-family="{}" fill="{}" fill-opacity="{}" dominant-baseline="text-before-edge" textLength="{}" lengthAdjust="spacingAndGlyphs" xml:space="preserve">{}</text>"#,
+                    run.rect.x,
+                    run.rect.y,
+                    run.font_size,
+                    family,
+                    color,
+                    opacity,
+                    run.rect.width,
+                    escape_xml(&run.text)
+                );
+            }
+            if *angle != 0.0 {
+                out.push_str("</g>");
+            }
+        }
+        VectorShape::Mesh { rect, .. } | VectorShape::Callback { rect } => {
+            let _ = write!(
+                out,
+                r##"<rect x="{}" y="{}" width="{}" height="{}" fill="none" stroke="#808080" stroke-dasharray="4 2"/>"##,
+                rect.x, rect.y, rect.width, rect.height
+            );
+        }
+    }
+}
+
+fn paint_attrs(fill: Option<&str>, stroke: Option<&VectorStroke>) -> String {
+    let mut attrs = String::new();
+    match fill {
+        Some(fill) => {
+            let (color, opacity) = svg_color(fill);
+            let _ = write!(attrs, r#" fill="{}" fill-opacity="{}""#, color, opacity);
+        }
+        None => attrs.push_str(r#" fill="none""#),
+    }
+    if let Some(stroke) = stroke {
+        let (color, opacity) = svg_color(&stroke.color);
+        let _ = write!(
+            attrs,
+            r#" stroke="{}" stroke-opacity="{}" stroke-width="{}""#,
+            color, opacity, stroke.width
+        );
+    }
+    attrs
+}
+
+/// Split a `#rrggbbaa` color into an SVG `#rrggbb` color and an opacity
+///
+/// egui colors are premultiplied, so the color is unmultiplied by the alpha.
+fn svg_color(hex: &str) -> (String, f32) {
+    let channel = |i: usize| {
+        hex.get(1 + 2 * i..3 + 2 * i)
+            .and_then(|c| u8::from_str_radix(c, 16).ok())
+            .unwrap_or(255)
+    };
+    let alpha = channel(3);
+    if alpha == 0 {
+        return ("#000000".to_string(), 0.0);
+    }
+    let unmultiply = |c: u8| ((c as u32 * 255 + alpha as u32 / 2) / alpha as u32).min(255);
+    (
+        format!(
+            "#{:02x}{:02x}{:02x}",
+            unmultiply(channel(0)),
+            unmultiply(channel(1)),
+            unmultiply(channel(2))
+        ),
+        alpha as f32 / 255.0,
+    )
+}
+
+fn intersects(a: &Rect, b: &Rect) -> bool {
+    a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height && b.y < a.y + a.height
+}
+
+fn same_rect(a: &Rect, b: &Rect) -> bool {
+    a.x == b.x && a.y == b.y && a.width == b.width && a.height == b.height
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui_mcp_protocol::{PaintedShape, TextRun};
+
+    fn rect(x: f32, y: f32, width: f32, height: f32) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn text(text: &str, rect: Rect) -> VectorShape {
+        VectorShape::Text {
+            rect,
+            text: text.to_string(),
+            runs: vec![TextRun {
+                text: text.to_string(),
+                rect,
+                color: "#ffffffff".to_string(),
+                font_size: 14.0,
+                font_family: "Proportional".to_string(),
+            }],
+            angle: 0.0,
+        }
+    }
+
+    fn frame(shapes: Vec<(Rect, VectorShape)>) -> FrameShapes {
+        FrameShapes {
+            viewport_id: 0,
+            screen_rect: rect(0.0, 0.0, 100.0, 50.0),
+            pixels_per_point: 1.0,
+            shapes: shapes
+                .into_iter()
+                .map(|(clip_rect, shape)| PaintedShape {
+                    layer_id: 1,
+                    layer_order: "Background".to_string(),
+                    clip_rect,
+                    shape,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_frame_shapes_to_svg() {
+        let screen = rect(0.0, 0.0, 100.0, 50.0);
+        let frame = frame(vec![
+            (
+                screen,
+                VectorShape::Rect {
+                    rect: screen,
+                    corner_radius: 2.0,
+                    fill: Some("#80000080".to_string()),
+                    stroke: None,
+                },
+            ),
+            (screen, text("a < b", rect(8.0, 8.0, 40.0, 14.0))),
+        ]);
+
+        let svg = frame_shapes_to_svg(&frame);
+        assert!(svg.starts_with("<svg"));
+        // Both shapes share one clip path
+        assert_eq!(svg.matches("<clipPath").count(), 1);
+        assert!(svg.contains(r##"fill="#ff0000" fill-opacity="0.5019608""##));
+        assert!(svg.contains(">a &lt; b</text>"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn test_visible_text_skips_clipped_runs() {
+        let frame = frame(vec![
+            (
+                rect(0.0, 0.0, 100.0, 50.0),
+                text("Axis label", rect(10.0, 10.0, 50.0, 14.0)),
+            ),
+            // Scrolled out of its scroll area
+            (
+                rect(0.0, 0.0, 100.0, 20.0),
+                text("Hidden row", rect(10.0, 30.0, 50.0, 14.0)),
+            ),
+        ]);
+
+        let texts = visible_text(&frame, None);
+        assert_eq!(texts.len(), 1);
+        assert_eq!(texts[0]["text"], "Axis label");
+        assert_eq!(texts[0]["center"], json!([35.0, 17.0]));
+        assert_eq!(texts[0]["layer_order"], "Background");
+
+        assert_eq!(visible_text(&frame, Some("AXIS")).len(), 1);
+        assert!(visible_text(&frame, Some("hidden")).is_empty());
+    }
+}
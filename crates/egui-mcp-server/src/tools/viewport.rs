@@ -0,0 +1,38 @@
+//! Viewport tool implementations
+
+use super::{ToolResult, error_response, not_connected_error};
+use crate::ipc_client::IpcClient;
+use serde_json::json;
+
+/// List the viewports (native windows) of the egui application
+pub async fn list_viewports(ipc_client: &IpcClient) -> ToolResult {
+    if !ipc_client.is_socket_available() {
+        return not_connected_error();
+    }
+
+    match ipc_client.list_viewports().await {
+        Ok(viewports) => {
+            let viewports: Vec<_> = viewports
+                .into_iter()
+                .map(|v| {
+                    json!({
+                        "id": v.id.to_string(),
+                        "parent": v.parent.map(|p| p.to_string()),
+                        "is_root": v.is_root,
+                        "title": v.title,
+                        "inner_rect": v.inner_rect,
+                        "outer_rect": v.outer_rect,
+                        "focused": v.focused,
+                        "minimized": v.minimized
+                    })
+                })
+                .collect();
+            json!({
+                "count": viewports.len(),
+                "viewports": viewports
+            })
+            .to_string()
+        }
+        Err(e) => error_response("ipc_error", format!("Failed to list viewports: {}", e)),
+    }
+}
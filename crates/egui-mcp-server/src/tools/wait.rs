@@ -1,7 +1,11 @@
 //! Wait tool implementations
 
-use super::{ToolResult, error_response};
-use crate::constants::{DEFAULT_WAIT_TIMEOUT_MS, WAIT_POLL_INTERVAL_MS};
+use super::{ToolResult, error_response, not_connected_error};
+use crate::constants::{
+    DEFAULT_IDLE_STABLE_FRAMES, DEFAULT_WAIT_TIMEOUT_MS, IDLE_POLL_INTERVAL_MS,
+    IDLE_QUIET_PERIOD_MS, WAIT_POLL_INTERVAL_MS,
+};
+use crate::ipc_client::IpcClient;
 use serde_json::json;
 
 #[cfg(target_os = "linux")]
@@ -151,3 +155,72 @@ pub async fn wait_for_state(
 
 /// Default timeout in milliseconds
 pub const DEFAULT_TIMEOUT_MS: u64 = DEFAULT_WAIT_TIMEOUT_MS;
+
+/// Wait until no repaint has been requested for `stable_frames` consecutive frames
+///
+/// An app that stops repainting altogether is also considered idle once its
+/// last frame was idle and no frame has run for a short quiet period.
+pub async fn wait_for_idle(
+    ipc_client: &IpcClient,
+    stable_frames: Option<u32>,
+    timeout_ms: u64,
+) -> ToolResult {
+    if !ipc_client.is_socket_available() {
+        return not_connected_error();
+    }
+
+    let stable_frames = stable_frames.unwrap_or(DEFAULT_IDLE_STABLE_FRAMES).max(1);
+    let start = std::time::Instant::now();
+    let deadline = start + std::time::Duration::from_millis(timeout_ms);
+
+    loop {
+        let state = match ipc_client.get_idle_state().await {
+            Ok(state) => state,
+            Err(e) => {
+                return error_response("ipc_error", format!("Failed to get idle state: {}", e));
+            }
+        };
+
+        let settled = state.idle_frames >= stable_frames
+            || (state.idle_frames > 0
+                && state.pending_inputs == 0
+                && state
+                    .ms_since_last_frame
+                    .is_some_and(|ms| ms >= IDLE_QUIET_PERIOD_MS));
+        if settled {
+            return json!({
+                "success": true,
+                "idle": true,
+                "idle_frames": state.idle_frames,
+                "frame_count": state.frame_count,
+                "waited_ms": start.elapsed().as_millis() as u64
+            })
+            .to_string();
+        }
+
+        if std::time::Instant::now() >= deadline {
+            let message = if state.frame_count == 0 {
+                format!(
+                    "Timeout after {}ms: no frames recorded. Make sure the egui app calls record_idle_state_sync() in update",
+                    timeout_ms
+                )
+            } else {
+                format!(
+                    "Timeout after {}ms waiting for {} idle frames",
+                    timeout_ms, stable_frames
+                )
+            };
+            return json!({
+                "success": false,
+                "timeout": true,
+                "idle_frames": state.idle_frames,
+                "repaint_requested": state.repaint_requested,
+                "pending_inputs": state.pending_inputs,
+                "message": message
+            })
+            .to_string();
+        }
+
+        tokio::time::sleep(std::time::Duration::from_millis(IDLE_POLL_INTERVAL_MS)).await;
+    }
+}
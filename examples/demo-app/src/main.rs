@@ -5,34 +5,51 @@
 //! - Coordinate-based input (click, hover, drag)
 //! - Keyboard input
 //! - Scroll events
+//! - Multiple viewports (a deferred secondary window)
+//! - Time control (freeze/step/resume)
 //!
 //! Note: UI tree access is handled via AT-SPI on the server side
 //! and doesn't require any special code in the egui application.
 
 use eframe::egui;
-use egui_mcp_client::{McpClient, McpLogLayer, MouseButton, PendingInput};
-use image::ImageEncoder;
+use egui_mcp_client::{McpClient, McpLogLayer, McpProfileLayer, MouseButton, PendingInput};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use tokio::runtime::Runtime;
 use tracing_subscriber::prelude::*;
 
+// Count allocations for get_memory_stats (alloc-tracking feature)
+#[global_allocator]
+static ALLOC: egui_mcp_client::McpAllocator = egui_mcp_client::McpAllocator::system();
+
 fn main() -> eframe::Result<()> {
     // Set up MCP log layer for log capture
     let (mcp_layer, log_buffer) = McpLogLayer::new(1000);
+    // Reloadable filter of the log layer (set_log_filter)
+    let log_filter = mcp_layer.filter();
+    // Set up MCP profile layer for span timings (export_perf_trace)
+    let (profile_layer, profile_buffer) = McpProfileLayer::new(100_000);
 
     tracing_subscriber::registry()
         .with(mcp_layer)
+        .with(profile_layer)
         .with(
             tracing_subscriber::fmt::layer()
                 .with_filter(tracing_subscriber::filter::LevelFilter::WARN),
         )
         .init();
 
-    // Create tokio runtime for async operations
+    // Create tokio runtime for the IPC server (the UI thread uses the sync API)
     let runtime = Arc::new(Runtime::new().expect("Failed to create tokio runtime"));
 
     // Create MCP client with log buffer
-    let mcp_client = McpClient::new().with_log_buffer_sync(log_buffer);
+    let mcp_client = McpClient::new()
+        .with_log_buffer_sync(log_buffer)
+        .with_log_filter_sync(log_filter)
+        .with_profile_buffer_sync(profile_buffer);
+
+    // Record panics to the crash file so the MCP server can report them
+    mcp_client.install_panic_hook();
 
     // Start IPC server in background
     let client_clone = mcp_client.clone();
@@ -43,10 +60,7 @@ fn main() -> eframe::Result<()> {
     });
 
     tracing::info!("Starting demo app with MCP client...");
-    tracing::info!(
-        "Socket path: {:?}",
-        runtime.block_on(mcp_client.socket_path())
-    );
+    tracing::info!("Socket path: {:?}", mcp_client.socket_path_sync());
 
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default().with_inner_size([500.0, 600.0]),
@@ -69,7 +83,8 @@ struct DemoApp {
     counter: i32,
     checkbox_value: bool,
     mcp_client: McpClient,
-    runtime: Arc<Runtime>,
+    /// Keeps the runtime hosting the IPC server alive (the UI thread itself never blocks on it)
+    _runtime: Arc<Runtime>,
     // Input state for visualization
     last_mouse_pos: Option<(f32, f32)>,
     last_click: Option<(f32, f32, String)>,
@@ -81,6 +96,9 @@ struct DemoApp {
     slider_value: f64,
     selected_item: usize,
     multi_line_text: String,
+    // Secondary viewport state (shared with the deferred viewport callback)
+    show_secondary: Arc<AtomicBool>,
+    secondary_clicks: Arc<AtomicU32>,
 }
 
 impl DemoApp {
@@ -88,12 +106,15 @@ impl DemoApp {
         // Enable AccessKit at initialization for AT-SPI integration
         cc.egui_ctx.enable_accesskit();
 
+        // Collect paint statistics (shape counts, repaint causes) for the perf tools
+        mcp_client.install_plugin(&cc.egui_ctx);
+
         Self {
             name: String::new(),
             counter: 0,
             checkbox_value: false,
             mcp_client,
-            runtime,
+            _runtime: runtime,
             last_mouse_pos: None,
             last_click: None,
             last_double_click: None,
@@ -104,38 +125,49 @@ impl DemoApp {
             slider_value: 50.0,
             selected_item: 0,
             multi_line_text: "Hello, World!\nThis is a test.\nEdit me!".to_string(),
+            show_secondary: Arc::new(AtomicBool::new(false)),
+            secondary_clicks: Arc::new(AtomicU32::new(0)),
         }
     }
 
-    /// Encode ColorImage to PNG bytes
-    fn encode_png(image: &egui::ColorImage) -> Option<Vec<u8>> {
-        let mut png_data = Vec::new();
-        let encoder = image::codecs::png::PngEncoder::new(&mut png_data);
-
-        // Convert RGBA pixels to bytes
-        let pixels: Vec<u8> = image
-            .pixels
-            .iter()
-            .flat_map(|c| [c.r(), c.g(), c.b(), c.a()])
-            .collect();
-
-        encoder
-            .write_image(
-                &pixels,
-                image.width() as u32,
-                image.height() as u32,
-                image::ExtendedColorType::Rgba8,
-            )
-            .ok()?;
-
-        Some(png_data)
+    /// Show a deferred secondary viewport for exercising viewport targeting
+    fn show_secondary_viewport(&self, ctx: &egui::Context) {
+        let mcp_client = self.mcp_client.clone();
+        let show_secondary = self.show_secondary.clone();
+        let clicks = self.secondary_clicks.clone();
+        ctx.show_viewport_deferred(
+            egui::ViewportId::from_hash_of("secondary"),
+            egui::ViewportBuilder::default()
+                .with_title("egui-mcp Secondary Window")
+                .with_inner_size([300.0, 200.0]),
+            move |ctx, _class| {
+                mcp_client.handle_screenshot_sync(ctx);
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    ui.heading("Secondary Window");
+                    if ui.button("Click me").clicked() {
+                        clicks.fetch_add(1, Ordering::Relaxed);
+                    }
+                    ui.label(format!("Clicks: {}", clicks.load(Ordering::Relaxed)));
+                });
+
+                mcp_client.handle_inspect_sync(ctx);
+
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    show_secondary.store(false, Ordering::Relaxed);
+                }
+            },
+        );
     }
 }
 
 impl eframe::App for DemoApp {
     /// Hook to inject MCP inputs as egui events before processing
     fn raw_input_hook(&mut self, ctx: &egui::Context, raw_input: &mut egui::RawInput) {
-        let inputs = self.runtime.block_on(self.mcp_client.take_pending_inputs());
+        // Override time while frozen/stepping (deterministic animations)
+        self.mcp_client.apply_time_control_sync(ctx, raw_input);
+
+        let inputs = self.mcp_client.take_viewport_inputs_sync(raw_input);
 
         // Update visualization state before injecting
         for input in &inputs {
@@ -191,31 +223,14 @@ impl eframe::App for DemoApp {
         // This ensures raw_input_hook is called regularly to process pending inputs
         ctx.request_repaint_after(std::time::Duration::from_millis(16)); // ~60fps for responsive MCP input
 
-        // Check if screenshot is requested and send viewport command
-        let screenshot_requested = self
-            .runtime
-            .block_on(self.mcp_client.take_screenshot_request());
-        if screenshot_requested {
-            ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot(egui::UserData::default()));
-        }
+        // Capture the root viewport if a screenshot targets it
+        self.mcp_client.handle_screenshot_sync(ctx);
 
-        // Handle screenshot events
-        ctx.input(|i| {
-            for event in &i.events {
-                if let egui::Event::Screenshot { image, .. } = event {
-                    if let Some(png_data) = Self::encode_png(image) {
-                        tracing::info!("Screenshot captured: {} bytes", png_data.len());
-                        let client = self.mcp_client.clone();
-                        self.runtime.spawn(async move {
-                            client.set_screenshot(png_data).await;
-                        });
-                    } else {
-                        tracing::error!("Failed to encode screenshot as PNG");
-                    }
-                }
-            }
-        });
+        if self.show_secondary.load(Ordering::Relaxed) {
+            self.show_secondary_viewport(ctx);
+        }
 
+        let _span = tracing::info_span!("demo_ui").entered();
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.heading("egui-mcp Demo");
 
@@ -242,6 +257,14 @@ impl eframe::App for DemoApp {
                 self.checkbox_value = false;
             }
 
+            let mut show_secondary = self.show_secondary.load(Ordering::Relaxed);
+            if ui
+                .checkbox(&mut show_secondary, "Show secondary window")
+                .changed()
+            {
+                self.show_secondary.store(show_secondary, Ordering::Relaxed);
+            }
+
             ui.separator();
             ui.label(format!(
                 "Hello, {}!",
@@ -344,10 +367,16 @@ impl eframe::App for DemoApp {
         });
 
         // Draw MCP highlights on top of UI
-        let highlights = self.runtime.block_on(self.mcp_client.get_highlights());
+        let highlights = self.mcp_client.get_highlights_sync();
         egui_mcp_client::draw_highlights(ctx, &highlights);
 
         // Record frame for performance metrics (1行だけ！)
-        self.runtime.block_on(self.mcp_client.record_frame_auto());
+        self.mcp_client.record_frame_auto_sync();
+
+        // Record repaint activity for wait_for_idle
+        self.mcp_client.record_idle_state_sync(ctx);
+
+        // Answer inspect_context requests after all widgets were added
+        self.mcp_client.handle_inspect_sync(ctx);
     }
 }